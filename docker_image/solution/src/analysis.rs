@@ -0,0 +1,873 @@
+// src/analysis.rs
+// Graph analyses over the empty cells of the board.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::board::{Board, Owner};
+use crate::grid::{read, write, Grid};
+use crate::scratch;
+
+const DIRS: &[(isize, isize)] = &[(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+/// For every empty cell, how many empty cells the opponent would lose access
+/// to if that cell were taken. Non-zero values are the articulation points
+/// (chokepoints) of the empty-cell graph on the opponent's way through.
+///
+/// The opponent is modelled as one extra vertex joined to every empty cell
+/// next to one of their cells, and Tarjan's algorithm is run from it: when a
+/// DFS child can't reach above a cell, its whole subtree hangs off that cell.
+pub fn chokepoints(board: &Board) -> Grid<usize> {
+    let rows = board.rows();
+    let cols = board.cols().max(1);
+    let n = rows * cols;
+    let root = n;
+
+    let empty = |v: usize| board.is(v / cols, v % cols, Owner::Empty);
+    let neighbor = |v: usize, dir: usize| -> Option<usize> {
+        let &(dy, dx) = DIRS.get(dir)?;
+        board
+            .offset(v / cols, v % cols, dy, dx)
+            .map(|(ny, nx)| ny * cols + nx)
+    };
+    let touches_opponent = |v: usize| {
+        board
+            .neighbors(v / cols, v % cols)
+            .any(|(ny, nx)| board.is(ny, nx, Owner::Opponent))
+    };
+
+    let mut entries = scratch::take::<usize>();
+    entries.extend((0..n).filter(|&v| empty(v) && touches_opponent(v)));
+
+    let mut cut = Grid::new(rows, cols, 0usize);
+    let mut disc = scratch::filled(n + 1, usize::MAX);
+    let mut low = scratch::filled(n + 1, 0usize);
+    let mut size = scratch::filled(n + 1, 1usize);
+    let mut timer = 0;
+
+    // Frames are (vertex, parent, next neighbour to look at). Cells have the
+    // four directions plus, for entry cells, the edge back to the root.
+    let mut stack = scratch::take::<(usize, usize, usize)>();
+    stack.push((root, usize::MAX, 0));
+    write(&mut disc, root, timer);
+    write(&mut low, root, timer);
+
+    while let Some(&mut (v, parent, ref mut next)) = stack.last_mut() {
+        let step = *next;
+        *next += 1;
+
+        let w = if v == root {
+            match entries.get(step) {
+                Some(&w) => Some(w),
+                None => {
+                    stack.pop();
+                    continue;
+                }
+            }
+        } else if step < DIRS.len() {
+            neighbor(v, step).filter(|&w| empty(w))
+        } else if step == DIRS.len() {
+            touches_opponent(v).then_some(root)
+        } else {
+            // Done with v: fold it into its parent
+            stack.pop();
+            if parent != root {
+                let (low_v, size_v) = (read(&low, v, 0), read(&size, v, 0));
+                let (low_parent, size_parent) = (read(&low, parent, 0), read(&size, parent, 0));
+                write(&mut low, parent, low_parent.min(low_v));
+                write(&mut size, parent, size_parent + size_v);
+                if low_v >= read(&disc, parent, 0) {
+                    let (py, px) = (parent / cols, parent % cols);
+                    let total = cut.get(py, px).unwrap_or(0) + size_v;
+                    cut.set(py, px, total);
+                }
+            }
+            continue;
+        };
+
+        let w = match w {
+            Some(w) if w != parent => w,
+            _ => continue,
+        };
+
+        let found = read(&disc, w, 0);
+        if found == usize::MAX {
+            timer += 1;
+            write(&mut disc, w, timer);
+            write(&mut low, w, timer);
+            stack.push((w, v, 0));
+        } else {
+            let low_v = read(&low, v, 0);
+            write(&mut low, v, low_v.min(found));
+        }
+    }
+
+    cut
+}
+
+/// Cells that are mine or that I reach strictly before the opponent, and the
+/// same for them. Ties and cells nobody can reach count for neither.
+pub fn voronoi_areas(board: &Board) -> (usize, usize) {
+    let mine = board.distance_field(Owner::Me);
+    let theirs = board.distance_field(Owner::Opponent);
+
+    let mut my_area = 0;
+    let mut their_area = 0;
+    for ((y, x), m) in mine.iter() {
+        let t = theirs.get(y, x).unwrap_or(usize::MAX);
+        if m < t {
+            my_area += 1;
+        } else if t < m {
+            their_area += 1;
+        }
+    }
+
+    (my_area, their_area)
+}
+
+/// Cells of our territory (held, or reached strictly before them) outside
+/// its largest 4-connected part. The parts are kept apart by cells they
+/// reach at least as soon as we do, so each can be contained on its own.
+pub fn detached_territory(board: &Board, mine: &Grid<usize>, theirs: &Grid<usize>) -> usize {
+    let ours = |y: usize, x: usize| {
+        mine.get(y, x).unwrap_or(usize::MAX) < theirs.get(y, x).unwrap_or(usize::MAX)
+    };
+    let mut seen = Grid::new(board.rows(), board.cols(), false);
+    let mut queue = Vec::new();
+    let (mut total, mut largest) = (0, 0);
+    for ((y, x), _) in mine.iter() {
+        if !ours(y, x) || seen.get(y, x) != Some(false) {
+            continue;
+        }
+        seen.set(y, x, true);
+        queue.push((y, x));
+        let mut size = 0;
+        while let Some((y, x)) = queue.pop() {
+            size += 1;
+            for (ny, nx) in board.neighbors(y, x) {
+                if ours(ny, nx) && seen.get(ny, nx) == Some(false) {
+                    seen.set(ny, nx, true);
+                    queue.push((ny, nx));
+                }
+            }
+        }
+        total += size;
+        largest = largest.max(size);
+    }
+    total - largest
+}
+
+/// How far either side of the even split the min cut in `safe_areas` may
+/// wander to find a shorter wall.
+const SAFE_BAND: usize = 3;
+
+/// Cells each side can count on, mine first: what it holds, plus the empty
+/// cells on its side of the cheapest wall between the two.
+///
+/// Cells well inside either side's reach (more than `SAFE_BAND` steps ahead
+/// of the other) are that side's outright. Across the band between them a
+/// minimum vertex cut is found with unit-capacity max flow: the fewest cells
+/// that separate the two sides. Band cells on our side of it are ours, on
+/// theirs theirs; the cut itself is still up for grabs. Unlike
+/// `voronoi_areas`, a narrow neck just past the even split gets its far side
+/// counted for whoever can close it.
+pub fn safe_areas(board: &Board) -> (usize, usize) {
+    safe_areas_from(board, &board.distance_field(Owner::Me), &board.distance_field(Owner::Opponent))
+}
+
+/// `safe_areas` with both sides' distance fields already at hand.
+pub fn safe_areas_from(board: &Board, mine: &Grid<usize>, theirs: &Grid<usize>) -> (usize, usize) {
+    let cols = board.cols();
+
+    // Which side each cell is on: -1 ours, 1 theirs, 0 in the band
+    let side = |y: usize, x: usize| -> Option<i8> {
+        match board.get(y, x)? {
+            Owner::Me => Some(-1),
+            Owner::Opponent => Some(1),
+            Owner::Unknown => None,
+            Owner::Empty => {
+                let m = mine.get(y, x).unwrap_or(usize::MAX);
+                let t = theirs.get(y, x).unwrap_or(usize::MAX);
+                if m == usize::MAX && t == usize::MAX {
+                    None
+                } else if m.saturating_add(SAFE_BAND) < t {
+                    Some(-1)
+                } else if t.saturating_add(SAFE_BAND) < m {
+                    Some(1)
+                } else {
+                    Some(0)
+                }
+            }
+        }
+    };
+
+    let mut index = scratch::filled(board.rows() * cols, usize::MAX);
+    let mut band = scratch::take::<(usize, usize)>();
+    let (mut my_area, mut their_area) = (0, 0);
+    for ((y, x), _) in board.iter() {
+        match side(y, x) {
+            Some(-1) => my_area += 1,
+            Some(1) => their_area += 1,
+            Some(_) => {
+                write(&mut index, y * cols + x, band.len());
+                band.push((y, x));
+            }
+            None => {}
+        }
+    }
+    if band.is_empty() {
+        return (my_area, their_area);
+    }
+
+    // Each band cell is an edge in -> out of capacity one, so cutting it
+    // costs one; everything else can't be cut
+    let (source, sink) = (2 * band.len(), 2 * band.len() + 1);
+    let unlimited = band.len() + 1;
+    let mut net = Network::new(sink + 1);
+    for (k, &(y, x)) in band.iter().enumerate() {
+        net.link(2 * k, 2 * k + 1, 1);
+        for (ny, nx) in board.neighbors(y, x) {
+            match side(ny, nx) {
+                Some(-1) => net.link(source, 2 * k, unlimited),
+                Some(1) => net.link(2 * k + 1, sink, unlimited),
+                Some(_) => net.link(2 * k + 1, 2 * read(&index, ny * cols + nx, 0), unlimited),
+                None => {}
+            }
+        }
+    }
+    let ours = net.min_cut(source, sink);
+
+    for k in 0..band.len() {
+        if read(&ours, 2 * k + 1, false) {
+            my_area += 1;
+        } else if !read(&ours, 2 * k, true) {
+            their_area += 1;
+        }
+    }
+    (my_area, their_area)
+}
+
+/// A flow network for `safe_areas`, as edge lists with each edge's reverse
+/// right after it.
+struct Network {
+    first: scratch::Buffer<usize>,
+    to: scratch::Buffer<usize>,
+    capacity: scratch::Buffer<usize>,
+    next: scratch::Buffer<usize>,
+}
+
+impl Network {
+    fn new(nodes: usize) -> Self {
+        Network {
+            first: scratch::filled(nodes, usize::MAX),
+            to: scratch::take(),
+            capacity: scratch::take(),
+            next: scratch::take(),
+        }
+    }
+
+    fn link(&mut self, from: usize, to: usize, capacity: usize) {
+        for (a, b, c) in [(from, to, capacity), (to, from, 0)] {
+            self.to.push(b);
+            self.capacity.push(c);
+            self.next.push(read(&self.first, a, usize::MAX));
+            write(&mut self.first, a, self.to.len() - 1);
+        }
+    }
+
+    /// Push flow along shortest augmenting paths until none is left, then
+    /// mark the nodes still reachable from `source`: its side of a minimum
+    /// cut.
+    fn min_cut(&mut self, source: usize, sink: usize) -> scratch::Buffer<bool> {
+        let nodes = self.first.len();
+        let mut via = scratch::filled(nodes, usize::MAX);
+        let mut reached = scratch::filled(nodes, false);
+        let mut queue = scratch::take::<usize>();
+        loop {
+            via.iter_mut().for_each(|v| *v = usize::MAX);
+            reached.iter_mut().for_each(|r| *r = false);
+            queue.clear();
+            queue.push(source);
+            write(&mut reached, source, true);
+            let mut head = 0;
+            while let Some(&node) = queue.get(head) {
+                head += 1;
+                // The last edge links on to usize::MAX, which ends the list
+                let mut edge = read(&self.first, node, usize::MAX);
+                while let (Some(&to), Some(&capacity), Some(&next)) =
+                    (self.to.get(edge), self.capacity.get(edge), self.next.get(edge))
+                {
+                    if capacity > 0 && !read(&reached, to, true) {
+                        write(&mut reached, to, true);
+                        write(&mut via, to, edge);
+                        queue.push(to);
+                    }
+                    edge = next;
+                }
+                if read(&reached, sink, false) {
+                    break;
+                }
+            }
+            if !read(&reached, sink, false) {
+                return reached;
+            }
+            // Every path carries one: the band cells on it cap it there
+            let mut node = sink;
+            while node != source {
+                let edge = read(&via, node, usize::MAX);
+                let Some(&from) = self.to.get(edge ^ 1) else {
+                    return reached;
+                };
+                if let Some(forward) = self.capacity.get_mut(edge) {
+                    *forward -= 1;
+                }
+                if let Some(backward) = self.capacity.get_mut(edge ^ 1) {
+                    *backward += 1;
+                }
+                node = from;
+            }
+        }
+    }
+}
+
+/// Empty cells about as far from us as from the opponent (within one step,
+/// so odd gaps still have a line). Whoever gets there first owns the split.
+pub fn midline(board: &Board) -> Vec<(usize, usize)> {
+    let mine = board.distance_field(Owner::Me);
+    let theirs = board.distance_field(Owner::Opponent);
+
+    mine.iter()
+        .filter(|&((y, x), m)| {
+            let t = theirs.get(y, x).unwrap_or(usize::MAX);
+            m != usize::MAX && t != usize::MAX && m > 0 && t > 0 && m.abs_diff(t) <= 1
+        })
+        .map(|(pos, _)| pos)
+        .collect()
+}
+
+/// If no empty cell can be reached by both players, the board is partitioned
+/// and the final score is capped: returns the most cells each side can still
+/// end up with. Returns `None` while any region is still contested.
+pub fn partition_bounds(board: &Board) -> Option<(usize, usize)> {
+    let mine = board.distance_field(Owner::Me);
+    let theirs = board.distance_field(Owner::Opponent);
+
+    let mut my_max = 0;
+    let mut their_max = 0;
+    for ((y, x), owner) in board.iter() {
+        let m = mine.get(y, x).unwrap_or(usize::MAX);
+        let t = theirs.get(y, x).unwrap_or(usize::MAX);
+        match owner {
+            Owner::Me => my_max += 1,
+            Owner::Opponent => their_max += 1,
+            Owner::Empty if m != usize::MAX && t != usize::MAX => return None,
+            Owner::Empty if m != usize::MAX => my_max += 1,
+            Owner::Empty if t != usize::MAX => their_max += 1,
+            Owner::Empty | Owner::Unknown => {}
+        }
+    }
+
+    Some((my_max, their_max))
+}
+
+/// The fewest and most cells each side can end up with, as
+/// `((my_min, my_max), (their_min, their_max))`: a side keeps its cells and
+/// the empty cells only it can reach, and at most gets every empty cell it
+/// can reach at all. On a partitioned board the two are the same (see
+/// `partition_bounds`).
+pub fn score_bounds(board: &Board) -> ((usize, usize), (usize, usize)) {
+    let mine = board.distance_field(Owner::Me);
+    let theirs = board.distance_field(Owner::Opponent);
+
+    let (mut my_min, mut my_max, mut their_min, mut their_max) = (0, 0, 0, 0);
+    for ((y, x), owner) in board.iter() {
+        let m = mine.get(y, x).is_some_and(|d| d != usize::MAX);
+        let t = theirs.get(y, x).is_some_and(|d| d != usize::MAX);
+        match owner {
+            Owner::Me => (my_min, my_max) = (my_min + 1, my_max + 1),
+            Owner::Opponent => (their_min, their_max) = (their_min + 1, their_max + 1),
+            Owner::Empty => {
+                my_min += usize::from(m && !t);
+                my_max += usize::from(m);
+                their_min += usize::from(t && !m);
+                their_max += usize::from(t);
+            }
+            Owner::Unknown => {}
+        }
+    }
+
+    ((my_min, my_max), (their_min, their_max))
+}
+
+/// How far every cell sits behind the front line: the distance, through any
+/// cells, to the nearest empty cell the opponent can still reach. Piece
+/// cells spent deep behind it gain nothing we don't already have.
+/// Without a contested cell everything is `usize::MAX`.
+pub fn frontier_depth(board: &Board, dead: &Grid<bool>) -> Grid<usize> {
+    let mut depth = Grid::new(board.rows(), board.cols(), usize::MAX);
+    let mut queue = scratch::take::<(usize, usize, usize)>();
+
+    for ((y, x), owner) in board.iter() {
+        if owner == Owner::Empty && dead.get(y, x) == Some(false) {
+            depth.set(y, x, 0);
+            queue.push((y, x, 0));
+        }
+    }
+
+    let mut head = 0;
+    while let Some(&(y, x, d)) = queue.get(head) {
+        head += 1;
+        for (ny, nx) in board.neighbors(y, x) {
+            if depth.get(ny, nx) == Some(usize::MAX) {
+                depth.set(ny, nx, d + 1);
+                queue.push((ny, nx, d + 1));
+            }
+        }
+    }
+
+    depth
+}
+
+/// For every empty cell, how many turns sooner we reach its connected empty
+/// region than the opponent does (negative if they're first). Arrival is the
+/// BFS distance to the region's nearest cell, at `step` cells per turn.
+/// `None` where either side can't reach the region at all.
+pub fn region_race(board: &Board, step: usize) -> Grid<Option<i64>> {
+    let mine = board.distance_field(Owner::Me);
+    let theirs = board.distance_field(Owner::Opponent);
+    let turns = |d: usize| d.div_ceil(step.max(1)) as i64;
+
+    let cols = board.cols();
+    let mut lead = Grid::new(board.rows(), cols, None);
+    let mut seen = scratch::filled(board.rows() * cols, false);
+    let mut region = scratch::take::<(usize, usize)>();
+    let mut stack = scratch::take::<(usize, usize)>();
+    for ((y, x), owner) in board.iter() {
+        if owner != Owner::Empty || read(&seen, y * cols + x, true) {
+            continue;
+        }
+
+        // Flood the region, noting each side's earliest arrival
+        region.clear();
+        region.push((y, x));
+        stack.push((y, x));
+        write(&mut seen, y * cols + x, true);
+        let (mut my_first, mut their_first) = (usize::MAX, usize::MAX);
+        while let Some((cy, cx)) = stack.pop() {
+            my_first = my_first.min(mine.get(cy, cx).unwrap_or(usize::MAX));
+            their_first = their_first.min(theirs.get(cy, cx).unwrap_or(usize::MAX));
+            for (ny, nx) in board.neighbors(cy, cx) {
+                if board.is(ny, nx, Owner::Empty) && !read(&seen, ny * cols + nx, true) {
+                    write(&mut seen, ny * cols + nx, true);
+                    region.push((ny, nx));
+                    stack.push((ny, nx));
+                }
+            }
+        }
+
+        if my_first != usize::MAX && their_first != usize::MAX {
+            let value = Some(turns(their_first) - turns(my_first));
+            for &(ry, rx) in region.iter() {
+                lead.set(ry, rx, value);
+            }
+        }
+    }
+
+    lead
+}
+
+/// How far past a placement's rim to look for a way round it.
+const SPLIT_MARGIN: usize = 2;
+
+/// Empty cells a placement on `covered` newly cuts off from the opponent:
+/// pieces of the empty region it splits away from everything they can
+/// reach, less what was already out of their reach (`dead`).
+///
+/// Most placements split nothing: the live empty cells around them still
+/// join up close by, so every way through them has a way round. Only when
+/// they don't is each side flood-filled, stopping as soon as it meets them.
+/// The fill goes best-first down `toward` (their distance field before the
+/// placement), so a side that still meets them does so in about as many
+/// steps as they are away; a later fill that runs into one that met them
+/// has met them too. `covered` must be sorted, as a placement's cells are.
+pub fn sealed_by(board: &Board, covered: &[(usize, usize)], dead: &Grid<bool>, toward: &Grid<usize>) -> usize {
+    debug_assert!(covered.is_sorted());
+    let open = |y: usize, x: usize| board.is(y, x, Owner::Empty) && covered.binary_search(&(y, x)).is_err();
+
+    let mut rim = scratch::take::<(usize, usize)>();
+    for &(y, x) in covered {
+        rim.extend(
+            board
+                .neighbors(y, x)
+                .filter(|&(ny, nx)| open(ny, nx) && dead.get(ny, nx) == Some(false)),
+        );
+    }
+    rim.sort_unstable();
+    rim.dedup();
+    if rim.len() < 2 {
+        return 0;
+    }
+
+    // Flood the rim within a window round the placement
+    let top = rim.iter().map(|c| c.0).min().unwrap_or(0).saturating_sub(SPLIT_MARGIN);
+    let left = rim.iter().map(|c| c.1).min().unwrap_or(0).saturating_sub(SPLIT_MARGIN);
+    let bottom = (rim.iter().map(|c| c.0).max().unwrap_or(0) + SPLIT_MARGIN).min(board.rows() - 1);
+    let right = (rim.iter().map(|c| c.1).max().unwrap_or(0) + SPLIT_MARGIN).min(board.cols() - 1);
+    let width = right - left + 1;
+    let mut near = scratch::filled((bottom - top + 1) * width, false);
+    let at = |(y, x): (usize, usize)| (y - top) * width + x - left;
+    let mut stack = scratch::take::<(usize, usize)>();
+    stack.extend(rim.first().copied());
+    for &start in stack.iter() {
+        write(&mut near, at(start), true);
+    }
+    while let Some((y, x)) = stack.pop() {
+        for (ny, nx) in board.neighbors(y, x) {
+            let inside = (top..=bottom).contains(&ny) && (left..=right).contains(&nx);
+            if inside && open(ny, nx) && !read(&near, at((ny, nx)), true) {
+                write(&mut near, at((ny, nx)), true);
+                stack.push((ny, nx));
+            }
+        }
+    }
+    if rim.iter().all(|&cell| read(&near, at(cell), false)) {
+        return 0;
+    }
+
+    // Something may be cut off: flood each side until it meets them
+    // Which fill each cell was reached by, and how that fill ended
+    const UNSEEN: usize = 0;
+    const FILLING: usize = 1;
+    const THEIRS: usize = 2;
+    const SEALED: usize = 3;
+    let cols = board.cols();
+    let mut seen = scratch::filled(board.rows() * cols, UNSEEN);
+    let mut side = scratch::take::<(usize, usize)>();
+    // The heap's vector comes from the pool too, and goes back below
+    let mut queued = scratch::take::<Reverse<(usize, (usize, usize))>>();
+    let mut queue = BinaryHeap::from(std::mem::take(&mut *queued));
+    let mut sealed = 0;
+    for &(ry, rx) in rim.iter() {
+        if read(&seen, ry * cols + rx, SEALED) != UNSEEN {
+            continue;
+        }
+        side.clear();
+        queue.clear();
+        queue.push(Reverse((toward.get(ry, rx).unwrap_or(usize::MAX), (ry, rx))));
+        write(&mut seen, ry * cols + rx, FILLING);
+        let mut theirs = false;
+        while let Some(Reverse((_, (y, x)))) = queue.pop() {
+            side.push((y, x));
+            if board.neighbors(y, x).any(|(ny, nx)| board.is(ny, nx, Owner::Opponent)) {
+                theirs = true;
+                break;
+            }
+            for (ny, nx) in board.neighbors(y, x) {
+                if !open(ny, nx) {
+                    continue;
+                }
+                match read(&seen, ny * cols + nx, SEALED) {
+                    UNSEEN => {
+                        write(&mut seen, ny * cols + nx, FILLING);
+                        queue.push(Reverse((toward.get(ny, nx).unwrap_or(usize::MAX), (ny, nx))));
+                    }
+                    THEIRS => theirs = true,
+                    _ => {}
+                }
+            }
+            if theirs {
+                break;
+            }
+        }
+        let ending = if theirs { THEIRS } else { SEALED };
+        side.extend(queue.drain().map(|Reverse((_, cell))| cell));
+        for &(y, x) in side.iter() {
+            write(&mut seen, y * cols + x, ending);
+        }
+        if !theirs {
+            sealed += side.iter().filter(|&&(y, x)| dead.get(y, x) == Some(false)).count();
+        }
+    }
+    *queued = queue.into_vec();
+    sealed
+}
+
+/// The shortest run of empty cells from next to ours to next to theirs,
+/// found with A* toward `target` (one of their cells). Walls and obstacles
+/// bend it where a straight line toward them would run into them. Empty if
+/// they can't be reached.
+pub fn corridor(board: &Board, target: (usize, usize)) -> Vec<(usize, usize)> {
+    let guess = |(y, x): (usize, usize)| y.abs_diff(target.0) + x.abs_diff(target.1);
+    let arrived = |(y, x): (usize, usize)| board.neighbors(y, x).any(|(ny, nx)| board.is(ny, nx, Owner::Opponent));
+
+    let mut cost = Grid::new(board.rows(), board.cols(), usize::MAX);
+    let mut came_from = Grid::new(board.rows(), board.cols(), None);
+    let mut open = BinaryHeap::new();
+    for (y, x) in board.coords(Owner::Me) {
+        for (ny, nx) in board.neighbors(y, x) {
+            if board.is(ny, nx, Owner::Empty) && cost.get(ny, nx) != Some(1) {
+                cost.set(ny, nx, 1);
+                open.push(Reverse((1 + guess((ny, nx)), 1, (ny, nx))));
+            }
+        }
+    }
+
+    while let Some(Reverse((_, steps, cell))) = open.pop() {
+        if cost.get(cell.0, cell.1) != Some(steps) {
+            continue;
+        }
+        if arrived(cell) {
+            let mut path = vec![cell];
+            let mut at = cell;
+            while let Some(Some(previous)) = came_from.get(at.0, at.1) {
+                path.push(previous);
+                at = previous;
+            }
+            path.reverse();
+            return path;
+        }
+        for (ny, nx) in board.neighbors(cell.0, cell.1) {
+            if board.is(ny, nx, Owner::Empty) && cost.get(ny, nx).is_some_and(|c| steps + 1 < c) {
+                cost.set(ny, nx, steps + 1);
+                came_from.set(ny, nx, Some(cell));
+                open.push(Reverse((steps + 1 + guess((ny, nx)), steps + 1, (ny, nx))));
+            }
+        }
+    }
+    Vec::new()
+}
+
+/// Groups of enemy border cells smaller than this aren't a front.
+const MIN_FRONT: usize = 3;
+/// Fronts past the biggest few are scraps not worth a distance field each.
+const MAX_FRONTS: usize = 4;
+
+/// The opponent's fronts: their cells that still border an empty cell,
+/// grouped into 8-connected clusters, up to `MAX_FRONTS` of the biggest.
+/// Walls and our own cells split their border into separate fronts.
+pub fn fronts(board: &Board) -> Vec<Vec<(usize, usize)>> {
+    let cols = board.cols();
+    let border = |y: usize, x: usize| {
+        board.is(y, x, Owner::Opponent) && board.neighbors(y, x).any(|(ny, nx)| board.is(ny, nx, Owner::Empty))
+    };
+
+    let mut seen = scratch::filled(board.rows() * cols, false);
+    let mut stack = scratch::take::<(usize, usize)>();
+    let mut fronts = Vec::new();
+    for ((y, x), _) in board.iter() {
+        if read(&seen, y * cols + x, true) || !border(y, x) {
+            continue;
+        }
+        let mut front = vec![(y, x)];
+        write(&mut seen, y * cols + x, true);
+        stack.push((y, x));
+        while let Some((cy, cx)) = stack.pop() {
+            for (dy, dx) in [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)] {
+                if let Some((ny, nx)) = board.offset(cy, cx, dy, dx) {
+                    if !read(&seen, ny * cols + nx, true) && border(ny, nx) {
+                        write(&mut seen, ny * cols + nx, true);
+                        front.push((ny, nx));
+                        stack.push((ny, nx));
+                    }
+                }
+            }
+        }
+        if front.len() >= MIN_FRONT {
+            fronts.push(front);
+        }
+    }
+    fronts.sort_by_key(|front| std::cmp::Reverse(front.len()));
+    fronts.truncate(MAX_FRONTS);
+    fronts
+}
+
+/// Influence of each player over every cell: `decay^d` for our distance `d`
+/// minus the same for theirs, so +1 is ours, -1 theirs and 0 up for grabs.
+/// Cells a player can't reach get nothing from them.
+pub fn influence_map(board: &Board, decay: f64) -> Grid<f64> {
+    let mine = board.distance_field(Owner::Me);
+    let theirs = board.distance_field(Owner::Opponent);
+    let pull = |d: Option<usize>| match d {
+        Some(d) if d != usize::MAX => decay.powi(d.min(i32::MAX as usize) as i32),
+        _ => 0.0,
+    };
+
+    let mut influence = Grid::new(board.rows(), board.cols(), 0.0);
+    for ((y, x), owner) in board.iter() {
+        let value = match owner {
+            Owner::Me => 1.0,
+            Owner::Opponent => -1.0,
+            Owner::Empty => pull(mine.get(y, x)) - pull(theirs.get(y, x)),
+            Owner::Unknown => 0.0,
+        };
+        influence.set(y, x, value);
+    }
+    influence
+}
+
+/// Empty neighbours an anchor needs to count as live: with only one, most
+/// pieces have nowhere to go.
+pub const LIVE_ANCHOR_EMPTY: usize = 2;
+
+pub fn empty_neighbors(board: &Board, y: usize, x: usize) -> usize {
+    board
+        .neighbors(y, x)
+        .filter(|&(ny, nx)| board.is(ny, nx, Owner::Empty))
+        .count()
+}
+
+/// How many of `who`'s cells have at least `LIVE_ANCHOR_EMPTY` empty
+/// neighbours. A cheap stand-in for how many placements they have left;
+/// once it hits zero they're nearly out of moves.
+pub fn live_anchors(board: &Board, who: Owner) -> usize {
+    board
+        .coords(who)
+        .into_iter()
+        .filter(|&(y, x)| empty_neighbors(board, y, x) >= LIVE_ANCHOR_EMPTY)
+        .count()
+}
+
+/// Empty cells one more piece `reach` cells across could cover from one of
+/// our cells: those within `reach - 1` steps of it, along both axes. What
+/// next turn's placements can get to, assuming an average piece.
+pub fn placeable_next(board: &Board, reach: usize) -> Grid<bool> {
+    let mut mine = Grid::new(board.rows(), board.cols(), false);
+    for (y, x) in board.coords(Owner::Me) {
+        mine.set(y, x, true);
+    }
+    let mut placeable = dilate(&mine, reach.saturating_sub(1));
+    for ((y, x), owner) in board.iter() {
+        if owner != Owner::Empty {
+            placeable.set(y, x, false);
+        }
+    }
+    placeable
+}
+
+/// Every cell within `span` steps of a marked one along both axes: a
+/// square of side `2 * span + 1` around each. Done a row and then a column
+/// at a time with running counts, so a wide span costs no more than a
+/// narrow one.
+pub fn dilate(marked: &Grid<bool>, span: usize) -> Grid<bool> {
+    let (rows, cols) = (marked.rows(), marked.cols());
+    let mut cells = scratch::take::<bool>();
+    cells.extend(marked.iter().map(|(_, on)| on));
+    dilate_cells(&mut cells, rows, cols, span);
+    let mut dilated = Grid::new(rows, cols, false);
+    for (i, &near) in cells.iter().enumerate() {
+        dilated.set(i / cols, i % cols, near);
+    }
+    dilated
+}
+
+/// `dilate` in place, on a `rows` x `cols` window kept row by row, for
+/// callers that keep their marks in a scratch buffer. Anything but
+/// `rows * cols` flags is left alone.
+pub fn dilate_cells(cells: &mut [bool], rows: usize, cols: usize, span: usize) {
+    if cells.len() != rows * cols {
+        return;
+    }
+    let mut before = scratch::take::<usize>();
+    let mut spread = |cells: &mut [bool], len: usize, index: &dyn Fn(usize) -> usize| {
+        before.clear();
+        let mut count = 0;
+        before.push(count);
+        for i in 0..len {
+            count += read(cells, index(i), false) as usize;
+            before.push(count);
+        }
+        for i in 0..len {
+            let near = read(&before, (i + span + 1).min(len), 0) > read(&before, i.saturating_sub(span), 0);
+            write(cells, index(i), near);
+        }
+    };
+    for y in 0..rows {
+        spread(cells, cols, &|x| y * cols + x);
+    }
+    for x in 0..cols {
+        spread(cells, rows, &|y| y * cols + x);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `rows` as seen by player 1: `@` ours, `$` theirs.
+    fn board(rows: &[&str]) -> Board {
+        let mut lines = vec![format!("Anfield {} {}:", rows[0].len(), rows.len())];
+        lines.extend(rows.iter().enumerate().map(|(y, row)| format!("{:03} {}", y, row)));
+        Board::from_anfield_lines(&lines, 1).expect("valid board")
+    }
+
+    fn grid<T: Copy>(rows: &[&[T]], fill: T) -> Grid<T> {
+        Grid::from_rows(rows.iter().map(|row| row.to_vec()).collect(), fill)
+    }
+
+    /// Each cell of a one-wide corridor cuts off everything past it.
+    #[test]
+    fn chokepoints_along_a_corridor() {
+        let board = board(&["$.@@@", "@.@@@", "@...."]);
+        let expected = grid(&[&[0, 5, 0, 0, 0], &[0, 4, 0, 0, 0], &[0, 3, 2, 1, 0]], 0);
+        assert_eq!(chokepoints(&board), expected);
+    }
+
+    /// Around a loop there is always another way.
+    #[test]
+    fn no_chokepoints_around_a_loop() {
+        let board = board(&["$....", ".@@@.", "....."]);
+        assert!(chokepoints(&board).iter().all(|(_, cut)| cut == 0));
+    }
+
+    /// A cell next to theirs is a way in of its own: the cell above it
+    /// cuts nothing off, and it cuts off only what it alone leads to.
+    #[test]
+    fn chokepoints_next_to_them() {
+        let pocket = board(&["$.@", "$.@", "@.@", "@.."]);
+        let expected = grid(&[&[0, 0, 0], &[0, 3, 0], &[0, 2, 0], &[0, 1, 0]], 0);
+        assert_eq!(chokepoints(&pocket), expected);
+
+        let reached = board(&["$.@", "$.@", "$.@", "@.."]);
+        let expected = grid(&[&[0, 0, 0], &[0, 0, 0], &[0, 2, 0], &[0, 1, 0]], 0);
+        assert_eq!(chokepoints(&reached), expected);
+    }
+
+    /// Walled off from each other, each side has its own cells and all
+    /// the empty ones behind them.
+    #[test]
+    fn safe_areas_of_sealed_sides() {
+        let board = board(&["..@$...", "..@$...", "..@$..."]);
+        assert_eq!(safe_areas(&board), (9, 12));
+    }
+
+    /// Before contact, two cells each are well inside one side's reach,
+    /// and the band between takes one cell to wall off. On a line any of
+    /// its cells will do: the first found, nearest us, leaves the band
+    /// past it to them.
+    #[test]
+    fn safe_areas_before_contact() {
+        let board = board(&["@.......$"]);
+        assert_eq!(safe_areas(&board), (3, 5));
+    }
+
+    fn sealed_by_at(board: &Board, covered: &[(usize, usize)]) -> usize {
+        sealed_by(board, covered, &board.dead_zones(), &board.distance_field(Owner::Opponent))
+    }
+
+    /// Filling the one gap in our wall cuts them off from everything
+    /// behind it.
+    #[test]
+    fn sealed_by_closing_the_gap() {
+        let board = board(&["$.@....", "..@....", ".......", "..@...."]);
+        assert_eq!(sealed_by_at(&board, &[(2, 2)]), 16);
+        assert_eq!(sealed_by_at(&board, &[(2, 1), (2, 2)]), 16);
+    }
+
+    /// With a second gap in the wall there is still a way round.
+    #[test]
+    fn sealed_by_with_a_way_round() {
+        let board = board(&["$.@....", "..@....", ".......", "......."]);
+        assert_eq!(sealed_by_at(&board, &[(2, 2)]), 0);
+        assert_eq!(sealed_by_at(&board, &[(2, 1), (2, 2)]), 0);
+    }
+}
@@ -0,0 +1,112 @@
+// src/anytime.rs
+// Iterative deepening on the clock. The one-ply greedy answer comes first
+// and is always kept; then the beam search (see beam.rs) goes one ply
+// deeper at a time while the budget lasts. A pass the clock cuts short is
+// thrown away, so what's returned is always the deepest finished one.
+//
+// The budget defaults to a share of FILLER_DEADLINE_MS, leaving the rest for
+// the parts of a turn that aren't search. The time bank (see timebank.rs)
+// moves some of it from opening turns to the first turns in contact, never
+// past `CEILING_SHARE` of the deadline.
+
+use std::time::{Duration, Instant};
+
+use crate::beam::{BeamConfig, BeamSearch};
+use crate::board::Board;
+use crate::eval::Evaluator;
+use crate::game::Phase;
+use crate::piece::Piece;
+use crate::timebank::{BankConfig, TimeBank};
+use crate::transposition::TableConfig;
+
+/// Share of the deadline spent searching by default. Well short of the
+/// ceiling, so banked time has somewhere to go.
+pub const BUDGET_SHARE: f64 = 0.5;
+
+/// No turn searches past this share of the deadline, bank or not: a ply
+/// only checks the clock when it's done.
+pub const CEILING_SHARE: f64 = 0.9;
+
+#[derive(Clone, Copy, Debug)]
+pub struct AnytimeConfig {
+    /// Time allowed per turn.
+    pub budget: Duration,
+    /// Time no turn may go past.
+    pub ceiling: Duration,
+    pub bank: BankConfig,
+    /// Stop deepening here even with time left.
+    pub max_depth: usize,
+    /// Positions kept per ply, as `BeamConfig::width`.
+    pub width: usize,
+    pub table: TableConfig,
+}
+
+impl Default for AnytimeConfig {
+    fn default() -> Self {
+        AnytimeConfig {
+            budget: Duration::from_millis(1500),
+            ceiling: Duration::from_millis(2700),
+            bank: BankConfig::default(),
+            max_depth: 9,
+            width: BeamConfig::default().width,
+            table: TableConfig::default(),
+        }
+    }
+}
+
+pub struct Anytime {
+    config: AnytimeConfig,
+    beam: BeamSearch,
+    bank: TimeBank,
+}
+
+impl Anytime {
+    pub fn new(config: AnytimeConfig) -> Self {
+        let beam = BeamSearch::new(BeamConfig {
+            width: config.width,
+            depth: config.max_depth,
+            table: config.table,
+        });
+        Anytime {
+            config,
+            beam,
+            bank: TimeBank::new(config.bank),
+        }
+    }
+
+    pub fn with_evaluator(mut self, evaluator: Evaluator) -> Self {
+        self.beam = self.beam.with_evaluator(evaluator);
+        self
+    }
+
+    pub fn bank(&self) -> &TimeBank {
+        &self.bank
+    }
+
+    pub fn choose_move(&mut self, board: &Board, piece: &Piece) -> Option<(usize, usize)> {
+        let started = Instant::now();
+        let phase = Phase::of(board);
+        let budget = self.bank.budget(self.config.budget, phase).min(self.config.ceiling);
+        let deadline = started + budget;
+        self.beam.observe(piece);
+
+        // One ply can't run out of time: there's nothing to cut short
+        let mut best = self.beam.search(board, piece, 1, None).unwrap_or(None);
+        let mut depth = 1;
+        while depth < self.config.max_depth && Instant::now() < deadline {
+            match self.beam.search(board, piece, depth + 1, Some(deadline)) {
+                Ok(found) => {
+                    best = found.or(best);
+                    depth += 1;
+                }
+                Err(_) => break,
+            }
+        }
+
+        let used = started.elapsed();
+        self.bank.settle(self.config.budget, phase, used);
+        debug!("[DEBUG] Anytime search finished depth {} in {:?} of {:?}", depth, used, budget);
+        self.beam.log_table();
+        best
+    }
+}
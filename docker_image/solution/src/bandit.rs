@@ -0,0 +1,73 @@
+// src/bandit.rs
+// Adapting the weights to this opponent on this map, within the game. A
+// few settings of the aggression dial (see `Weights::with_aggression`) are
+// the arms of a bandit: each contact turn plays one, and the next turn's
+// census pays it what we gained less what they gained over the two moves,
+// as a share of both. UCB1 then leans on whichever setting has been paying
+// while still trying the others now and then. The neutral setting goes
+// first, so a short game plays as if there were no bandit.
+
+use crate::tempo::Gain;
+use crate::weights::NEUTRAL_AGGRESSION;
+
+/// The dial settings tried.
+const ARMS: [f32; 3] = [NEUTRAL_AGGRESSION, 0.3, 0.7];
+
+/// UCB1 exploration constant. Rewards run from -1 to 1.
+const EXPLORATION: f64 = 0.5;
+
+#[derive(Default)]
+pub struct Bandit {
+    pulls: [usize; ARMS.len()],
+    rewards: [f64; ARMS.len()],
+    /// The arm played last turn, still waiting for its reward.
+    pending: Option<usize>,
+}
+
+impl Bandit {
+    /// Pay the arm played last turn, if any, for `gain`.
+    pub fn reward(&mut self, gain: Gain) {
+        let Some(arm) = self.pending.take() else {
+            return;
+        };
+        let total = (gain.me + gain.opponent).max(1) as f64;
+        if let (Some(reward), Some(pulls)) = (self.rewards.get_mut(arm), self.pulls.get_mut(arm)) {
+            *reward += (gain.me as f64 - gain.opponent as f64) / total;
+            *pulls += 1;
+        }
+    }
+
+    /// The aggression setting to play this turn: an untried one if any is
+    /// left, else the best by UCB1.
+    pub fn pull(&mut self) -> f32 {
+        let total: usize = self.pulls.iter().sum();
+        let arm = match self.pulls.iter().position(|&n| n == 0) {
+            Some(untried) => untried,
+            None => self
+                .pulls
+                .iter()
+                .zip(&self.rewards)
+                .map(|(&pulls, &reward)| bound(pulls, reward, total))
+                .enumerate()
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map_or(0, |(arm, _)| arm),
+        };
+        self.pending = Some(arm);
+        ARMS.get(arm).copied().unwrap_or(NEUTRAL_AGGRESSION)
+    }
+
+    /// Each setting with its pulls and mean reward, for the debug output.
+    pub fn arms(&self) -> Vec<(f32, usize, f64)> {
+        ARMS.iter()
+            .zip(&self.pulls)
+            .zip(&self.rewards)
+            .map(|((&arm, &pulls), &reward)| (arm, pulls, reward / pulls.max(1) as f64))
+            .collect()
+    }
+}
+
+/// UCB1's upper bound on an arm's mean reward.
+fn bound(pulls: usize, reward: f64, total: usize) -> f64 {
+    let n = pulls as f64;
+    reward / n + EXPLORATION * ((total as f64).ln() / n).sqrt()
+}
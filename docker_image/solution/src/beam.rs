@@ -0,0 +1,197 @@
+// src/beam.rs
+// Beam search over a few plies: a cheaper alternative to full minimax.
+//
+// Our own plies keep the best `width` positions over every placement, the
+// opponent answers each with the MCTS rollout policy, and positions are
+// scored by the evaluator (see eval.rs), the Voronoi share of the board
+// unless a model is loaded. Future pieces are unknown, so
+// every ply after the first uses a piece-size prior: the median-sized piece
+// among the ones we've been dealt recently. Scores are remembered in a
+// transposition table (see transposition.rs), so a board reached by two
+// lines, or again on a deeper pass, is only scored once.
+
+use std::time::Instant;
+
+use crate::board::{Board, Owner};
+use crate::eval::Evaluator;
+use crate::mcts;
+use crate::piece::Piece;
+use crate::rng::Rng;
+use crate::transposition::{Table, TableConfig};
+
+#[derive(Clone, Copy, Debug)]
+pub struct BeamConfig {
+    /// Positions kept after each of our plies.
+    pub width: usize,
+    /// Plies searched, ours and theirs, the current move included.
+    pub depth: usize,
+    pub table: TableConfig,
+}
+
+impl Default for BeamConfig {
+    fn default() -> Self {
+        BeamConfig {
+            width: 6,
+            depth: 3,
+            table: TableConfig::default(),
+        }
+    }
+}
+
+/// Only the most recent pieces feed the prior.
+const PIECE_POOL_SIZE: usize = 32;
+
+/// The deadline passed before a search finished.
+#[derive(Debug)]
+pub struct Expired;
+
+/// A line being searched: the position reached and the move that started it.
+struct Line {
+    board: Board,
+    first: (usize, usize),
+    score: f64,
+}
+
+pub struct BeamSearch {
+    config: BeamConfig,
+    rng: Rng,
+    seen_pieces: Vec<Piece>,
+    table: Table,
+    evaluator: Evaluator,
+}
+
+impl BeamSearch {
+    pub fn new(config: BeamConfig) -> Self {
+        BeamSearch {
+            config,
+            // Only breaks ties in the opponent's replies
+            rng: Rng::new(0xBEA4),
+            seen_pieces: Vec::new(),
+            table: Table::new(config.table),
+            evaluator: Evaluator::Voronoi,
+        }
+    }
+
+    pub fn with_evaluator(mut self, evaluator: Evaluator) -> Self {
+        self.evaluator = evaluator;
+        self
+    }
+
+    pub fn choose_move(&mut self, board: &Board, piece: &Piece) -> Option<(usize, usize)> {
+        self.observe(piece);
+        let chosen = self.search(board, piece, self.config.depth, None).unwrap_or(None);
+        self.log_table();
+        chosen
+    }
+
+    /// How often the table had a board's score this turn.
+    pub fn log_table(&self) {
+        debug!("[DEBUG] Transposition table: {} hits, {} misses", self.table.hits, self.table.misses);
+    }
+
+    /// Start a turn: add `piece` to the ones the prior is drawn from.
+    pub fn observe(&mut self, piece: &Piece) {
+        self.table.next_turn();
+        if self.seen_pieces.len() == PIECE_POOL_SIZE {
+            self.seen_pieces.remove(0);
+        }
+        self.seen_pieces.push(piece.clone());
+    }
+
+    /// The first move of the best line `depth` plies deep, giving up once
+    /// `deadline` passes. `observe` the piece first.
+    pub fn search(
+        &mut self,
+        board: &Board,
+        piece: &Piece,
+        depth: usize,
+        deadline: Option<Instant>,
+    ) -> Result<Option<(usize, usize)>, Expired> {
+        let expired = || deadline.is_some_and(|d| Instant::now() >= d);
+        let roots: Vec<Line> = board
+            .legal_placements(piece, Owner::Me)
+            .into_iter()
+            .map(|first| {
+                let (board, score) = self.place(board, piece, first, Owner::Me, 0);
+                Line { board, first, score }
+            })
+            .collect();
+        let mut beam = self.prune(roots);
+
+        let prior = self.prior(piece);
+        for ply in 1..depth.max(1) {
+            if expired() {
+                return Err(Expired);
+            }
+            beam = if ply % 2 == 1 {
+                beam.into_iter().map(|line| self.reply(line, &prior, ply)).collect()
+            } else {
+                let expanded = beam.into_iter().flat_map(|line| self.expand(line, &prior, ply)).collect();
+                self.prune(expanded)
+            };
+        }
+
+        Ok(beam.iter().max_by(|a, b| a.score.total_cmp(&b.score)).map(|line| line.first))
+    }
+
+    /// Keep the `width` best lines.
+    fn prune(&self, mut lines: Vec<Line>) -> Vec<Line> {
+        lines.sort_by(|a, b| b.score.total_cmp(&a.score));
+        lines.truncate(self.config.width.max(1));
+        lines
+    }
+
+    /// The opponent's answer to `line`, or `line` itself if they can't move.
+    fn reply(&mut self, line: Line, prior: &Piece, ply: usize) -> Line {
+        match mcts::greedy_move(&line.board, prior, Owner::Opponent, &mut self.rng) {
+            Some(at) => {
+                let (board, score) = self.place(&line.board, prior, at, Owner::Opponent, ply);
+                Line { board, score, ..line }
+            }
+            None => line,
+        }
+    }
+
+    /// Every placement of `piece` after `line`; the line itself if there's none.
+    fn expand(&mut self, line: Line, piece: &Piece, ply: usize) -> Vec<Line> {
+        let children: Vec<Line> = line
+            .board
+            .legal_placements(piece, Owner::Me)
+            .into_iter()
+            .map(|at| {
+                let (board, score) = self.place(&line.board, piece, at, Owner::Me, ply);
+                Line { board, first: line.first, score }
+            })
+            .collect();
+
+        if children.is_empty() {
+            vec![line]
+        } else {
+            children
+        }
+    }
+
+    /// `board` with `piece` placed at `at`, and its score, looked up in the
+    /// table first.
+    fn place(&mut self, board: &Board, piece: &Piece, at: (usize, usize), owner: Owner, ply: usize) -> (Board, f64) {
+        let mut next = board.clone();
+        next.place(piece, at.0, at.1, owner);
+        let key = next.zobrist();
+        let score = match self.table.get(key) {
+            Some(score) => score,
+            None => {
+                let score = self.evaluator.score(&next);
+                self.table.put(key, ply, score);
+                score
+            }
+        };
+        (next, score)
+    }
+
+    /// The median-sized recent piece, or `piece` if none was observed.
+    fn prior(&self, piece: &Piece) -> Piece {
+        let mut pieces: Vec<&Piece> = self.seen_pieces.iter().collect();
+        pieces.sort_by_key(|p| p.cells.len());
+        pieces.get(pieces.len() / 2).map_or(piece, |p| p).clone()
+    }
+}
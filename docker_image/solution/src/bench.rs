@@ -0,0 +1,125 @@
+// src/bench.rs
+// `filler bench`: raw decision speed of every strategy on one fixed set of
+// positions, to judge whether a fancier strategy can afford its cost before
+// it plays matches.
+//
+// The positions come from heuristic self-play on the given maps with fixed
+// seeds, sampled evenly over each game, so every run and every strategy
+// sees exactly the same set. The opening book is off, or book moves would
+// make the first turns look free.
+
+use std::fs;
+use std::time::{Duration, Instant};
+
+use crate::board::Board;
+use crate::config::{Config, STRATEGIES};
+use crate::piece::Piece;
+use crate::referee::{Match, PieceSource};
+
+const USAGE: &str = "usage: filler bench <map>... [--positions N] [--seed N] [--strategies a,b,...]";
+
+pub fn run_cli(args: &[String]) -> Result<(), String> {
+    let mut map_paths: Vec<&str> = Vec::new();
+    let mut positions = 30usize;
+    let mut seed: u64 = 1;
+    let mut names: Vec<String> = STRATEGIES.iter().map(|s| s.name.to_string()).collect();
+
+    let mut i = 0;
+    while let Some(arg) = args.get(i) {
+        let value = args.get(i + 1);
+        let number = |name: &str| -> Result<u64, String> {
+            value
+                .and_then(|v| v.parse().ok())
+                .ok_or(format!("{} expects a number", name))
+        };
+        match arg.as_str() {
+            "--positions" => positions = number("--positions")?.max(1) as usize,
+            "--seed" => seed = number("--seed")?,
+            "--strategies" => {
+                let list = value.ok_or("--strategies expects a comma-separated list")?;
+                names = list.split(',').map(|s| s.trim().to_string()).collect();
+            }
+            other => {
+                map_paths.push(other);
+                i += 1;
+                continue;
+            }
+        }
+        i += 2;
+    }
+    if map_paths.is_empty() {
+        return Err(USAGE.to_string());
+    }
+
+    let mut fixtures = Vec::new();
+    for path in &map_paths {
+        let map: Vec<String> = fs::read_to_string(path)
+            .map_err(|e| format!("{}: {}", path, e))?
+            .lines()
+            .map(|l| l.to_string())
+            .collect();
+        fixtures.extend(sample_positions(&map, seed, positions)?);
+    }
+
+    println!("{} positions from {} map(s)", fixtures.len(), map_paths.len());
+    println!("{:<12} {:>10} {:>12} {:>12} {:>12}", "strategy", "decisions", "per second", "mean", "max");
+    for name in &names {
+        if !STRATEGIES.iter().any(|s| s.name == name) {
+            return Err(format!("unknown strategy `{}`", name));
+        }
+        let (total, max) = time_strategy(name, &fixtures);
+        let mean = total / fixtures.len().max(1) as u32;
+        println!(
+            "{:<12} {:>10} {:>12.1} {:>12.2?} {:>12.2?}",
+            name,
+            fixtures.len(),
+            fixtures.len() as f64 / total.as_secs_f64().max(f64::MIN_POSITIVE),
+            mean,
+            max
+        );
+    }
+    Ok(())
+}
+
+/// Up to `count` positions spread evenly over one heuristic self-play game.
+fn sample_positions(map: &[String], seed: u64, count: usize) -> Result<Vec<(Board, Piece)>, String> {
+    let mut players = [1u8, 2].map(|p| heuristic_config().build_game(p));
+    let mut game = Match::new(map, PieceSource::scripted(Vec::new(), seed))?;
+
+    let mut all = Vec::new();
+    while let Some(turn) = game.step(&mut players) {
+        if turn.placed.is_some() {
+            all.push((turn.board, turn.piece));
+        }
+    }
+
+    let stride = all.len().div_ceil(count).max(1);
+    Ok(all.into_iter().step_by(stride).collect())
+}
+
+/// Total and worst decision time of `name` over `fixtures`. Each position
+/// gets a fresh game, so one position's outcome (a resignation, say) can't
+/// change how the next is decided.
+fn time_strategy(name: &str, fixtures: &[(Board, Piece)]) -> (Duration, Duration) {
+    let mut total = Duration::ZERO;
+    let mut max = Duration::ZERO;
+    for (board, piece) in fixtures {
+        let mut config = heuristic_config();
+        config.strategy = name.to_string();
+        let mut game = config.build_game(1);
+
+        let started = Instant::now();
+        game.choose_best_move(board, piece);
+        let elapsed = started.elapsed();
+        total += elapsed;
+        max = max.max(elapsed);
+    }
+    (total, max)
+}
+
+fn heuristic_config() -> Config {
+    let mut config = Config::from_env();
+    config.strategy = "heuristic".to_string();
+    config.book = Some("off".to_string());
+    config
+}
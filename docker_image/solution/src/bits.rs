@@ -0,0 +1,97 @@
+// src/bits.rs
+// One bit per cell, a row at a time, for the placement check. Checking a
+// placement cell by cell costs a bounds check and an owner comparison per
+// piece cell; with each side's cells kept as bit rows alongside the board,
+// a whole piece row is tested against 64 board cells with one AND. Pieces
+// keep their rows as masks (see `Piece::row_masks`) to match.
+
+/// The cells of a `rows` x `cols` board that are set, `words` u64s a row,
+/// lowest bit leftmost.
+#[derive(Clone, Debug, Default)]
+pub struct BitRows {
+    cols: usize,
+    words: usize,
+    bits: Vec<u64>,
+}
+
+impl BitRows {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        let words = cols.div_ceil(64);
+        BitRows {
+            cols,
+            words,
+            bits: vec![0; rows * words],
+        }
+    }
+
+    /// Set or clear (y, x). Off the board nothing changes.
+    pub fn set(&mut self, y: usize, x: usize, on: bool) {
+        let Some(word) = self.bits.get_mut(y * self.words + x / 64).filter(|_| x / 64 < self.words) else {
+            return;
+        };
+        let bit = 1u64 << (x % 64);
+        if on {
+            *word |= bit;
+        } else {
+            *word &= !bit;
+        }
+    }
+
+    /// The 64 cells of row `y` from column `x` on, the first in the lowest
+    /// bit. Cells past the edge of the board read as clear.
+    pub fn window(&self, y: usize, x: usize) -> u64 {
+        let (word, shift) = (x / 64, x % 64);
+        if word >= self.words {
+            return 0;
+        }
+        let at = |w: usize| self.bits.get(y * self.words + w).copied().unwrap_or(0);
+        let low = at(word) >> shift;
+        if shift == 0 || word + 1 >= self.words {
+            low
+        } else {
+            low | at(word + 1) << (64 - shift)
+        }
+    }
+
+    /// How many cells are set.
+    pub fn count(&self) -> usize {
+        self.bits.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// The cells that are set, in scan order.
+    pub fn ones(&self) -> Vec<(usize, usize)> {
+        let mut ones = Vec::new();
+        for (at, &word) in self.bits.iter().enumerate() {
+            let (y, w) = (at / self.words, at % self.words);
+            let mut rest = word;
+            while rest != 0 {
+                ones.push((y, 64 * w + rest.trailing_zeros() as usize));
+                rest &= rest - 1;
+            }
+        }
+        ones
+    }
+
+    /// Words per row.
+    pub fn words(&self) -> usize {
+        self.words
+    }
+
+    /// Cells `64 * w` to `64 * w + 63` of row `y`; clear off the board.
+    pub fn word(&self, y: usize, w: usize) -> u64 {
+        if w >= self.words {
+            return 0;
+        }
+        self.bits.get(y * self.words + w).copied().unwrap_or(0)
+    }
+
+    /// The bits of word `w` that are on the board: all of them but in a
+    /// row's last word.
+    pub fn span(&self, w: usize) -> u64 {
+        match self.cols.saturating_sub(64 * w) {
+            0 => 0,
+            n if n >= 64 => u64::MAX,
+            n => (1 << n) - 1,
+        }
+    }
+}
@@ -1,120 +1,654 @@
-// src/board.rs
-
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub enum Owner {
-    Empty,
-    Me,
-    Opponent,
-}
-
-pub struct Board {
-    pub rows: usize,
-    pub cols: usize,
-    pub cells: Vec<Vec<Owner>>,
-}
-
-impl Board {
-    /// Build a board from the "Anfield" block lines.
-    ///
-    /// The block looks something like:
-    /// Anfield <cols> <rows>:
-    ///     012345...
-    /// 000 ....@...
-    /// 001 ...$....
-    ///
-    /// Player 1 uses '@' / 'a', player 2 uses '$' / 's'.
-    pub fn from_anfield_lines(lines: &[String], my_player: u8) -> Option<Self> {
-        if lines.is_empty() {
-            return None;
-        }
-
-        let mut grid: Vec<Vec<Owner>> = Vec::new();
-        let mut seen_header = false;
-
-        for line in lines {
-            let trimmed = line.trim();
-
-            if trimmed.is_empty() {
-                continue;
-            }
-
-            // Skip header line but mark that we've seen it
-            if trimmed.starts_with("Anfield") {
-                seen_header = true;
-                continue;
-            }
-
-            if !seen_header {
-                // Ignore anything before the "Anfield" header
-                continue;
-            }
-
-            // Skip lines that are just row/column indices
-            if trimmed
-                .chars()
-                .all(|c| c.is_ascii_digit() || c.is_whitespace())
-            {
-                continue;
-            }
-
-            // Strip leading row indices and whitespace
-            let row_str: String = line
-                .chars()
-                .skip_while(|c| c.is_ascii_digit() || c.is_whitespace())
-                .collect();
-
-            if row_str.is_empty() {
-                continue;
-            }
-
-            let mut row: Vec<Owner> = Vec::with_capacity(row_str.len());
-
-            // IMPORTANT FIX: skip spaces between cells
-            for ch in row_str.chars() {
-                if ch == ' ' {
-                    continue;
-                }
-                let owner = classify_char(ch, my_player);
-                row.push(owner);
-            }
-
-            if !row.is_empty() {
-                grid.push(row);
-            }
-        }
-
-        if grid.is_empty() {
-            return None;
-        }
-
-        let rows = grid.len();
-        let cols = grid[0].len();
-
-        Some(Board { rows, cols, cells: grid })
-    }
-}
-
-// IMPORTANT FIX: treat unknown characters as Empty, not Opponent
-fn classify_char(c: char, my_player: u8) -> Owner {
-    match c {
-        '.' => Owner::Empty,
-
-        '@' | 'a' => {
-            if my_player == 1 {
-                Owner::Me
-            } else {
-                Owner::Opponent
-            }
-        }
-
-        '$' | 's' => {
-            if my_player == 2 {
-                Owner::Me
-            } else {
-                Owner::Opponent
-            }
-        }
-
-        _ => Owner::Empty,
-    }
-}
+// src/board.rs
+
+use crate::bits::BitRows;
+use crate::grid::Grid;
+use crate::piece::Piece;
+use crate::scratch;
+use crate::snapshot;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Owner {
+    Empty,
+    Me,
+    Opponent,
+    /// Hidden by the fog variant (see `referee::Rules::fog`), shown as '?'.
+    Unknown,
+}
+
+/// The parsed Anfield from our point of view. Cells are only reachable
+/// through the checked accessors below, see grid.rs.
+///
+/// The referee shows each side's most recent placement in lowercase. That
+/// is kept as a separate layer rather than as more `Owner` variants, so a
+/// latest cell is still just `Me` or `Opponent` to everything else.
+///
+/// Every board carries a Zobrist hash of its cells, the XOR of a key per
+/// occupied cell, kept up to date by `set`. Two boards with the same cells
+/// hash the same however they got there, which is what transposition
+/// tables and repetition checks want, and a placement costs a few XORs
+/// instead of a rehash of the whole board.
+///
+/// The cells held by each side, and those hidden by fog, are also kept as
+/// bit rows (see bits.rs), again by `set`, for `can_place`.
+#[derive(Clone)]
+pub struct Board {
+    grid: Grid<Owner>,
+    /// `Me`, `Opponent` and `Unknown` cells, in that order.
+    layers: [BitRows; 3],
+    /// Cells of the last placement of whoever holds them, as shown. Only a
+    /// piece or two, so a list keeps boards cheap to clone.
+    latest: Vec<(usize, usize)>,
+    zobrist: u64,
+}
+
+impl Board {
+    /// Build a board from the "Anfield" block lines.
+    ///
+    /// The block looks something like:
+    /// Anfield <cols> <rows>:
+    ///     012345...
+    /// 000 ....@...
+    /// 001 ...$....
+    ///
+    /// Player 1 uses '@' / 'a', player 2 uses '$' / 's'.
+    pub fn from_anfield_lines(lines: &[String], my_player: u8) -> Option<Self> {
+        if lines.is_empty() {
+            return None;
+        }
+
+        let mut grid: Vec<Vec<Owner>> = Vec::new();
+        let mut latest: Vec<(usize, usize)> = Vec::new();
+
+        for row_str in anfield_rows(lines) {
+            let mut row: Vec<Owner> = Vec::with_capacity(row_str.len());
+            for (owner, just_placed) in parse_row(row_str, my_player) {
+                if just_placed {
+                    latest.push((grid.len(), row.len()));
+                }
+                row.push(owner);
+            }
+            grid.push(row);
+        }
+
+        if grid.is_empty() {
+            return None;
+        }
+
+        let grid = Grid::from_rows(grid, Owner::Empty);
+        let zobrist = grid.iter().fold(0, |hash, ((y, x), owner)| hash ^ cell_key(y, x, owner));
+        let mut layers = [(); 3].map(|_| BitRows::new(grid.rows(), grid.cols()));
+        for ((y, x), owner) in grid.iter() {
+            if let Some(bits) = layer(owner).and_then(|layer| layers.get_mut(layer)) {
+                bits.set(y, x, true);
+            }
+        }
+        Some(Board {
+            grid,
+            layers,
+            latest,
+            zobrist,
+        })
+    }
+
+    /// The board shown by `lines`, given that `before` showed this one.
+    /// Only rows whose text changed are parsed again, which between two
+    /// turns is the rows of two placements. A different number of rows, or
+    /// a changed row of a different width, is parsed from scratch.
+    pub fn reparse(&self, before: &[String], lines: &[String], my_player: u8) -> Option<Self> {
+        let (old, new) = (anfield_rows(before), anfield_rows(lines));
+        if old.len() != new.len() || new.len() != self.rows() {
+            return Board::from_anfield_lines(lines, my_player);
+        }
+
+        let mut board = self.clone();
+        board.latest.retain(|&(y, _)| old.get(y) == new.get(y));
+        for (y, row_str) in new.iter().enumerate().filter(|&(y, row_str)| old.get(y) != Some(row_str)) {
+            let row: Vec<(Owner, bool)> = parse_row(row_str, my_player).collect();
+            if row.len() != self.cols() {
+                return Board::from_anfield_lines(lines, my_player);
+            }
+            for (x, (owner, just_placed)) in row.into_iter().enumerate() {
+                if just_placed {
+                    board.latest.push((y, x));
+                }
+                if board.get(y, x) != Some(owner) {
+                    board.set(y, x, owner);
+                }
+            }
+        }
+        Some(board)
+    }
+
+    /// The Zobrist hash of the cells (see `Board`).
+    pub fn zobrist(&self) -> u64 {
+        self.zobrist
+    }
+
+    pub fn rows(&self) -> usize {
+        self.grid.rows()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.grid.cols()
+    }
+
+    /// Owner of (y, x), or `None` off the board.
+    pub fn get(&self, y: usize, x: usize) -> Option<Owner> {
+        self.grid.get(y, x)
+    }
+
+    /// Whether (y, x) is on the board and owned by `owner`.
+    pub fn is(&self, y: usize, x: usize, owner: Owner) -> bool {
+        self.grid.get(y, x) == Some(owner)
+    }
+
+    pub fn set(&mut self, y: usize, x: usize, owner: Owner) -> bool {
+        let Some(old) = self.grid.get(y, x) else {
+            return false;
+        };
+        self.zobrist ^= cell_key(y, x, old) ^ cell_key(y, x, owner);
+        for (who, on) in [(old, false), (owner, true)] {
+            if let Some(bits) = layer(who).and_then(|layer| self.layers.get_mut(layer)) {
+                bits.set(y, x, on);
+            }
+        }
+        self.grid.set(y, x, owner)
+    }
+
+    /// The on-board orthogonal neighbours of (y, x).
+    pub fn neighbors(&self, y: usize, x: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.grid.neighbors(y, x)
+    }
+
+    /// The cell at (y + dy, x + dx), if that's on the board.
+    pub fn offset(&self, y: usize, x: usize, dy: isize, dx: isize) -> Option<(usize, usize)> {
+        self.grid.offset(y, x, dy, dx)
+    }
+
+    /// Every cell with its coordinates, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), Owner)> + '_ {
+        self.grid.iter()
+    }
+
+    /// Coordinates of every cell held by `owner`.
+    pub fn coords(&self, owner: Owner) -> Vec<(usize, usize)> {
+        self.iter().filter(|&(_, o)| o == owner).map(|(pos, _)| pos).collect()
+    }
+
+    /// Cells of `owner`'s most recent placement, as the referee marked them
+    /// on the board we were shown. `place` doesn't update these.
+    pub fn latest(&self, owner: Owner) -> Vec<(usize, usize)> {
+        self.latest.iter().copied().filter(|&(y, x)| self.is(y, x, owner)).collect()
+    }
+
+    /// `owner`'s bit rows; empty cells have none.
+    fn bits(&self, owner: Owner) -> Option<&BitRows> {
+        layer(owner).and_then(|layer| self.layers.get(layer))
+    }
+
+    /// Cells held by `owner`, counted off the bit rows.
+    pub fn count(&self, owner: Owner) -> usize {
+        match self.bits(owner) {
+            Some(bits) => bits.count(),
+            None => self.rows() * self.cols() - self.layers.iter().map(BitRows::count).sum::<usize>(),
+        }
+    }
+
+    /// Every cell whose owner differs from `previous`, as (cell, was, now).
+    /// Boards of different sizes have nothing in common: every cell changed.
+    /// Found a word of bit rows at a time, so a turn's two placements cost
+    /// a pass over the words rather than over the cells.
+    pub fn changes<'a>(
+        &'a self,
+        previous: &'a Board,
+    ) -> impl Iterator<Item = ((usize, usize), Option<Owner>, Owner)> + 'a {
+        let same_size = previous.rows() == self.rows() && previous.cols() == self.cols();
+        let words = self.layers[0].words();
+        (0..self.rows())
+            .flat_map(move |y| (0..words).map(move |w| (y, w)))
+            .flat_map(move |(y, w)| {
+                let differ = if same_size {
+                    self.layers
+                        .iter()
+                        .zip(&previous.layers)
+                        .fold(0, |differ, (now, was)| differ | (now.word(y, w) ^ was.word(y, w)))
+                } else {
+                    self.layers[0].span(w)
+                };
+                // The set bits, lowest first
+                std::iter::successors(Some(differ).filter(|&d| d != 0), |&d| Some(d & (d - 1)).filter(|&d| d != 0))
+                    .map(move |d| (y, 64 * w + d.trailing_zeros() as usize))
+            })
+            .filter_map(move |(y, x)| {
+                let was = if same_size { previous.get(y, x) } else { None };
+                Some(((y, x), was, self.get(y, x)?))
+            })
+    }
+
+    /// A placement is valid when every filled cell lands on the board,
+    /// none of them covers the other player, and exactly one covers `who`.
+    /// Checked a piece row at a time against the bit rows, for pieces
+    /// narrow enough to have row masks.
+    pub fn can_place(&self, piece: &Piece, top_y: usize, left_x: usize, who: Owner) -> bool {
+        let (Some(own), Some(other), Some(hidden)) = (self.bits(who), self.bits(other(who)), self.bits(Owner::Unknown))
+        else {
+            return self.can_place_cells(piece, top_y, left_x, who);
+        };
+        let Some(masks) = piece.row_masks() else {
+            return self.can_place_cells(piece, top_y, left_x, who);
+        };
+        // A trimmed piece has cells on every side of its box
+        if top_y + piece.height > self.rows() || left_x + piece.width > self.cols() {
+            return false;
+        }
+
+        let mut overlap_count = 0;
+        for (dy, &mask) in masks.iter().enumerate() {
+            let y = top_y + dy;
+            if (other.window(y, left_x) | hidden.window(y, left_x)) & mask != 0 {
+                return false;
+            }
+            overlap_count += (own.window(y, left_x) & mask).count_ones();
+            if overlap_count > 1 {
+                return false;
+            }
+        }
+
+        overlap_count == 1
+    }
+
+    /// `can_place`, one cell at a time.
+    fn can_place_cells(&self, piece: &Piece, top_y: usize, left_x: usize, who: Owner) -> bool {
+        let mut overlap_count = 0;
+
+        for &(dy, dx) in &piece.cells {
+            match self.get(top_y + dy, left_x + dx) {
+                None => return false,
+                Some(Owner::Empty) => {}
+                Some(owner) if owner == who => {
+                    overlap_count += 1;
+                    if overlap_count > 1 {
+                        return false;
+                    }
+                }
+                Some(_) => return false,
+            }
+        }
+
+        overlap_count == 1
+    }
+
+    /// Every top-left offset where `who` could legally place `piece`, in
+    /// scan order.
+    pub fn legal_placements(&self, piece: &Piece, who: Owner) -> Vec<(usize, usize)> {
+        self.anchored_offsets(piece, who)
+            .into_iter()
+            .filter(|&(top_y, left_x)| self.can_place(piece, top_y, left_x, who))
+            .collect()
+    }
+
+    /// The top-left offsets that put one of `piece`'s cells on one of
+    /// `who`'s anchors, sorted in scan order. A legal placement overlaps
+    /// exactly one of their cells, so nothing else can be, and for a sparse
+    /// piece on a big board that's far fewer than `placement_window`. The
+    /// anchors are their cells next to an empty one: a connected piece of
+    /// two or more cells has one of its others there. A single cell or a
+    /// scattered piece can overlap any of their cells. Offsets are marked
+    /// on a bit per cell and read back, which keeps them in scan order
+    /// without sorting the anchors times piece cells they come from.
+    pub fn anchored_offsets(&self, piece: &Piece, who: Owner) -> Vec<(usize, usize)> {
+        let frontier_only = piece.cells.len() >= 2 && piece.is_connected();
+        let mut marked = BitRows::new(self.rows(), self.cols());
+        let held = if frontier_only { self.anchors(who) } else { self.coords(who) };
+        for (y, x) in held {
+            for &(dy, dx) in &piece.cells {
+                if y >= dy && x >= dx && y - dy + piece.height <= self.rows() && x - dx + piece.width <= self.cols() {
+                    marked.set(y - dy, x - dx, true);
+                }
+            }
+        }
+        marked.ones()
+    }
+
+    /// `who`'s cells next to an empty one, in scan order. Worked out a word
+    /// of a row at a time from the bit rows: a cell is an anchor when its
+    /// bit is set in `who`'s row and an empty bit sits beside, above or
+    /// below it.
+    pub fn anchors(&self, who: Owner) -> Vec<(usize, usize)> {
+        let Some(own) = self.bits(who) else {
+            return self
+                .coords(who)
+                .into_iter()
+                .filter(|&(y, x)| self.neighbors(y, x).any(|(ny, nx)| self.is(ny, nx, Owner::Empty)))
+                .collect();
+        };
+        let rows = &self.layers;
+        let empty = |y: usize, w: usize| -> u64 {
+            if y >= self.rows() {
+                return 0;
+            }
+            rows.iter().fold(own.span(w), |empty, layer| empty & !layer.word(y, w))
+        };
+
+        let mut anchors = Vec::new();
+        for y in 0..self.rows() {
+            for w in 0..own.words() {
+                let mine = own.word(y, w);
+                if mine == 0 {
+                    continue;
+                }
+                let here = empty(y, w);
+                let before = if w > 0 { empty(y, w - 1) >> 63 } else { 0 };
+                let after = empty(y, w + 1) << 63;
+                let above = if y > 0 { empty(y - 1, w) } else { 0 };
+                let mut open = mine & (here << 1 | before | here >> 1 | after | above | empty(y + 1, w));
+                while open != 0 {
+                    anchors.push((y, 64 * w + open.trailing_zeros() as usize));
+                    open &= open - 1;
+                }
+            }
+        }
+        anchors
+    }
+
+    /// The top-left offsets worth checking for `who`'s placements of
+    /// `piece`, in scan order. A legal placement covers one of their cells,
+    /// so only offsets within the piece's size of the bounding box of those
+    /// cells can be; on a big board with a small territory that's a small
+    /// fraction of the scan. Empty when they hold no cell.
+    pub fn placement_window(&self, piece: &Piece, who: Owner) -> impl Iterator<Item = (usize, usize)> {
+        let mut top = usize::MAX;
+        let mut left = usize::MAX;
+        let mut bottom = 0;
+        let mut right = 0;
+        for ((y, x), owner) in self.iter() {
+            if owner == who {
+                top = top.min(y);
+                left = left.min(x);
+                bottom = bottom.max(y);
+                right = right.max(x);
+            }
+        }
+
+        // From past these the piece would hang off the board
+        let max_y = self.rows().saturating_sub(piece.height).saturating_add(1);
+        let max_x = self.cols().saturating_sub(piece.width).saturating_add(1);
+        let (ys, xs) = if top == usize::MAX {
+            (0..0, 0..0)
+        } else {
+            (
+                top.saturating_sub(piece.height.saturating_sub(1))..(bottom + 1).min(max_y),
+                left.saturating_sub(piece.width.saturating_sub(1))..(right + 1).min(max_x),
+            )
+        };
+        ys.flat_map(move |top_y| xs.clone().map(move |left_x| (top_y, left_x)))
+    }
+
+    /// BFS distance from `who`'s cells through empty cells.
+    /// Cells that can't be reached are `usize::MAX`.
+    pub fn distance_field(&self, who: Owner) -> Grid<usize> {
+        self.distance_from(self.iter().filter(|&(_, owner)| owner == who).map(|(pos, _)| pos))
+    }
+
+    /// BFS distance from `sources` through empty cells, as `distance_field`.
+    pub fn distance_from(&self, sources: impl IntoIterator<Item = (usize, usize)>) -> Grid<usize> {
+        let mut dist = Grid::new(self.rows(), self.cols(), usize::MAX);
+        let mut queue = scratch::take::<(usize, usize, usize)>();
+
+        for (y, x) in sources {
+            if dist.set(y, x, 0) {
+                queue.push((y, x, 0));
+            }
+        }
+
+        let mut head = 0;
+        while let Some(&(y, x, d)) = queue.get(head) {
+            head += 1;
+            for (ny, nx) in self.neighbors(y, x) {
+                if self.is(ny, nx, Owner::Empty) && dist.get(ny, nx) == Some(usize::MAX) {
+                    dist.set(ny, nx, d + 1);
+                    queue.push((ny, nx, d + 1));
+                }
+            }
+        }
+
+        dist
+    }
+
+    /// Empty cells the opponent can never reach: regions fully enclosed by
+    /// my cells and the walls. They're mine whenever I get around to them.
+    pub fn dead_zones(&self) -> Grid<bool> {
+        let theirs = self.distance_field(Owner::Opponent);
+
+        let mut dead = Grid::new(self.rows(), self.cols(), false);
+        for ((y, x), owner) in self.iter() {
+            dead.set(y, x, owner == Owner::Empty && theirs.get(y, x) == Some(usize::MAX));
+        }
+        dead
+    }
+
+    /// Claim the piece's cells for `who`. The caller is expected to have
+    /// checked the placement with `can_place` first.
+    pub fn place(&mut self, piece: &Piece, top_y: usize, left_x: usize, who: Owner) {
+        for &(dy, dx) in &piece.cells {
+            self.set(top_y + dy, left_x + dx, who);
+        }
+    }
+}
+
+const UNKNOWN: usize = 2;
+
+/// Which of `Board::layers` holds `owner`'s cells; empty cells have none.
+fn layer(owner: Owner) -> Option<usize> {
+    match owner {
+        Owner::Me => Some(0),
+        Owner::Opponent => Some(1),
+        Owner::Unknown => Some(UNKNOWN),
+        Owner::Empty => None,
+    }
+}
+
+/// The other player; nobody for empty or hidden cells.
+fn other(owner: Owner) -> Owner {
+    match owner {
+        Owner::Me => Owner::Opponent,
+        Owner::Opponent => Owner::Me,
+        _ => Owner::Empty,
+    }
+}
+
+/// A cell's share of the Zobrist hash. Empty cells add nothing, and the
+/// keys come from `snapshot::mix` so there's no random table to seed.
+fn cell_key(y: usize, x: usize, owner: Owner) -> u64 {
+    let owner = match owner {
+        Owner::Empty => return 0,
+        Owner::Me => 1,
+        Owner::Opponent => 2,
+        Owner::Unknown => 3,
+    };
+    snapshot::mix(&[y as u64, x as u64, owner])
+}
+
+/// The cell rows of an Anfield block, row indices stripped: every line
+/// after the header that isn't blank or just column indices.
+fn anfield_rows(lines: &[String]) -> Vec<&str> {
+    let Some(header) = lines.iter().position(|line| line.trim().starts_with("Anfield")) else {
+        return Vec::new();
+    };
+    lines
+        .iter()
+        .skip(header + 1)
+        // Skip repeated headers and lines that are just row/column indices
+        .filter(|line| {
+            let trimmed = line.trim();
+            !trimmed.starts_with("Anfield") && !trimmed.chars().all(|c| c.is_ascii_digit() || c.is_whitespace())
+        })
+        // Strip leading row indices and whitespace
+        .map(|line| line.trim_start_matches(|c: char| c.is_ascii_digit() || c.is_whitespace()))
+        .collect()
+}
+
+/// The cells of one row, spaces between them skipped.
+fn parse_row(row_str: &str, my_player: u8) -> impl Iterator<Item = (Owner, bool)> + '_ {
+    row_str.chars().filter(|&ch| ch != ' ').map(move |ch| classify_char(ch, my_player))
+}
+
+// IMPORTANT FIX: treat unknown characters as Empty, not Opponent
+/// The owner of a cell character, and whether it's lowercase: part of
+/// that player's latest placement.
+fn classify_char(c: char, my_player: u8) -> (Owner, bool) {
+    let owner = match c {
+        '.' => Owner::Empty,
+
+        '@' | 'a' => {
+            if my_player == 1 {
+                Owner::Me
+            } else {
+                Owner::Opponent
+            }
+        }
+
+        '$' | 's' => {
+            if my_player == 2 {
+                Owner::Me
+            } else {
+                Owner::Opponent
+            }
+        }
+
+        '?' => Owner::Unknown,
+
+        _ => Owner::Empty,
+    };
+    (owner, c == 'a' || c == 's')
+}
+
+/// How many cells each owner holds, kept up to date from the cells that
+/// changed since the last board instead of being recounted cell by cell.
+/// Not incremental in the placements: `update` still diffs every word of
+/// the bit rows against the previous board, which the caller has to keep.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Census {
+    pub me: usize,
+    pub opponent: usize,
+    pub empty: usize,
+    pub unknown: usize,
+}
+
+impl Census {
+    /// Counted from scratch.
+    pub fn of(board: &Board) -> Census {
+        let mut census = Census::default();
+        for (_, owner) in board.iter() {
+            *census.slot(owner) += 1;
+        }
+        census
+    }
+
+    /// Move every cell that differs from `previous` from its old owner's
+    /// count to its new one.
+    pub fn update(&mut self, previous: &Board, board: &Board) {
+        if previous.rows() != board.rows() || previous.cols() != board.cols() {
+            *self = Census::of(board);
+            return;
+        }
+        for (_, was, now) in board.changes(previous) {
+            if let Some(was) = was {
+                *self.slot(was) -= 1;
+            }
+            *self.slot(now) += 1;
+        }
+    }
+
+    fn slot(&mut self, owner: Owner) -> &mut usize {
+        match owner {
+            Owner::Me => &mut self.me,
+            Owner::Opponent => &mut self.opponent,
+            Owner::Empty => &mut self.empty,
+            Owner::Unknown => &mut self.unknown,
+        }
+    }
+}
+
+/// Each side's cells and our frontier (our cells with an empty neighbour),
+/// row by row as `coords` lists them, kept live from board changes like
+/// `Census` instead of being collected again every turn.
+#[derive(Clone, Default)]
+pub struct Holdings {
+    pub mine: Vec<(usize, usize)>,
+    pub theirs: Vec<(usize, usize)>,
+    pub frontier: Vec<(usize, usize)>,
+    /// The board they were last brought up to date with.
+    board: Option<Board>,
+}
+
+impl Holdings {
+    /// Collected from scratch.
+    pub fn of(board: &Board) -> Holdings {
+        Holdings {
+            mine: board.coords(Owner::Me),
+            theirs: board.coords(Owner::Opponent),
+            frontier: board.anchors(Owner::Me),
+            board: Some(board.clone()),
+        }
+    }
+
+    /// Move every cell that changed since the last board between the
+    /// lists, and look again at whether it and its neighbours are frontier.
+    /// Lists that no longer add up to the board are collected from scratch.
+    pub fn update(&mut self, board: &Board) {
+        let Some(previous) = self.board.take() else {
+            *self = Holdings::of(board);
+            return;
+        };
+        if previous.rows() != board.rows() || previous.cols() != board.cols() {
+            *self = Holdings::of(board);
+            return;
+        }
+
+        let mut touched = Vec::new();
+        for (cell, was, now) in board.changes(&previous) {
+            if let Some(list) = was.and_then(|was| self.list(was)) {
+                mark(list, cell, false);
+            }
+            if let Some(list) = self.list(now) {
+                mark(list, cell, true);
+            }
+            touched.push(cell);
+            touched.extend(board.neighbors(cell.0, cell.1));
+        }
+        for (y, x) in touched {
+            let on = board.is(y, x, Owner::Me) && board.neighbors(y, x).any(|(ny, nx)| board.is(ny, nx, Owner::Empty));
+            mark(&mut self.frontier, (y, x), on);
+        }
+
+        let consistent = self.mine.len() == board.count(Owner::Me)
+            && self.theirs.len() == board.count(Owner::Opponent)
+            && self.frontier.iter().all(|&(y, x)| board.is(y, x, Owner::Me));
+        if !consistent {
+            debug!("[DEBUG] Cell lists drifted from the board, collecting them again");
+            *self = Holdings::of(board);
+            return;
+        }
+        self.board = Some(board.clone());
+    }
+
+    fn list(&mut self, owner: Owner) -> Option<&mut Vec<(usize, usize)>> {
+        match owner {
+            Owner::Me => Some(&mut self.mine),
+            Owner::Opponent => Some(&mut self.theirs),
+            Owner::Empty | Owner::Unknown => None,
+        }
+    }
+}
+
+/// Put `cell` in the sorted `list` or take it out.
+fn mark(list: &mut Vec<(usize, usize)>, cell: (usize, usize), on: bool) {
+    match (list.binary_search(&cell), on) {
+        (Err(at), true) => list.insert(at, cell),
+        (Ok(at), false) => {
+            list.remove(at);
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,176 @@
+// src/book.rs
+// Opening book. The first few turns on the official maps come up over and
+// over, so instead of searching them live we look the position up in a book
+// built offline with a much bigger search budget (`filler book`).
+//
+// A position is the board plus the piece we were dealt, hashed with
+// `snapshot::position_hash`; the board already pins down the map and the
+// starting positions. The file format is one header line and then one
+// entry per line:
+//
+//     turns 3
+//     <position hash, hex> <row> <col>
+
+use std::collections::HashMap;
+use std::fs;
+
+use crate::board::{Board, Owner};
+use crate::game::{Game, Strategy};
+use crate::mcts::{Mcts, MctsConfig};
+use crate::piece::Piece;
+use crate::referee::{Match, PieceSource};
+use crate::snapshot::position_hash;
+
+pub const BUNDLED: &str = include_str!("../book/openings.book");
+
+#[derive(Default)]
+pub struct Book {
+    /// Only our first `turns` moves are looked up.
+    pub turns: usize,
+    entries: HashMap<u64, (usize, usize)>,
+}
+
+impl Book {
+    /// The book shipped with the bot.
+    pub fn bundled() -> Self {
+        Book::parse(BUNDLED).unwrap_or_default()
+    }
+
+    pub fn new(turns: usize) -> Self {
+        Book {
+            turns,
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        Book::parse(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut book = Book::default();
+        for (n, line) in text.lines().enumerate() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let bad = || format!("line {}: bad entry `{}`", n + 1, line.trim());
+            match fields.as_slice() {
+                [] => {}
+                ["turns", turns] => book.turns = turns.parse().map_err(|_| bad())?,
+                [hash, y, x] => {
+                    let hash = u64::from_str_radix(hash, 16).map_err(|_| bad())?;
+                    let y = y.parse().map_err(|_| bad())?;
+                    let x = x.parse().map_err(|_| bad())?;
+                    book.entries.insert(hash, (y, x));
+                }
+                _ => return Err(bad()),
+            }
+        }
+        Ok(book)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        entries.sort();
+
+        let mut lines = vec![format!("turns {}", self.turns)];
+        lines.extend(entries.iter().map(|(hash, (y, x))| format!("{:016x} {} {}", hash, y, x)));
+        fs::write(path, lines.join("\n") + "\n").map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn insert(&mut self, board: &Board, piece: &Piece, placement: (usize, usize)) {
+        self.entries.insert(position_hash(board, piece), placement);
+    }
+
+    /// The book move for our `turn`-th move (1-based), if there is one and
+    /// it's legal here. The legality check guards against hash collisions.
+    pub fn lookup(&self, turn: usize, board: &Board, piece: &Piece) -> Option<(usize, usize)> {
+        if turn > self.turns {
+            return None;
+        }
+        self.entries
+            .get(&position_hash(board, piece))
+            .copied()
+            .filter(|&(y, x)| board.can_place(piece, y, x, Owner::Me))
+    }
+}
+
+const USAGE: &str = "usage: filler book <map>... [--turns N] [--games N] [--playouts N] [--seed N] [--out FILE]";
+
+/// `filler book`: play `--games` referee games per map with a high-budget
+/// MCTS on both sides and record each side's first `--turns` moves.
+pub fn run_cli(args: &[String]) -> Result<(), String> {
+    let mut map_paths: Vec<&str> = Vec::new();
+    let mut turns = 3usize;
+    let mut games = 20usize;
+    let mut playouts = 2000usize;
+    let mut seed: u64 = 1;
+    let mut out = "openings.book".to_string();
+
+    let mut i = 0;
+    while let Some(arg) = args.get(i) {
+        let value = args.get(i + 1);
+        let number = |name: &str| -> Result<u64, String> {
+            value
+                .and_then(|v| v.parse().ok())
+                .ok_or(format!("{} expects a number", name))
+        };
+        match arg.as_str() {
+            "--turns" => turns = number("--turns")? as usize,
+            "--games" => games = number("--games")? as usize,
+            "--playouts" => playouts = number("--playouts")? as usize,
+            "--seed" => seed = number("--seed")?,
+            "--out" => out = value.cloned().ok_or("--out expects a path")?,
+            other => {
+                map_paths.push(other);
+                i += 1;
+                continue;
+            }
+        }
+        i += 2;
+    }
+    if map_paths.is_empty() {
+        return Err(USAGE.to_string());
+    }
+
+    let mut book = Book::new(turns);
+    for path in map_paths {
+        let map: Vec<String> = fs::read_to_string(path)
+            .map_err(|e| format!("{}: {}", path, e))?
+            .lines()
+            .map(|l| l.to_string())
+            .collect();
+
+        for g in 0..games as u64 {
+            let config = MctsConfig {
+                playouts,
+                ..MctsConfig::default()
+            };
+            let mut players = [1u8, 2].map(|p| Game::new(p, Strategy::Mcts(Mcts::new(config))));
+            let mut game = Match::new(&map, PieceSource::scripted(Vec::new(), seed + g))?;
+            let mut moves = [0usize; 2];
+
+            while let Some(turn) = game.step(&mut players) {
+                let Some(made) = moves.get_mut(turn.player as usize - 1) else {
+                    continue;
+                };
+                *made += 1;
+                let early = *made <= turns;
+                if moves.iter().all(|&made| made > turns) {
+                    break;
+                }
+                if let (true, Some(placed)) = (early, turn.placed) {
+                    book.insert(&turn.board, &turn.piece, placed);
+                }
+            }
+        }
+        println!("{}: {} positions so far", path, book.len());
+    }
+
+    book.save(&out)?;
+    println!("wrote {} positions to {}", book.len(), out);
+    Ok(())
+}
@@ -0,0 +1,280 @@
+// src/cmaes.rs
+// CMA-ES (covariance matrix adaptation evolution strategy), maximising.
+// Used by `filler tune --method cmaes` on the continuous weight space; it
+// follows Hansen's "The CMA Evolution Strategy: A Tutorial" step by step.
+//
+// The whole state can be written to a text checkpoint and read back, so a
+// long run can be stopped and resumed where it left off.
+//
+// The matrix arithmetic indexes freely (see the `allow`s): the shapes are
+// fixed by `new` and checked by `load`, and none of this runs in a match.
+
+use std::fs;
+
+use crate::rng::Rng;
+
+pub struct Cmaes {
+    pub generation: usize,
+    mean: Vec<f64>,
+    sigma: f64,
+    /// Covariance matrix, row-major n x n.
+    cov: Vec<f64>,
+    path_sigma: Vec<f64>,
+    path_cov: Vec<f64>,
+    rng: Rng,
+    /// Best point seen so far and its fitness.
+    pub best: Option<(f64, Vec<f64>)>,
+}
+
+impl Cmaes {
+    pub fn new(mean: Vec<f64>, sigma: f64, seed: u64) -> Self {
+        let n = mean.len();
+        let mut cov = vec![0.0; n * n];
+        cov.iter_mut().step_by(n + 1).for_each(|c| *c = 1.0);
+        Cmaes {
+            generation: 0,
+            mean,
+            sigma,
+            cov,
+            path_sigma: vec![0.0; n],
+            path_cov: vec![0.0; n],
+            rng: Rng::new(seed),
+            best: None,
+        }
+    }
+
+    fn dim(&self) -> usize {
+        self.mean.len()
+    }
+
+    /// Offspring per generation.
+    pub fn lambda(&self) -> usize {
+        4 + (3.0 * (self.dim() as f64).ln()).floor() as usize
+    }
+
+    /// Recombination weights of the best half, summing to one, and the
+    /// variance-effective selection mass.
+    fn recombination(&self) -> (Vec<f64>, f64) {
+        let mu = self.lambda() / 2;
+        let raw: Vec<f64> = (1..=mu)
+            .map(|i| (mu as f64 + 0.5).ln() - (i as f64).ln())
+            .collect();
+        let sum: f64 = raw.iter().sum();
+        let weights: Vec<f64> = raw.iter().map(|w| w / sum).collect();
+        let mueff = 1.0 / weights.iter().map(|w| w * w).sum::<f64>();
+        (weights, mueff)
+    }
+
+    /// Sample a generation: x = mean + sigma * B * D * z.
+    #[allow(clippy::indexing_slicing)]
+    pub fn ask(&mut self) -> Vec<Vec<f64>> {
+        let n = self.dim();
+        let (basis, scales) = eigen(&self.cov, n);
+
+        (0..self.lambda())
+            .map(|_| {
+                let z: Vec<f64> = (0..n).map(|_| gaussian(&mut self.rng)).collect();
+                (0..n)
+                    .map(|i| {
+                        let y: f64 = (0..n).map(|k| basis[i * n + k] * scales[k].sqrt() * z[k]).sum();
+                        self.mean[i] + self.sigma * y
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Update the distribution from the fitness of every point `ask` gave.
+    #[allow(clippy::indexing_slicing)]
+    pub fn tell(&mut self, points: &[Vec<f64>], fitness: &[f64]) {
+        let n = self.dim();
+        let nf = n as f64;
+        let (weights, mueff) = self.recombination();
+
+        let cc = (4.0 + mueff / nf) / (nf + 4.0 + 2.0 * mueff / nf);
+        let cs = (mueff + 2.0) / (nf + mueff + 5.0);
+        let c1 = 2.0 / ((nf + 1.3).powi(2) + mueff);
+        let cmu = (1.0 - c1).min(2.0 * (mueff - 2.0 + 1.0 / mueff) / ((nf + 2.0).powi(2) + mueff));
+        let damps = 1.0 + 2.0 * (((mueff - 1.0) / (nf + 1.0)).sqrt() - 1.0).max(0.0) + cs;
+        let chi_n = nf.sqrt() * (1.0 - 1.0 / (4.0 * nf) + 1.0 / (21.0 * nf * nf));
+
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        order.sort_by(|&a, &b| fitness[b].total_cmp(&fitness[a]));
+        if let Some(&top) = order.first() {
+            if self.best.as_ref().is_none_or(|(f, _)| fitness[top] > *f) {
+                self.best = Some((fitness[top], points[top].clone()));
+            }
+        }
+
+        // Steps of the selected points, in units of sigma
+        let steps: Vec<Vec<f64>> = order
+            .iter()
+            .take(weights.len())
+            .map(|&i| {
+                (0..n)
+                    .map(|k| (points[i][k] - self.mean[k]) / self.sigma)
+                    .collect()
+            })
+            .collect();
+        let step_w: Vec<f64> = (0..n)
+            .map(|k| steps.iter().zip(&weights).map(|(s, w)| w * s[k]).sum())
+            .collect();
+        for (m, s) in self.mean.iter_mut().zip(&step_w) {
+            *m += self.sigma * s;
+        }
+
+        // C^(-1/2) * step_w = B * D^-1 * B^T * step_w
+        let (basis, scales) = eigen(&self.cov, n);
+        let rotated: Vec<f64> = (0..n)
+            .map(|k| {
+                let proj: f64 = (0..n).map(|i| basis[i * n + k] * step_w[i]).sum();
+                proj / scales[k].max(1e-20).sqrt()
+            })
+            .collect();
+        let whitened: Vec<f64> = (0..n)
+            .map(|i| (0..n).map(|k| basis[i * n + k] * rotated[k]).sum())
+            .collect();
+
+        let cs_norm = (cs * (2.0 - cs) * mueff).sqrt();
+        for (p, w) in self.path_sigma.iter_mut().zip(&whitened) {
+            *p = (1.0 - cs) * *p + cs_norm * w;
+        }
+        let ps_len = self.path_sigma.iter().map(|v| v * v).sum::<f64>().sqrt();
+        let decay = 1.0 - (1.0 - cs).powi(2 * (self.generation as i32 + 1));
+        let hsig = ps_len / decay.sqrt() / chi_n < 1.4 + 2.0 / (nf + 1.0);
+
+        let cc_norm = (cc * (2.0 - cc) * mueff).sqrt();
+        for (p, s) in self.path_cov.iter_mut().zip(&step_w) {
+            *p = (1.0 - cc) * *p + if hsig { cc_norm * s } else { 0.0 };
+        }
+
+        let hsig_fix = if hsig { 0.0 } else { c1 * cc * (2.0 - cc) };
+        for i in 0..n {
+            for j in 0..n {
+                let rank_mu: f64 = steps.iter().zip(&weights).map(|(s, w)| w * s[i] * s[j]).sum();
+                let c = &mut self.cov[i * n + j];
+                *c = (1.0 - c1 - cmu) * *c
+                    + hsig_fix * *c
+                    + c1 * self.path_cov[i] * self.path_cov[j]
+                    + cmu * rank_mu;
+            }
+        }
+
+        self.sigma *= ((cs / damps) * (ps_len / chi_n - 1.0)).exp();
+        self.generation += 1;
+    }
+
+    /// Write the full state as `key value...` lines.
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let join = |v: &[f64]| v.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(" ");
+        let mut lines = vec![
+            format!("generation {}", self.generation),
+            format!("sigma {}", self.sigma),
+            format!("rng {}", self.rng.state()),
+            format!("mean {}", join(&self.mean)),
+            format!("cov {}", join(&self.cov)),
+            format!("path_sigma {}", join(&self.path_sigma)),
+            format!("path_cov {}", join(&self.path_cov)),
+        ];
+        if let Some((fitness, point)) = &self.best {
+            lines.push(format!("best_fitness {}", fitness));
+            lines.push(format!("best {}", join(point)));
+        }
+        fs::write(path, lines.join("\n") + "\n").map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// Read a checkpoint written by `save`.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let bad = |key: &str| format!("{}: bad or missing `{}`", path, key);
+        let field = |key: &str| -> Option<Vec<f64>> {
+            let line = text.lines().find(|l| l.split_whitespace().next() == Some(key))?;
+            line.split_whitespace().skip(1).map(|v| v.parse().ok()).collect()
+        };
+        let scalar = |key: &str| field(key).and_then(|v| v.first().copied()).ok_or_else(|| bad(key));
+
+        let mean = field("mean").ok_or_else(|| bad("mean"))?;
+        let n = mean.len();
+        let vector = |key: &str, len: usize| field(key).filter(|v| v.len() == len).ok_or_else(|| bad(key));
+        let rng_state: u64 = text
+            .lines()
+            .find_map(|l| l.strip_prefix("rng "))
+            .and_then(|v| v.trim().parse().ok())
+            .ok_or_else(|| bad("rng"))?;
+
+        let best = match (field("best_fitness").as_deref(), field("best")) {
+            (Some(&[f]), Some(point)) if point.len() == n => Some((f, point)),
+            _ => None,
+        };
+
+        Ok(Cmaes {
+            generation: scalar("generation")? as usize,
+            sigma: scalar("sigma")?,
+            cov: vector("cov", n * n)?,
+            path_sigma: vector("path_sigma", n)?,
+            path_cov: vector("path_cov", n)?,
+            mean,
+            rng: Rng::new(rng_state),
+            best,
+        })
+    }
+}
+
+/// Standard normal sample (Box-Muller).
+fn gaussian(rng: &mut Rng) -> f64 {
+    let u1 = rng.unit().max(f64::MIN_POSITIVE);
+    let u2 = rng.unit();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+/// Eigen decomposition of a symmetric n x n matrix by cyclic Jacobi
+/// rotations. Returns the eigenvectors as the columns of a row-major matrix,
+/// and the eigenvalues (clamped at zero).
+#[allow(clippy::indexing_slicing)]
+fn eigen(matrix: &[f64], n: usize) -> (Vec<f64>, Vec<f64>) {
+    let mut a = matrix.to_vec();
+    let mut v = vec![0.0; n * n];
+    v.iter_mut().step_by(n + 1).for_each(|c| *c = 1.0);
+
+    for _ in 0..50 {
+        let off: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i * n + j] * a[i * n + j])
+            .sum();
+        if off < 1e-18 {
+            break;
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                let apq = a[p * n + q];
+                if apq.abs() < 1e-300 {
+                    continue;
+                }
+                let theta = (a[q * n + q] - a[p * n + p]) / (2.0 * apq);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                for k in 0..n {
+                    let (akp, akq) = (a[k * n + p], a[k * n + q]);
+                    a[k * n + p] = c * akp - s * akq;
+                    a[k * n + q] = s * akp + c * akq;
+                }
+                for k in 0..n {
+                    let (apk, aqk) = (a[p * n + k], a[q * n + k]);
+                    a[p * n + k] = c * apk - s * aqk;
+                    a[q * n + k] = s * apk + c * aqk;
+                }
+                for k in 0..n {
+                    let (vkp, vkq) = (v[k * n + p], v[k * n + q]);
+                    v[k * n + p] = c * vkp - s * vkq;
+                    v[k * n + q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+
+    let values = (0..n).map(|i| a[i * n + i].max(0.0)).collect();
+    (v, values)
+}
@@ -0,0 +1,442 @@
+// src/config.rs
+// Runtime settings. The engine launches us with no arguments, so everything
+// is read from environment variables with sensible defaults.
+
+use std::env;
+use std::time::Duration;
+
+use crate::anytime::{self, Anytime, AnytimeConfig};
+use crate::beam::{BeamConfig, BeamSearch};
+use crate::timebank::BankConfig;
+use crate::transposition::TableConfig;
+use crate::book::Book;
+use crate::ensemble::{self, Ensemble, EnsembleConfig};
+use crate::eval::Evaluator;
+use crate::fog::Stance;
+use crate::game::{Exploration, Game, SamplingConfig, Strategy, DEFAULT_INFLUENCE_DECAY};
+use crate::mcts::{Mcts, MctsConfig};
+use crate::patterns::PatternLibrary;
+use crate::profiles::Profiles;
+use crate::ties::TieBreak;
+use crate::weights::{Weights, NEUTRAL_AGGRESSION};
+
+/// About the score gap between neighbouring candidates in contact.
+const DEFAULT_EXPLORE_TEMPERATURE: f64 = 5000.0;
+
+/// Well inside the engine's own timeout, and far past any decision the
+/// guardrail test lets through.
+const DEFAULT_DEADLINE_MS: u64 = 3000;
+
+pub struct Config {
+    /// FILLER_STRATEGY: any name in `STRATEGIES`, by default
+    /// `default_strategy()`.
+    pub strategy: String,
+    /// FILLER_MCTS_PLAYOUTS / FILLER_MCTS_EXPLORATION / FILLER_MCTS_ROLLOUT_DEPTH
+    pub mcts: MctsConfig,
+    /// FILLER_BEAM_WIDTH / FILLER_BEAM_DEPTH, and FILLER_TT_SIZE /
+    /// FILLER_TT_REPLACE for its transposition table.
+    pub beam: BeamConfig,
+    /// FILLER_ANYTIME_BUDGET_MS / FILLER_ANYTIME_MAX_DEPTH, the time bank's
+    /// FILLER_BANK_OPENING_SHARE / FILLER_BANK_CONTACT_TURNS /
+    /// FILLER_BANK_MAX_FACTOR, and the beam's FILLER_BEAM_WIDTH and table
+    /// settings.
+    pub anytime: AnytimeConfig,
+    /// FILLER_ENSEMBLE / FILLER_ENSEMBLE_COMBINE
+    pub ensemble: EnsembleConfig,
+    /// FILLER_MODEL: an ONNX model for the lookahead strategies to score
+    /// positions with, see eval.rs. Needs a build with `--features onnx`.
+    pub model: Option<String>,
+    /// FILLER_SAMPLE_ABOVE / FILLER_SAMPLE_COUNT
+    pub sampling: SamplingConfig,
+    /// FILLER_INFLUENCE_DECAY: per-step falloff of the influence map.
+    pub influence_decay: f64,
+    /// FILLER_WEIGHTS: path to a weights file, see weights.rs.
+    pub weights: Weights,
+    /// FILLER_PROFILES: path to a file of weights per kind of map, or "off".
+    /// Defaults to the bundled one, see profiles.rs. Off with FILLER_WEIGHTS,
+    /// which pins the weights on every map.
+    pub profiles: Option<String>,
+    /// FILLER_AGGRESSION: 0 (pure expansion) to 1 (pure blocking), see
+    /// `Weights::with_aggression`.
+    pub aggression: f32,
+    /// FILLER_EXPLORE_EPSILON / FILLER_EXPLORE_TEMPERATURE, for self-play only.
+    pub exploration: Exploration,
+    /// FILLER_PATTERNS: path to a pattern file, see patterns.rs.
+    pub patterns: Option<String>,
+    /// FILLER_BOOK: path to an opening book, or "off". Defaults to the
+    /// bundled one.
+    pub book: Option<String>,
+    /// FILLER_FOG: "optimistic" (default) or "pessimistic" about cells
+    /// hidden by fog of war, see fog.rs.
+    pub fog: Stance,
+    /// FILLER_TIE_BREAK: how equal scores are settled, "hash" (default),
+    /// "scan" or "random[:seed]", see ties.rs.
+    pub ties: TieBreak,
+    /// FILLER_DEADLINE_MS: past this the fallback move goes out instead of
+    /// waiting for the strategy (see fallback.rs). 0 waits forever.
+    pub deadline: Option<Duration>,
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        Config::read(|key| env::var(key).ok())
+    }
+
+    /// The settings named by `var`, which looks a variable up by name.
+    fn read(var: impl Fn(&str) -> Option<String>) -> Self {
+        let defaults = MctsConfig::default();
+        let sampling = SamplingConfig::default();
+        let beam = BeamConfig::default();
+        let anytime = AnytimeConfig::default();
+        let ensemble = EnsembleConfig::default();
+        let table = TableConfig {
+            size: env_or(&var, "FILLER_TT_SIZE", TableConfig::default().size),
+            replace: env_or(&var, "FILLER_TT_REPLACE", TableConfig::default().replace),
+        };
+        let deadline = Some(Duration::from_millis(env_or(&var, "FILLER_DEADLINE_MS", DEFAULT_DEADLINE_MS)))
+            .filter(|d| !d.is_zero());
+        let budget = deadline.map_or(anytime.budget, |d| d.mul_f64(anytime::BUDGET_SHARE));
+
+        Config {
+            strategy: var("FILLER_STRATEGY").unwrap_or_else(|| default_strategy().to_string()),
+            mcts: MctsConfig {
+                playouts: env_or(&var, "FILLER_MCTS_PLAYOUTS", defaults.playouts),
+                exploration: env_or(&var, "FILLER_MCTS_EXPLORATION", defaults.exploration),
+                rollout_depth: env_or(&var, "FILLER_MCTS_ROLLOUT_DEPTH", defaults.rollout_depth),
+            },
+            beam: BeamConfig {
+                width: env_or(&var, "FILLER_BEAM_WIDTH", beam.width),
+                depth: env_or(&var, "FILLER_BEAM_DEPTH", beam.depth),
+                table,
+            },
+            anytime: AnytimeConfig {
+                budget: Duration::from_millis(env_or(&var, "FILLER_ANYTIME_BUDGET_MS", budget.as_millis() as u64)),
+                ceiling: deadline.map_or(Duration::MAX, |d| d.mul_f64(anytime::CEILING_SHARE)),
+                bank: BankConfig {
+                    opening_share: env_or(&var, "FILLER_BANK_OPENING_SHARE", anytime.bank.opening_share),
+                    contact_turns: env_or(&var, "FILLER_BANK_CONTACT_TURNS", anytime.bank.contact_turns),
+                    max_factor: env_or(&var, "FILLER_BANK_MAX_FACTOR", anytime.bank.max_factor),
+                },
+                max_depth: env_or(&var, "FILLER_ANYTIME_MAX_DEPTH", anytime.max_depth),
+                width: env_or(&var, "FILLER_BEAM_WIDTH", anytime.width),
+                table,
+            },
+            ensemble: EnsembleConfig {
+                members: var("FILLER_ENSEMBLE")
+                    .and_then(|v| ensemble::parse_members(&v).ok())
+                    .unwrap_or(ensemble.members),
+                combine: env_or(&var, "FILLER_ENSEMBLE_COMBINE", ensemble.combine),
+            },
+            sampling: SamplingConfig {
+                above_cells: env_or(&var, "FILLER_SAMPLE_ABOVE", sampling.above_cells),
+                samples: env_or(&var, "FILLER_SAMPLE_COUNT", sampling.samples),
+            },
+            influence_decay: env_or(&var, "FILLER_INFLUENCE_DECAY", DEFAULT_INFLUENCE_DECAY),
+            weights: load_weights(&var),
+            profiles: match var("FILLER_WEIGHTS") {
+                Some(path) if !path.trim().is_empty() => Some("off".to_string()),
+                _ => var("FILLER_PROFILES").filter(|p| !p.trim().is_empty()),
+            },
+            aggression: env_or(&var, "FILLER_AGGRESSION", NEUTRAL_AGGRESSION),
+            patterns: var("FILLER_PATTERNS").filter(|p| !p.trim().is_empty()),
+            book: var("FILLER_BOOK").filter(|p| !p.trim().is_empty()),
+            model: var("FILLER_MODEL").filter(|p| !p.trim().is_empty()),
+            exploration: Exploration {
+                epsilon: env_or(&var, "FILLER_EXPLORE_EPSILON", 0.0),
+                temperature: env_or(&var, "FILLER_EXPLORE_TEMPERATURE", DEFAULT_EXPLORE_TEMPERATURE),
+            },
+            fog: env_or(&var, "FILLER_FOG", Stance::default()),
+            ties: env_or(&var, "FILLER_TIE_BREAK", TieBreak::default()),
+            deadline,
+        }
+    }
+
+    /// Build the configured strategy. Unknown names fall back to the default.
+    pub fn build_strategy(self) -> Strategy {
+        let name = self.strategy.trim().to_ascii_lowercase();
+        let info = STRATEGIES
+            .iter()
+            .find(|s| s.name == name)
+            .or_else(|| STRATEGIES.iter().find(|s| s.name == default_strategy()));
+        match info {
+            Some(info) => (info.build)(&self),
+            None => Strategy::Heuristic,
+        }
+    }
+
+    /// The positional score for the lookahead strategies.
+    pub fn evaluator(&self) -> Evaluator {
+        Evaluator::load(self.model.as_deref())
+    }
+
+    /// A game for `my_player` with everything configured.
+    pub fn build_game(self, my_player: u8) -> Game {
+        let (sampling, decay, weights, exploration, fog, ties) = (
+            self.sampling,
+            self.influence_decay,
+            self.weights.with_aggression(self.aggression),
+            self.exploration,
+            self.fog,
+            self.ties,
+        );
+        let patterns = match &self.patterns {
+            Some(path) => PatternLibrary::load(path).unwrap_or_else(|e| {
+                eprintln!("ignoring pattern file: {}", e);
+                PatternLibrary::builtin()
+            }),
+            None => PatternLibrary::builtin(),
+        };
+        let profiles = match self.profiles.as_deref() {
+            Some("off") => Profiles::default(),
+            Some(path) => Profiles::load(path).unwrap_or_else(|e| {
+                eprintln!("ignoring profiles file: {}", e);
+                Profiles::bundled()
+            }),
+            None => Profiles::bundled(),
+        }
+        .with_aggression(self.aggression);
+        let book = match self.book.as_deref() {
+            Some("off") => Book::default(),
+            Some(path) => Book::load(path).unwrap_or_else(|e| {
+                eprintln!("ignoring opening book: {}", e);
+                Book::bundled()
+            }),
+            None => Book::bundled(),
+        };
+        Game::new(my_player, self.build_strategy())
+            .with_patterns(patterns)
+            .with_book(book)
+            .with_sampling(sampling)
+            .with_influence_decay(decay)
+            .with_weights(weights)
+            .with_profiles(profiles)
+            .with_exploration(exploration)
+            .with_fog(fog)
+            .with_ties(ties)
+    }
+}
+
+/// Every setting at its default, whatever the environment says: what
+/// tests play with.
+impl Default for Config {
+    fn default() -> Self {
+        Config::read(|_| None)
+    }
+}
+
+/// A compiled-in strategy, as selected by FILLER_STRATEGY. This table is
+/// both what `build_strategy` picks from and what `filler strategies` prints.
+pub struct StrategyInfo {
+    pub name: &'static str,
+    pub summary: &'static str,
+    pub lookahead: &'static str,
+    pub time_control: &'static str,
+    /// Environment variables it reads, with their defaults.
+    params: fn() -> Vec<(&'static str, String)>,
+    /// Default weight profiles, one line each.
+    profiles: fn() -> Vec<String>,
+    build: fn(&Config) -> Strategy,
+}
+
+/// Listed by `filler strategies` in this order; which one plays by default
+/// is `default_strategy()`.
+pub const STRATEGIES: &[StrategyInfo] = &[
+    StrategyInfo {
+        name: "heuristic",
+        summary: "Hand-tuned rush/block scorer with per-phase weights",
+        lookahead: "none (one ply)",
+        time_control: "fixed work per turn; samples offsets above FILLER_SAMPLE_ABOVE cells",
+        params: || {
+            let sampling = SamplingConfig::default();
+            vec![
+                ("FILLER_SAMPLE_ABOVE", sampling.above_cells.to_string()),
+                ("FILLER_SAMPLE_COUNT", sampling.samples.to_string()),
+                ("FILLER_INFLUENCE_DECAY", DEFAULT_INFLUENCE_DECAY.to_string()),
+                ("FILLER_WEIGHTS", "(built-in, below)".to_string()),
+                ("FILLER_PROFILES", "(built-in, below)".to_string()),
+                ("FILLER_AGGRESSION", NEUTRAL_AGGRESSION.to_string()),
+                ("FILLER_PATTERNS", "(built-in, below)".to_string()),
+                ("FILLER_EXPLORE_EPSILON", "0".to_string()),
+                ("FILLER_EXPLORE_TEMPERATURE", DEFAULT_EXPLORE_TEMPERATURE.to_string()),
+                ("FILLER_FOG", "optimistic".to_string()),
+            ]
+        },
+        profiles: || {
+            let mut lines = Weights::default().toml_lines();
+            lines.push(String::new());
+            for pattern in PatternLibrary::builtin().patterns {
+                lines.push(format!("# pattern {}: {:+}", pattern.name, pattern.bonus));
+            }
+            for profile in Profiles::bundled().profiles {
+                lines.push(format!("# profile {}: {}", profile.name, profile.describe()));
+            }
+            lines
+        },
+        build: |_| Strategy::Heuristic,
+    },
+    StrategyInfo {
+        name: "mcts",
+        summary: "Open-loop Monte Carlo Tree Search with greedy rollouts",
+        lookahead: "FILLER_MCTS_ROLLOUT_DEPTH plies past the tree",
+        time_control: "fixed playout budget per turn, no clock",
+        params: || {
+            let mcts = MctsConfig::default();
+            vec![
+                ("FILLER_MCTS_PLAYOUTS", mcts.playouts.to_string()),
+                ("FILLER_MCTS_EXPLORATION", mcts.exploration.to_string()),
+                ("FILLER_MCTS_ROLLOUT_DEPTH", mcts.rollout_depth.to_string()),
+                ("FILLER_MODEL", "(voronoi share)".to_string()),
+            ]
+        },
+        profiles: || vec![format!("{:?}", MctsConfig::default())],
+        build: |config| Strategy::Mcts(Mcts::new(config.mcts).with_evaluator(config.evaluator())),
+    },
+    StrategyInfo {
+        name: "beam",
+        summary: "Beam search over our moves and greedy replies, Voronoi-scored",
+        lookahead: "FILLER_BEAM_DEPTH plies, future pieces from a piece-size prior",
+        time_control: "fixed beam per turn, no clock",
+        params: || {
+            let beam = BeamConfig::default();
+            vec![
+                ("FILLER_BEAM_WIDTH", beam.width.to_string()),
+                ("FILLER_BEAM_DEPTH", beam.depth.to_string()),
+                ("FILLER_TT_SIZE", beam.table.size.to_string()),
+                ("FILLER_TT_REPLACE", "shallower".to_string()),
+                ("FILLER_MODEL", "(voronoi share)".to_string()),
+            ]
+        },
+        profiles: || vec![format!("{:?}", BeamConfig::default())],
+        build: |config| Strategy::Beam(BeamSearch::new(config.beam).with_evaluator(config.evaluator())),
+    },
+    StrategyInfo {
+        name: "anytime",
+        summary: "Greedy answer first, then beam search one ply deeper at a time",
+        lookahead: "as deep as the budget allows, up to FILLER_ANYTIME_MAX_DEPTH plies",
+        time_control: "FILLER_ANYTIME_BUDGET_MS per turn, keeps the deepest finished pass",
+        params: || {
+            let anytime = AnytimeConfig::default();
+            vec![
+                (
+                    "FILLER_ANYTIME_BUDGET_MS",
+                    format!("{} of FILLER_DEADLINE_MS", anytime::BUDGET_SHARE),
+                ),
+                ("FILLER_ANYTIME_MAX_DEPTH", anytime.max_depth.to_string()),
+                ("FILLER_BANK_OPENING_SHARE", anytime.bank.opening_share.to_string()),
+                ("FILLER_BANK_CONTACT_TURNS", anytime.bank.contact_turns.to_string()),
+                ("FILLER_BANK_MAX_FACTOR", anytime.bank.max_factor.to_string()),
+                ("FILLER_BEAM_WIDTH", anytime.width.to_string()),
+                ("FILLER_TT_SIZE", anytime.table.size.to_string()),
+                ("FILLER_TT_REPLACE", "shallower".to_string()),
+                ("FILLER_MODEL", "(voronoi share)".to_string()),
+            ]
+        },
+        profiles: || vec![format!("{:?}", AnytimeConfig::default())],
+        build: |config| Strategy::Anytime(Anytime::new(config.anytime).with_evaluator(config.evaluator())),
+    },
+    StrategyInfo {
+        name: "ensemble",
+        summary: "Several strategies per turn, their proposals combined by weighted vote or best evaluation",
+        lookahead: "whatever its members look ahead",
+        time_control: "all of its members' time, one after another",
+        params: || {
+            let ensemble = EnsembleConfig::default();
+            let members: Vec<String> = ensemble.members.iter().map(|(name, w)| format!("{}:{}", name, w)).collect();
+            vec![
+                ("FILLER_ENSEMBLE", members.join(",")),
+                ("FILLER_ENSEMBLE_COMBINE", ensemble.combine.name().to_string()),
+            ]
+        },
+        profiles: || vec![format!("{:?}", EnsembleConfig::default())],
+        build: |config| Strategy::Ensemble(Ensemble::new(config.ensemble.clone())),
+    },
+    StrategyInfo {
+        name: "crawler",
+        summary: "Sparring partner: threads 1-wide channels along the walls toward the opponent",
+        lookahead: "none (one ply)",
+        time_control: "one scan per turn",
+        params: Vec::new,
+        profiles: Vec::new,
+        build: |_| Strategy::Crawler,
+    },
+    StrategyInfo {
+        name: "mirror",
+        summary: "Sparring partner: copies the opponent's last move through the centre of the board",
+        lookahead: "none (one ply)",
+        time_control: "one scan per turn",
+        params: Vec::new,
+        profiles: Vec::new,
+        build: |_| Strategy::Mirror,
+    },
+    StrategyInfo {
+        name: "greedy-area",
+        summary: "Sparring partner: takes whatever placement leaves it the most cells it reaches first",
+        lookahead: "none (one ply)",
+        time_control: "two distance fields per legal placement",
+        params: Vec::new,
+        profiles: Vec::new,
+        build: |_| Strategy::GreedyArea,
+    },
+];
+
+/// The sparring suite: simple fixed opponents, each probing one weakness,
+/// that a strategy should beat before it's made the default.
+pub const SPARRING: &[&str] = &["crawler", "mirror", "greedy-area"];
+
+/// Where the default strategy is recorded, next to Cargo.toml. Only
+/// `filler promote` should change it.
+pub const DEFAULT_STRATEGY_FILE: &str = "default_strategy";
+
+/// The strategy played when FILLER_STRATEGY isn't set, as last promoted.
+/// Compiled in, so a promotion ships with the next build.
+const DEFAULT_STRATEGY: &str = include_str!("../default_strategy");
+
+pub fn default_strategy() -> &'static str {
+    DEFAULT_STRATEGY.trim()
+}
+
+/// `filler strategies`: describe every entry of `STRATEGIES`.
+pub fn print_strategies() {
+    for (i, info) in STRATEGIES.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let default = if info.name == default_strategy() {
+            " (default)"
+        } else if SPARRING.contains(&info.name) {
+            " (sparring)"
+        } else {
+            ""
+        };
+        println!("{}{} - {}", info.name, default, info.summary);
+        println!("  lookahead:    {}", info.lookahead);
+        println!("  time control: {}", info.time_control);
+        println!("  parameters:");
+        for (key, value) in (info.params)() {
+            println!("    {} = {}", key, value);
+        }
+        println!("  default profile:");
+        for line in (info.profiles)() {
+            println!("    {}", line);
+        }
+    }
+}
+
+/// The weights file named by FILLER_WEIGHTS, or the built-in weights. A bad
+/// file is reported once and ignored: a typo mustn't cost a game.
+fn load_weights(var: &impl Fn(&str) -> Option<String>) -> Weights {
+    match var("FILLER_WEIGHTS") {
+        Some(path) if !path.trim().is_empty() => Weights::load(&path).unwrap_or_else(|e| {
+            eprintln!("ignoring weights file: {}", e);
+            Weights::default()
+        }),
+        _ => Weights::default(),
+    }
+}
+
+/// Parse an environment variable, ignoring it if missing or malformed.
+fn env_or<T: std::str::FromStr>(var: &impl Fn(&str) -> Option<String>, key: &str, default: T) -> T {
+    var(key)
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(default)
+}
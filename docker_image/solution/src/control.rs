@@ -0,0 +1,216 @@
+// src/control.rs
+// Optional read-only control channel for inspecting a running bot. With
+// FILLER_CONTROL=<path> the bot listens on a Unix socket there; a client
+// sends one query line and gets a plain-text answer:
+//
+//   turn      the current turn number
+//   cells     how many cells each side holds
+//   decision  the last move and its score breakdown
+//   timing    decision latency by phase
+//   status    all of the above
+//   explain R C
+//             the candidate placed at row R, col C: its score broken down,
+//             its rank, and the terms where the chosen move beats it
+//
+// e.g. `echo decision | nc -U /tmp/filler.sock`. Queries are answered on a
+// background thread from a shared snapshot, so the game's stdin and stdout
+// are never touched and nothing a client sends can change the play.
+
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::board::{Board, Census};
+use crate::game::{Decision, Phase, Term, OUTLOOK_TURNS};
+use crate::latency::LatencyReport;
+
+/// Candidates listed after the decision, for `explain`.
+const RUNNERS_UP: usize = 5;
+
+/// A client gets this long to send its query.
+const READ_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Default)]
+struct Status {
+    turn: usize,
+    placement: Option<(usize, usize)>,
+    census: Census,
+    decision: Option<Decision>,
+    latency: LatencyReport,
+}
+
+pub struct Control {
+    status: Arc<Mutex<Status>>,
+}
+
+impl Control {
+    /// Start listening if FILLER_CONTROL is set. A socket that can't be
+    /// opened is reported and the game goes on without it.
+    pub fn from_env() -> Option<Control> {
+        let path = std::env::var("FILLER_CONTROL").ok().filter(|p| !p.trim().is_empty())?;
+        // A stale socket from an earlier run would make bind fail
+        let _ = std::fs::remove_file(&path);
+        let listener = match UnixListener::bind(&path) {
+            Ok(listener) => listener,
+            Err(e) => {
+                eprintln!("no control socket: {}: {}", path, e);
+                return None;
+            }
+        };
+
+        let status = Arc::new(Mutex::new(Status::default()));
+        let shared = Arc::clone(&status);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                respond(stream, &shared);
+            }
+        });
+        Some(Control { status })
+    }
+
+    /// Publish the turn just played.
+    pub fn record(
+        &self,
+        turn: usize,
+        board: &Board,
+        elapsed: Duration,
+        placement: Option<(usize, usize)>,
+        census: Census,
+        decision: Option<Decision>,
+    ) {
+        let phase = decision.as_ref().map_or_else(|| Phase::of(board), |d| d.phase);
+        if let Ok(mut status) = self.status.lock() {
+            status.turn = turn;
+            status.placement = placement;
+            status.census = census;
+            status.decision = decision;
+            status.latency.record(phase, elapsed);
+        }
+    }
+}
+
+fn respond(stream: UnixStream, status: &Mutex<Status>) {
+    let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+    let mut query = String::new();
+    if BufReader::new(&stream).read_line(&mut query).is_err() {
+        return;
+    }
+    let answer = match status.lock() {
+        Ok(status) => answer(query.trim(), &status),
+        Err(_) => "unavailable".to_string(),
+    };
+    let mut stream = stream;
+    let _ = stream.write_all(answer.as_bytes());
+}
+
+fn answer(query: &str, status: &Status) -> String {
+    let turn = format!("turn {}\n", status.turn);
+    let cells = format!("cells: us {}, them {}\n", status.census.me, status.census.opponent);
+    let decision = decision_lines(status);
+    let timing = status.latency.lines().iter().map(|l| format!("{}\n", l)).collect::<String>();
+
+    match query {
+        "turn" => turn,
+        "cells" => cells,
+        "decision" => decision,
+        "timing" => timing,
+        "status" => format!("{}{}{}{}", turn, cells, decision, timing),
+        other => match parse_explain(other) {
+            Some(at) => explain_lines(status, at),
+            None => format!(
+                "unknown query `{}`; try turn, cells, decision, timing, status or explain <row> <col>\n",
+                other
+            ),
+        },
+    }
+}
+
+fn parse_explain(query: &str) -> Option<(usize, usize)> {
+    let mut words = query.strip_prefix("explain")?.split_whitespace();
+    let at = (words.next()?.parse().ok()?, words.next()?.parse().ok()?);
+    words.next().is_none().then_some(at)
+}
+
+/// One candidate against the move we made, term by term.
+fn explain_lines(status: &Status, (y, x): (usize, usize)) -> String {
+    let Some(decision) = &status.decision else {
+        return "no decision to explain\n".to_string();
+    };
+    if decision.ranking.is_empty() {
+        return "no candidates recorded this turn\n".to_string();
+    }
+    let Some((rank, candidate)) = decision.ranking.iter().enumerate().find(|(_, c)| c.placement == (y, x)) else {
+        return format!("row {}, col {} was not a candidate\n", y, x);
+    };
+    let total = |terms: &[Term]| terms.iter().map(|&(_, v)| v).sum::<i64>();
+
+    let mut out = format!(
+        "row {}, col {}: rank {} of {}, total {:+} against {:+} chosen\n",
+        y,
+        x,
+        rank + 1,
+        decision.ranking.len(),
+        total(&candidate.terms),
+        total(&decision.terms)
+    );
+    if candidate.passed_over {
+        out += "not shortlisted, so scored without the safe area\n";
+    }
+    out += &format!("  {:<20} {:>12} {:>12}\n", "term", "this", "chosen");
+    let chosen = |name: &str| decision.terms.iter().find(|&&(n, _)| n == name).map_or(0, |&(_, v)| v);
+    for &(name, value) in &candidate.terms {
+        if value != 0 || chosen(name) != 0 {
+            out += &format!("  {:<20} {:>+12} {:>+12}\n", name, value, chosen(name));
+        }
+    }
+
+    let mut losses: Vec<(&str, i64)> = candidate
+        .terms
+        .iter()
+        .map(|&(name, value)| (name, chosen(name) - value))
+        .filter(|&(_, gap)| gap > 0)
+        .collect();
+    losses.sort_by_key(|&(_, gap)| std::cmp::Reverse(gap));
+    if !losses.is_empty() {
+        out += "loses to the chosen move on:\n";
+        for (name, gap) in losses {
+            out += &format!("  {:<20} {:>+12}\n", name, -gap);
+        }
+    }
+    out
+}
+
+fn decision_lines(status: &Status) -> String {
+    let mut out = match status.placement {
+        Some((y, x)) => format!("placed at row {}, col {}\n", y, x),
+        None => "no placement\n".to_string(),
+    };
+    if let Some(decision) = &status.decision {
+        out += &format!("phase {}, {} candidates scored\n", decision.phase.name(), decision.candidates);
+        out += &format!("objective: {}, served {}%\n", decision.objective.0, decision.objective.1);
+        out += &format!(
+            "outlook over {} turns: us {:.0} cells, them {:.0}\n",
+            OUTLOOK_TURNS, decision.outlook.0, decision.outlook.1
+        );
+        out += &format!("standing: {}\n", decision.standing.name());
+        for &(name, value) in &decision.terms {
+            if value != 0 {
+                out += &format!("  {:<20} {:>+12}\n", name, value);
+            }
+        }
+        out += &format!("  {:<20} {:>+12}\n", "total", decision.terms.iter().map(|&(_, v)| v).sum::<i64>());
+        if decision.ranking.len() > 1 {
+            let next: Vec<String> = decision
+                .ranking
+                .iter()
+                .skip(1)
+                .take(RUNNERS_UP)
+                .map(|c| format!("({}, {})", c.placement.0, c.placement.1))
+                .collect();
+            out += &format!("runners-up: {}\n", next.join(" "));
+        }
+    }
+    out
+}
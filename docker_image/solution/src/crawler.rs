@@ -0,0 +1,47 @@
+// src/crawler.rs
+// A sparring partner, not a contender. The wall-crawler threads thin
+// channels along the board's edges toward the opponent: the one approach a
+// blocker that scores adjacency to the enemy tends to miss, since a channel
+// hugging the wall has hardly any enemy cells next to it until it's already
+// past. Play containment changes against it (`filler play --p2 crawler`)
+// to see whether they hold the edges.
+//
+// Every placement is scored the same simple way: new cells on the edge
+// count for it, new cells off the edge against it (the channel stays one
+// wide), and among equally good ones the nearer it gets to the opponent
+// the better. Ties go to the first in scan order, so it plays the same way
+// every time.
+
+use crate::board::{Board, Owner};
+use crate::piece::Piece;
+
+/// Worth of a new cell on the edge, and cost of one off it.
+const ON_EDGE: i64 = 2;
+const OFF_EDGE: i64 = 1;
+
+pub fn choose_move(board: &Board, piece: &Piece) -> Option<(usize, usize)> {
+    let toward = board.distance_field(Owner::Opponent);
+    let unreachable = (board.rows() + board.cols()) as i64;
+
+    let mut best: Option<((i64, i64), (usize, usize))> = None;
+    for (top_y, left_x) in board.legal_placements(piece, Owner::Me) {
+        let mut hugging = 0;
+        let mut nearest = unreachable;
+        for &(dy, dx) in &piece.cells {
+            let (y, x) = (top_y + dy, left_x + dx);
+            if !board.is(y, x, Owner::Empty) {
+                continue;
+            }
+            let on_edge = y == 0 || x == 0 || y + 1 == board.rows() || x + 1 == board.cols();
+            hugging += if on_edge { ON_EDGE } else { -OFF_EDGE };
+            if let Some(d) = toward.get(y, x).filter(|&d| d != usize::MAX) {
+                nearest = nearest.min(d as i64);
+            }
+        }
+        let score = (hugging, -nearest);
+        if best.is_none_or(|(top, _)| score > top) {
+            best = Some((score, (top_y, left_x)));
+        }
+    }
+    best.map(|(_, at)| at)
+}
@@ -0,0 +1,155 @@
+// src/ensemble.rs
+// Several strategies asked the same question every turn, as a hedge
+// against any one of them having a blind spot. By default the ensemble is
+// the heuristic (the blocker), the greedy Voronoi maximiser and the beam
+// search; each member is a whole game of its own, so it keeps its own
+// memory across turns exactly as it would playing alone.
+//
+// Their proposals are combined one of two ways:
+//
+// - `vote` (the default): each proposal gets its member's weight, and the
+//   placement with the most wins. A tie goes to whichever is evaluated
+//   best below.
+// - `best`: every proposal is played out on a copy of the board and the
+//   one whose position `mcts::evaluate` scores highest wins, the outcome a
+//   member predicts rather than how many members predict it.
+//
+// Members run one after another, so a turn takes as long as all of them.
+
+use std::str::FromStr;
+
+use crate::board::{Board, Owner};
+use crate::config::{Config, STRATEGIES};
+use crate::game::Game;
+use crate::mcts;
+use crate::piece::Piece;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Combine {
+    /// Most member weight behind a placement.
+    #[default]
+    Vote,
+    /// Best evaluated position after the placement.
+    Best,
+}
+
+impl Combine {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Combine::Vote => "vote",
+            Combine::Best => "best",
+        }
+    }
+}
+
+impl FromStr for Combine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "vote" => Ok(Combine::Vote),
+            "best" => Ok(Combine::Best),
+            other => Err(format!("unknown way to combine proposals `{}`", other)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EnsembleConfig {
+    /// Member strategies by name, with their voting weights.
+    pub members: Vec<(String, f64)>,
+    pub combine: Combine,
+}
+
+impl Default for EnsembleConfig {
+    fn default() -> Self {
+        EnsembleConfig {
+            members: vec![
+                ("heuristic".to_string(), 2.0),
+                ("greedy-area".to_string(), 1.0),
+                ("beam".to_string(), 1.0),
+            ],
+            combine: Combine::default(),
+        }
+    }
+}
+
+/// Members as FILLER_ENSEMBLE lists them: `name[:weight],...`, weight 1 if
+/// left out. An ensemble can't be its own member.
+pub fn parse_members(text: &str) -> Result<Vec<(String, f64)>, String> {
+    let mut members = Vec::new();
+    for entry in text.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (name, weight) = match entry.split_once(':') {
+            Some((name, weight)) => {
+                let weight: f64 = weight.trim().parse().map_err(|_| format!("bad weight in `{}`", entry))?;
+                (name.trim(), weight)
+            }
+            None => (entry, 1.0),
+        };
+        let name = name.to_ascii_lowercase();
+        if name == "ensemble" || !STRATEGIES.iter().any(|s| s.name == name) {
+            return Err(format!("`{}` can't be an ensemble member", name));
+        }
+        members.push((name, weight));
+    }
+    if members.is_empty() {
+        return Err("an ensemble needs at least one member".to_string());
+    }
+    Ok(members)
+}
+
+pub struct Ensemble {
+    combine: Combine,
+    members: Vec<(Game, f64)>,
+}
+
+impl Ensemble {
+    pub fn new(config: EnsembleConfig) -> Self {
+        let members = config
+            .members
+            .into_iter()
+            .map(|(name, weight)| {
+                let mut member = Config::from_env();
+                member.strategy = name;
+                (member.build_game(1), weight)
+            })
+            .collect();
+        Ensemble {
+            combine: config.combine,
+            members,
+        }
+    }
+
+    pub fn choose_move(&mut self, board: &Board, piece: &Piece) -> Option<(usize, usize)> {
+        // Proposals with the weight behind each and how the position after
+        // it evaluates, in the order the members first made them
+        let mut proposals: Vec<((usize, usize), f64, f64)> = Vec::new();
+        for (member, weight) in &mut self.members {
+            let Some(at) = member.choose_best_move(board, piece) else {
+                continue;
+            };
+            debug!("[DEBUG] Ensemble: {} proposes {:?}", member.strategy.name(), at);
+            match proposals.iter_mut().find(|(placement, _, _)| *placement == at) {
+                Some((_, votes, _)) => *votes += *weight,
+                None => {
+                    let mut after = board.clone();
+                    after.place(piece, at.0, at.1, Owner::Me);
+                    proposals.push((at, *weight, mcts::evaluate(&after)));
+                }
+            }
+        }
+
+        let key = |&(_, votes, value): &((usize, usize), f64, f64)| match self.combine {
+            Combine::Vote => (votes, value),
+            Combine::Best => (value, votes),
+        };
+        // The first of equals, so members listed earlier win ties
+        let chosen = proposals
+            .iter()
+            .rev()
+            .max_by(|a, b| key(a).partial_cmp(&key(b)).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|&(at, _, _)| at);
+        debug!("[DEBUG] Ensemble: {} of {} distinct proposals, played {:?}", self.combine.name(), proposals.len(), chosen);
+        chosen
+    }
+}
@@ -0,0 +1,132 @@
+// src/eval.rs
+// The positional score the lookahead strategies (beam, anytime, MCTS) rank
+// boards by, as our expected share of the board from 0 to 1. Built in it's
+// the Voronoi share (see `mcts::evaluate`). A build with `--features onnx`
+// can load a network trained offline instead: FILLER_MODEL names an ONNX
+// file, run through onnxruntime (loaded at startup, from ORT_DYLIB_PATH or
+// the system library path, so the bot itself stays a single binary).
+//
+// The model sees the board as four planes, each rows x cols from our side:
+// our cells, theirs, empty cells, and the frontier (empty cells next to
+// anyone's). The input is float32 [1, 4, rows, cols] and the first output's
+// first value is read as our share. A model that fails to load or to run
+// is dropped with a warning, and the Voronoi share takes over.
+
+use crate::board::Board;
+#[cfg(feature = "onnx")]
+use crate::board::Owner;
+#[cfg(feature = "onnx")]
+use crate::grid;
+use crate::mcts;
+
+/// Input planes per cell, in the order above.
+#[cfg(feature = "onnx")]
+const PLANES: usize = 4;
+
+pub enum Evaluator {
+    Voronoi,
+    #[cfg(feature = "onnx")]
+    Net(net::Net),
+}
+
+impl Evaluator {
+    /// The evaluator for FILLER_MODEL, if it's set.
+    pub fn load(model: Option<&str>) -> Evaluator {
+        match model {
+            None => Evaluator::Voronoi,
+            #[cfg(feature = "onnx")]
+            Some(path) => net::Net::load(path).map(Evaluator::Net).unwrap_or_else(|e| {
+                eprintln!("ignoring model: {}", e);
+                Evaluator::Voronoi
+            }),
+            #[cfg(not(feature = "onnx"))]
+            Some(path) => {
+                eprintln!("ignoring model {}: built without the onnx feature", path);
+                Evaluator::Voronoi
+            }
+        }
+    }
+
+    pub fn score(&mut self, board: &Board) -> f64 {
+        match self {
+            Evaluator::Voronoi => mcts::evaluate(board),
+            #[cfg(feature = "onnx")]
+            Evaluator::Net(net) => match net.score(&planes(board), board.rows(), board.cols()) {
+                Ok(share) => share.clamp(0.0, 1.0),
+                Err(e) => {
+                    eprintln!("dropping model: {}", e);
+                    *self = Evaluator::Voronoi;
+                    mcts::evaluate(board)
+                }
+            },
+        }
+    }
+}
+
+/// `board` as the model's input, plane by plane, row by row.
+#[cfg(feature = "onnx")]
+fn planes(board: &Board) -> Vec<f32> {
+    let (rows, cols) = (board.rows(), board.cols());
+    let mut planes = vec![0.0; PLANES * rows * cols];
+    for y in 0..rows {
+        for x in 0..cols {
+            let cell = y * cols + x;
+            let plane = if board.is(y, x, Owner::Me) {
+                0
+            } else if board.is(y, x, Owner::Opponent) {
+                1
+            } else if board.is(y, x, Owner::Empty) {
+                2
+            } else {
+                continue;
+            };
+            grid::write(&mut planes, plane * rows * cols + cell, 1.0);
+
+            let taken = |(ny, nx)| board.is(ny, nx, Owner::Me) || board.is(ny, nx, Owner::Opponent);
+            if plane == 2 && board.neighbors(y, x).any(taken) {
+                grid::write(&mut planes, 3 * rows * cols + cell, 1.0);
+            }
+        }
+    }
+    planes
+}
+
+#[cfg(feature = "onnx")]
+mod net {
+    use std::panic;
+
+    use ort::session::Session;
+    use ort::value::Tensor;
+
+    use super::PLANES;
+
+    pub struct Net {
+        path: String,
+        session: Session,
+    }
+
+    impl Net {
+        pub fn load(path: &str) -> Result<Net, String> {
+            // ort panics, rather than failing, when onnxruntime itself is missing
+            let built = panic::catch_unwind(|| Session::builder().and_then(|builder| builder.commit_from_file(path)))
+                .map_err(|_| format!("{}: can't load onnxruntime (see ORT_DYLIB_PATH)", path))?;
+            let session = built.map_err(|e| format!("{}: {}", path, e))?;
+            Ok(Net {
+                path: path.to_string(),
+                session,
+            })
+        }
+
+        pub fn score(&mut self, planes: &[f32], rows: usize, cols: usize) -> Result<f64, String> {
+            let fail = |e: ort::Error| format!("{}: {}", self.path, e);
+            let input = Tensor::from_array(([1, PLANES, rows, cols], planes.to_vec())).map_err(fail)?;
+            let outputs = self.session.run(ort::inputs![input]).map_err(fail)?;
+            let output = outputs.values().next().ok_or(format!("{}: no output", self.path))?;
+            let (_, values) = output.try_extract_tensor::<f32>().map_err(fail)?;
+            values
+                .first()
+                .map(|&share| share as f64)
+                .ok_or(format!("{}: empty output", self.path))
+        }
+    }
+}
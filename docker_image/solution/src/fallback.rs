@@ -0,0 +1,355 @@
+// src/fallback.rs
+// What we print when the normal decision can't be used: the input didn't
+// parse, the strategy found nothing, panicked, answered with an illegal
+// placement or ran past the deadline. Every one of those goes through
+// `FallbackMovePolicy`, which tries, in order:
+//
+//   1. a legal placement close to the last one we made
+//   2. the first legal placement a plain scan finds
+//   3. `PASS`, which the engine takes as us giving up
+//
+// Whatever happens, exactly one well-formed "X Y" line goes out per turn.
+
+use std::io::Write;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::board::{Board, Owner};
+use crate::piece::Piece;
+
+/// Printed when there is no legal placement to fall back on. The engine
+/// rejects it like any other illegal move, which ends our part of the game.
+pub const PASS: (usize, usize) = (0, 0);
+
+/// How far from the last placement, per axis, the cheap search looks.
+const NEAR_LAST: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Failure {
+    /// The board or piece block didn't parse.
+    Parse,
+    /// The strategy returned no placement.
+    NoCandidates,
+    /// The strategy returned a placement the board doesn't allow.
+    Illegal,
+    /// The strategy panicked.
+    Panic,
+    /// The strategy took longer than the deadline.
+    Deadline,
+}
+
+impl Failure {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Failure::Parse => "parse error",
+            Failure::NoCandidates => "no candidates",
+            Failure::Illegal => "illegal placement",
+            Failure::Panic => "panic",
+            Failure::Deadline => "deadline",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct FallbackMovePolicy {
+    /// Top-left of the last placement we printed that was legal.
+    last: Option<(usize, usize)>,
+}
+
+impl FallbackMovePolicy {
+    /// The placement to print for the strategy's answer: `chosen` itself
+    /// when it's legal, the fallback otherwise.
+    pub fn settle(
+        &mut self,
+        board: &Board,
+        piece: &Piece,
+        chosen: Result<Option<(usize, usize)>, Failure>,
+    ) -> (usize, usize) {
+        let failure = match chosen {
+            Ok(Some((y, x))) if board.can_place(piece, y, x, Owner::Me) => {
+                self.last = Some((y, x));
+                return (y, x);
+            }
+            Ok(Some(_)) => Failure::Illegal,
+            Ok(None) => Failure::NoCandidates,
+            Err(failure) => failure,
+        };
+        self.recover(failure, Some(board), Some(piece))
+    }
+
+    /// The fallback placement after `failure`. Without a board or piece
+    /// nothing can be checked, so that's a pass.
+    pub fn recover(&mut self, failure: Failure, board: Option<&Board>, piece: Option<&Piece>) -> (usize, usize) {
+        let placement = match (board, piece) {
+            (Some(board), Some(piece)) => self.fallback(board, piece),
+            _ => None,
+        };
+        debug!("[DEBUG] {}, falling back to {:?}", failure.name(), placement);
+        if let Some(placement) = placement {
+            self.last = Some(placement);
+        }
+        placement.unwrap_or(PASS)
+    }
+
+    /// A legal placement near the last one, else the first one found.
+    pub fn fallback(&self, board: &Board, piece: &Piece) -> Option<(usize, usize)> {
+        self.near_last(board, piece).or_else(|| first_legal(board, piece))
+    }
+
+    /// Our newest cells are next to the last placement, so that's where a
+    /// legal one is most likely.
+    fn near_last(&self, board: &Board, piece: &Piece) -> Option<(usize, usize)> {
+        let (ly, lx) = self.last?;
+        let rows = ly.saturating_sub(NEAR_LAST)..=ly + NEAR_LAST;
+        rows.flat_map(|y| (lx.saturating_sub(NEAR_LAST)..=lx + NEAR_LAST).map(move |x| (y, x)))
+            .filter(|&(y, x)| board.can_place(piece, y, x, Owner::Me))
+            .min_by_key(|&(y, x)| y.abs_diff(ly) + x.abs_diff(lx))
+    }
+}
+
+fn first_legal(board: &Board, piece: &Piece) -> Option<(usize, usize)> {
+    board
+        .placement_window(piece, Owner::Me)
+        .find(|&(y, x)| board.can_place(piece, y, x, Owner::Me))
+}
+
+/// Run a decision, turning a panic into `Failure::Panic`.
+pub fn guarded(decide: impl FnOnce() -> Option<(usize, usize)>) -> Result<Option<(usize, usize)>, Failure> {
+    panic::catch_unwind(AssertUnwindSafe(decide)).map_err(|_| Failure::Panic)
+}
+
+/// Send panic messages through `debug!` rather than straight to stderr,
+/// which the engine never drains (see main.rs).
+pub fn quiet_panics() {
+    panic::set_hook(Box::new(|info| debug!("[DEBUG] {}", info)));
+}
+
+/// The one place turn answers are written. A turn is answered by whichever
+/// comes first: `answer` from the game loop, or the watchdog thread printing
+/// the armed fallback once the deadline passes. The other is dropped.
+/// Answers are in the engine's terms, see `Piece::declared`.
+pub struct Watchdog {
+    shared: Arc<(Mutex<Pending>, Condvar)>,
+}
+
+struct Pending {
+    out: Box<dyn Write + Send>,
+    /// The turn being decided, its fallback and when it's due.
+    armed: Option<(usize, (i64, i64), Instant)>,
+    answered: usize,
+}
+
+impl Watchdog {
+    /// Answers go to `out`. With no deadline nothing is answered early.
+    pub fn new(out: impl Write + Send + 'static, deadline: Option<Duration>) -> Watchdog {
+        let shared = Arc::new((
+            Mutex::new(Pending {
+                out: Box::new(out),
+                armed: None,
+                answered: 0,
+            }),
+            Condvar::new(),
+        ));
+        if let Some(deadline) = deadline {
+            let watched = Arc::clone(&shared);
+            thread::spawn(move || watch(&watched, deadline));
+        }
+        Watchdog { shared }
+    }
+
+    /// Start the clock on `turn`, with `fallback` ready to go out if the
+    /// decision misses the deadline.
+    pub fn arm(&self, turn: usize, fallback: (i64, i64)) {
+        let (lock, wake) = &*self.shared;
+        if let Ok(mut pending) = lock.lock() {
+            pending.armed = Some((turn, fallback, Instant::now()));
+            wake.notify_all();
+        }
+    }
+
+    /// Print `placement` for `turn` unless the watchdog already answered it.
+    /// Returns whether this was the answer that went out.
+    pub fn answer(&self, turn: usize, placement: (i64, i64)) -> bool {
+        let (lock, _) = &*self.shared;
+        let Ok(mut pending) = lock.lock() else {
+            return false;
+        };
+        if pending.answered >= turn {
+            debug!("[DEBUG] Turn {} already answered on the deadline, dropping {:?}", turn, placement);
+            return false;
+        }
+        pending.answered = turn;
+        pending.armed = None;
+        emit(&mut pending.out, placement);
+        true
+    }
+}
+
+fn watch(shared: &(Mutex<Pending>, Condvar), deadline: Duration) {
+    let (lock, wake) = shared;
+    let Ok(mut pending) = lock.lock() else {
+        return;
+    };
+    loop {
+        let Some((turn, fallback, started)) = pending.armed else {
+            pending = match wake.wait(pending) {
+                Ok(pending) => pending,
+                Err(_) => return,
+            };
+            continue;
+        };
+
+        let left = deadline.saturating_sub(started.elapsed());
+        if left.is_zero() {
+            debug!("[DEBUG] {} on turn {}, answering {:?}", Failure::Deadline.name(), turn, fallback);
+            pending.answered = turn;
+            pending.armed = None;
+            emit(&mut pending.out, fallback);
+            continue;
+        }
+        pending = match wake.wait_timeout(pending, left) {
+            Ok((pending, _)) => pending,
+            Err(_) => return,
+        };
+    }
+}
+
+/// The engine wants "X Y": column first, then row.
+fn emit(out: &mut dyn Write, (y, x): (i64, i64)) {
+    let _ = writeln!(out, "{} {}", x, y);
+    let _ = out.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A `Write` the test can read back after the watchdog has used it.
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Captured {
+        fn lines(&self) -> Vec<String> {
+            String::from_utf8(self.0.lock().unwrap().clone())
+                .unwrap()
+                .lines()
+                .map(|l| l.to_string())
+                .collect()
+        }
+    }
+
+    fn board(rows: &[&str]) -> Board {
+        let mut lines = vec![format!("Anfield {} {}:", rows[0].len(), rows.len())];
+        lines.extend(rows.iter().enumerate().map(|(i, r)| format!("{:03} {}", i, r)));
+        Board::from_anfield_lines(&lines, 1).unwrap()
+    }
+
+    fn piece(rows: &[&str]) -> Piece {
+        let mut lines = vec![format!("Piece {} {}:", rows[0].len(), rows.len())];
+        lines.extend(rows.iter().map(|r| r.to_string()));
+        Piece::from_piece_lines(&lines).unwrap()
+    }
+
+    fn well_formed(line: &str) -> bool {
+        let parts: Vec<&str> = line.split(' ').collect();
+        parts.len() == 2 && parts.iter().all(|p| p.parse::<i64>().is_ok())
+    }
+
+    #[test]
+    fn unparsed_input_passes() {
+        let mut policy = FallbackMovePolicy::default();
+        assert_eq!(policy.recover(Failure::Parse, None, None), PASS);
+        let b = board(&["@...", "...$"]);
+        assert_eq!(policy.recover(Failure::Parse, Some(&b), None), PASS);
+    }
+
+    #[test]
+    fn no_candidates_falls_back_to_a_legal_placement() {
+        let mut policy = FallbackMovePolicy::default();
+        let (b, p) = (board(&["....", ".@..", "...$"]), piece(&["**"]));
+        let (y, x) = policy.settle(&b, &p, Ok(None));
+        assert!(b.can_place(&p, y, x, Owner::Me));
+    }
+
+    #[test]
+    fn illegal_answer_is_replaced() {
+        let mut policy = FallbackMovePolicy::default();
+        let (b, p) = (board(&["....", ".@..", "...$"]), piece(&["**"]));
+        let (y, x) = policy.settle(&b, &p, Ok(Some((2, 2))));
+        assert_ne!((y, x), (2, 2));
+        assert!(b.can_place(&p, y, x, Owner::Me));
+    }
+
+    #[test]
+    fn nothing_legal_passes() {
+        let mut policy = FallbackMovePolicy::default();
+        let (b, p) = (board(&["@$", "$$"]), piece(&["**"]));
+        assert_eq!(policy.settle(&b, &p, Ok(None)), PASS);
+    }
+
+    #[test]
+    fn fallback_prefers_the_last_placement() {
+        let mut policy = FallbackMovePolicy::default();
+        let b = board(&["@@.......", ".........", "........@", "........$"]);
+        let p = piece(&["*", "*"]);
+        assert_eq!(policy.settle(&b, &p, Ok(Some((1, 8)))), (1, 8));
+        let (y, x) = policy.settle(&b, &p, Err(Failure::Panic));
+        assert!(b.can_place(&p, y, x, Owner::Me));
+        assert!(x >= 8 - NEAR_LAST, "expected a placement near the last one, got ({}, {})", y, x);
+    }
+
+    #[test]
+    fn margins_are_trimmed_and_answered_as_declared() {
+        let mut policy = FallbackMovePolicy::default();
+        let (b, p) = (board(&["@...", "...$"]), piece(&["..", ".*", ".*"]));
+        assert_eq!((p.height, p.width, p.margin), (2, 1, (1, 1)));
+        assert_eq!(policy.settle(&b, &p, Ok(None)), (0, 0));
+
+        let out = Captured::default();
+        let watchdog = Watchdog::new(out.clone(), None);
+        assert!(watchdog.answer(1, p.declared((0, 0))));
+        assert_eq!(out.lines(), ["-1 -1"]);
+        assert!(out.lines().iter().all(|l| well_formed(l)));
+    }
+
+    #[test]
+    fn panics_become_failures() {
+        assert_eq!(guarded(|| panic!("strategy bug")), Err(Failure::Panic));
+        assert_eq!(guarded(|| Some((1, 2))), Ok(Some((1, 2))));
+    }
+
+    #[test]
+    fn deadline_answers_once_with_the_fallback() {
+        let out = Captured::default();
+        let watchdog = Watchdog::new(out.clone(), Some(Duration::from_millis(10)));
+        watchdog.arm(1, (2, 3));
+        thread::sleep(Duration::from_millis(200));
+        assert!(!watchdog.answer(1, (4, 5)), "late answer went out");
+        assert!(watchdog.answer(2, (6, 7)));
+        assert_eq!(out.lines(), ["3 2", "7 6"]);
+        assert!(out.lines().iter().all(|l| well_formed(l)));
+    }
+
+    #[test]
+    fn answers_in_time_beat_the_deadline() {
+        let out = Captured::default();
+        let watchdog = Watchdog::new(out.clone(), Some(Duration::from_millis(200)));
+        watchdog.arm(1, (2, 3));
+        assert!(watchdog.answer(1, (4, 5)));
+        thread::sleep(Duration::from_millis(300));
+        assert_eq!(out.lines(), ["5 4"]);
+    }
+}
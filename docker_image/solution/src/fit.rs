@@ -0,0 +1,480 @@
+// src/fit.rs
+// `filler fit`: fit the heuristic's weights to recorded games instead of
+// searching for them by self-play. A replay is the transcript the bundled
+// engine prints (board, piece and answer for every turn, then the winner),
+// so any game against any robot can be learned from, not just our own.
+//
+// Each side's turns are played back through a heuristic with every weight
+// at 1 and `Game::explain` on, which breaks every candidate down into the
+// raw value of each scoring term. A turn is labelled by whether the side
+// that played it went on to win, and a logistic regression per phase fits
+// weights under which the winners' moves come out on top of their
+// candidates and the losers' don't. Scaled to the size of the current
+// weights, those are the new weights.
+//
+// `safe_area`, `denial` and `fragment` are only computed for the
+// shortlist, so most candidates report them as 0; they keep their current
+// weights, as does any term that never varied in the replays.
+//
+// `--winprob` fits the win-probability model (winprob.rs) from the same
+// replays instead: every turn's board from the mover's side, labelled by
+// whether the mover won. Its weights are compiled in, so they are printed
+// rather than written.
+
+use std::fs;
+
+use crate::board::Board;
+use crate::config::Config;
+use crate::game::{Game, Phase, Strategy, Term};
+use crate::piece::Piece;
+use crate::weights::{PhaseWeights, Weights};
+use crate::winprob::{self, Features};
+
+const USAGE: &str = "usage: filler fit <replay>... [--winprob] [--out FILE] [--epochs N] [--rate X] [--l2 X]";
+
+/// Terms only the shortlist is scored with (see `game::SAFE_SHORTLIST`).
+const SHORTLIST_ONLY: &[&str] = &["safe_area", "denial", "fragment"];
+
+/// A phase with fewer usable turns than this keeps its weights.
+const MIN_TURNS: usize = 20;
+
+/// Gradient descent: passes over the data, step size, and the L2 penalty
+/// that keeps terms the replays say little about near zero.
+const EPOCHS: usize = 200;
+const RATE: f64 = 1.0;
+const L2: f64 = 0.001;
+
+/// One game as the engine printed it.
+#[derive(Default)]
+struct Replay {
+    turns: Vec<Played>,
+    winner: Option<u8>,
+}
+
+/// What the mover was shown, and where it answered as (top_y, left_x) of
+/// the piece as declared (see `Piece::declared`).
+struct Played {
+    player: u8,
+    board: Vec<String>,
+    piece: Vec<String>,
+    answer: Option<(i64, i64)>,
+}
+
+/// A turn's training row.
+struct Sample {
+    phase: Phase,
+    won: bool,
+    /// Every candidate's raw terms, the played one first.
+    candidates: Vec<Vec<f64>>,
+}
+
+pub fn run_cli(args: &[String]) -> Result<(), String> {
+    let mut paths: Vec<&str> = Vec::new();
+    let mut out = "fitted.toml".to_string();
+    let mut epochs = EPOCHS;
+    let mut rate = RATE;
+    let mut l2 = L2;
+    let mut win_model = false;
+
+    let mut i = 0;
+    while let Some(arg) = args.get(i) {
+        let value = args.get(i + 1);
+        let number = |name: &str| -> Result<f64, String> {
+            value
+                .and_then(|v| v.parse().ok())
+                .ok_or(format!("{} expects a number", name))
+        };
+        match arg.as_str() {
+            "--out" => out = value.cloned().ok_or("--out expects a path")?,
+            "--epochs" => epochs = number("--epochs")?.max(1.0) as usize,
+            "--rate" => rate = number("--rate")?,
+            "--l2" => l2 = number("--l2")?,
+            "--winprob" => {
+                win_model = true;
+                i += 1;
+                continue;
+            }
+            other if other.starts_with("--") => return Err(USAGE.to_string()),
+            other => {
+                paths.push(other);
+                i += 1;
+                continue;
+            }
+        }
+        i += 2;
+    }
+    if paths.is_empty() {
+        return Err(USAGE.to_string());
+    }
+
+    let mut replays = Vec::new();
+    for path in &paths {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        replays.extend(parse_replays(&text));
+    }
+    let decided = replays.iter().filter(|r| r.winner.is_some()).count();
+    if win_model {
+        fit_win_model(&replays, epochs, rate, l2);
+        return Ok(());
+    }
+
+    let mut unit = Weights::default();
+    for phase in Phase::ALL {
+        *unit.phase_mut(phase) = PhaseWeights::uniform(1);
+    }
+    let mut names: Vec<&'static str> = Vec::new();
+    let mut samples = Vec::new();
+    for replay in &replays {
+        samples.extend(replay_samples(replay, unit, &mut names));
+    }
+    println!(
+        "{} replay(s), {} with a winner, {} usable turns over {} terms",
+        replays.len(),
+        decided,
+        samples.len(),
+        names.len()
+    );
+
+    let base = Config::from_env().weights;
+    let mut fitted = base;
+    for phase in Phase::ALL {
+        let rows: Vec<&Sample> = samples.iter().filter(|s| s.phase == phase).collect();
+        if rows.len() < MIN_TURNS {
+            println!("{}: {} turns, too few to fit", phase.name(), rows.len());
+            continue;
+        }
+
+        let current: Vec<i64> = names.iter().map(|name| weight(&base, phase, name)).collect();
+        let proposed = fit_phase(&rows, &current, epochs, rate, l2);
+        for (name, &value) in names.iter().zip(&proposed) {
+            if let Some(slot) = fitted.phase_mut(phase).get_mut(name) {
+                *slot = value;
+            }
+        }
+
+        println!(
+            "{}: {} turns, agrees with the winner {:.1}% -> {:.1}%",
+            phase.name(),
+            rows.len(),
+            100.0 * agreement(&rows, &current),
+            100.0 * agreement(&rows, &proposed)
+        );
+        for ((name, old), new) in names.iter().zip(&current).zip(&proposed) {
+            if old != new {
+                println!("  {:<20} {:>9} -> {:>9}", name, old, new);
+            }
+        }
+    }
+
+    fitted.save(&out).map_err(|e| format!("{}: {}", out, e))?;
+    println!("wrote {}", out);
+    Ok(())
+}
+
+/// Every game in an engine transcript; several can be concatenated.
+fn parse_replays(text: &str) -> Vec<Replay> {
+    let mut replays = Vec::new();
+    let mut current = Replay::default();
+    let (mut board, mut piece) = (Vec::new(), Vec::new());
+
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("$$$ exec p1") && !current.turns.is_empty() {
+            replays.push(std::mem::take(&mut current));
+        } else if trimmed.starts_with("Anfield") || trimmed.starts_with("Piece") {
+            // `<kind> <width> <height>:` then the rows, and a ruler over the board
+            let is_board = trimmed.starts_with("Anfield");
+            let height = trimmed
+                .split_whitespace()
+                .nth(2)
+                .and_then(|h| h.trim_end_matches(':').parse::<usize>().ok())
+                .unwrap_or(0);
+            let mut block = vec![line.to_string()];
+            block.extend(lines.by_ref().take(height + usize::from(is_board)).map(str::to_string));
+            if is_board {
+                board = block;
+            } else {
+                piece = block;
+            }
+        } else if let Some(rest) = trimmed.strip_prefix("-> Answer (") {
+            let player = if rest.starts_with('@') { 1 } else { 2 };
+            let answer = rest.split_once(':').and_then(|(_, xy)| {
+                let mut xy = xy.split_whitespace().map(|v| v.parse::<i64>().ok());
+                match (xy.next().flatten(), xy.next().flatten()) {
+                    (Some(x), Some(y)) => Some((y, x)),
+                    _ => None,
+                }
+            });
+            current.turns.push(Played {
+                player,
+                board: std::mem::take(&mut board),
+                piece: std::mem::take(&mut piece),
+                answer,
+            });
+        } else if let Some(who) = trimmed.strip_suffix(" won!") {
+            current.winner = who.strip_prefix("Player").and_then(|n| n.parse().ok());
+        }
+    }
+    if !current.turns.is_empty() {
+        replays.push(current);
+    }
+    replays
+}
+
+/// Play both sides of `replay` back and turn every move the heuristic
+/// could have made into a sample. `names` is filled with the terms'
+/// names on first use.
+fn replay_samples(replay: &Replay, unit: Weights, names: &mut Vec<&'static str>) -> Vec<Sample> {
+    let Some(winner) = replay.winner else {
+        return Vec::new();
+    };
+    let mut players = [1u8, 2].map(|p| {
+        let mut game = Game::new(p, Strategy::Heuristic).with_weights(unit);
+        game.explain = true;
+        game
+    });
+
+    let mut samples = Vec::new();
+    for turn in &replay.turns {
+        let (Some(board), Some(piece)) = (
+            Board::from_anfield_lines(&turn.board, turn.player),
+            Piece::from_piece_lines(&turn.piece),
+        ) else {
+            continue;
+        };
+        let Some(game) = players.get_mut(turn.player as usize - 1) else {
+            continue;
+        };
+        game.choose_best_move(&board, &piece);
+
+        // Packing turns, book moves and answers the heuristic never
+        // considered have nothing to learn from
+        let Some(decision) = game.last_decision.take() else {
+            continue;
+        };
+        let Some((played, chosen)) = decision
+            .ranking
+            .iter()
+            .enumerate()
+            .find(|(_, c)| Some(piece.declared(c.placement)) == turn.answer)
+        else {
+            continue;
+        };
+        if chosen.terms.len() < 2 {
+            continue;
+        }
+        if names.is_empty() {
+            *names = fitted_terms(&chosen.terms).map(|(name, _)| name).collect();
+        }
+
+        let raw = |terms: &[Term]| -> Vec<f64> { fitted_terms(terms).map(|(_, value)| value as f64).collect() };
+        let mut candidates = vec![raw(&chosen.terms)];
+        candidates.extend(
+            decision
+                .ranking
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| i != played)
+                .map(|(_, c)| raw(&c.terms)),
+        );
+        if candidates.iter().any(|c| c.len() != names.len()) {
+            continue;
+        }
+
+        samples.push(Sample {
+            phase: decision.phase,
+            won: turn.player == winner,
+            candidates,
+        });
+    }
+    samples
+}
+
+fn fitted_terms(terms: &[Term]) -> impl Iterator<Item = Term> + '_ {
+    terms.iter().copied().filter(|(name, _)| !SHORTLIST_ONLY.contains(name))
+}
+
+fn weight(weights: &Weights, phase: Phase, name: &str) -> i64 {
+    let mut copy = *weights.phase(phase);
+    copy.get_mut(name).map_or(0, |w| *w)
+}
+
+/// Conditional logistic regression over each turn's candidates, by
+/// gradient descent on standardised terms: the chance the move played is
+/// the one picked is the softmax of the candidates' scores, pushed up on
+/// the winner's turns and down on the loser's. The coefficients come back
+/// as integer weights, scaled so that the terms together count for as much
+/// as they do under `current`; a term that never varied keeps its weight.
+///
+/// Indexes freely: every candidate has a value per term of `current`
+/// (`replay_samples` drops turns where they don't), and this only runs
+/// offline.
+#[allow(clippy::indexing_slicing)]
+fn fit_phase(rows: &[&Sample], current: &[i64], epochs: usize, rate: f64, l2: f64) -> Vec<i64> {
+    let terms = current.len();
+    let all = || rows.iter().flat_map(|r| r.candidates.iter());
+    let count = all().count() as f64;
+    let mean: Vec<f64> = (0..terms).map(|j| all().map(|c| c[j]).sum::<f64>() / count).collect();
+    let spread: Vec<f64> = (0..terms)
+        .map(|j| (all().map(|c| (c[j] - mean[j]).powi(2)).sum::<f64>() / count).sqrt())
+        .collect();
+    let varies: Vec<bool> = spread.iter().map(|&s| s > 1e-9).collect();
+    let turns: Vec<Vec<Vec<f64>>> = rows
+        .iter()
+        .map(|r| {
+            r.candidates
+                .iter()
+                .map(|c| (0..terms).map(|j| if varies[j] { c[j] / spread[j] } else { 0.0 }).collect())
+                .collect()
+        })
+        .collect();
+
+    let mut coef = vec![0.0; terms];
+    for _ in 0..epochs {
+        let mut gradient = vec![0.0; terms];
+        for (candidates, row) in turns.iter().zip(rows) {
+            let scores: Vec<f64> = candidates
+                .iter()
+                .map(|x| x.iter().zip(&coef).map(|(x, c)| x * c).sum())
+                .collect();
+            let top = scores.iter().cloned().fold(f64::MIN, f64::max);
+            let odds: Vec<f64> = scores.iter().map(|s| (s - top).exp()).collect();
+            let total: f64 = odds.iter().sum();
+            let picked = odds[0] / total;
+
+            // d(-log p)/dw for a winner's move, d(-log(1 - p))/dw for a loser's
+            let scale = if row.won { 1.0 } else { -picked / (1.0 - picked).max(1e-9) };
+            for (j, g) in gradient.iter_mut().enumerate() {
+                let expected: f64 = candidates.iter().zip(&odds).map(|(x, o)| x[j] * o).sum::<f64>() / total;
+                *g += scale * (expected - candidates[0][j]);
+            }
+        }
+        for (c, g) in coef.iter_mut().zip(&gradient) {
+            *c -= rate * (g / rows.len() as f64 + l2 * *c);
+        }
+    }
+
+    let size: f64 = (0..terms).filter(|&j| varies[j]).map(|j| current[j].abs() as f64 * spread[j]).sum();
+    let fitted: f64 = (0..terms).filter(|&j| varies[j]).map(|j| coef[j].abs()).sum();
+    (0..terms)
+        .map(|j| {
+            if varies[j] && fitted > 0.0 {
+                (size / fitted * coef[j] / spread[j]).round() as i64
+            } else {
+                current[j]
+            }
+        })
+        .collect()
+}
+
+/// Refit `winprob::WEIGHTS` by logistic regression, on standardised terms
+/// like `fit_phase`, and print them next to how well each set predicts the
+/// results.
+fn fit_win_model(replays: &[Replay], epochs: usize, rate: f64, l2: f64) {
+    let rows: Vec<([f64; 4], bool)> = replays
+        .iter()
+        .filter_map(|replay| Some((replay, replay.winner?)))
+        .flat_map(|(replay, winner)| {
+            replay.turns.iter().filter_map(move |turn| {
+                let board = Board::from_anfield_lines(&turn.board, turn.player)?;
+                Some((Features::from_board(&board).terms(), turn.player == winner))
+            })
+        })
+        .collect();
+    if rows.len() < MIN_TURNS {
+        println!("{} turns with a winner, too few to fit", rows.len());
+        return;
+    }
+
+    let n = rows.len() as f64;
+    let mut mean = [0.0; 4];
+    for (terms, _) in &rows {
+        for (m, t) in mean.iter_mut().zip(terms) {
+            *m += t / n;
+        }
+    }
+    let mut spread = [0.0; 4];
+    for (terms, _) in &rows {
+        for ((s, t), m) in spread.iter_mut().zip(terms).zip(&mean) {
+            *s += (t - m).powi(2) / n;
+        }
+    }
+    let spread = spread.map(f64::sqrt);
+    // The first term is the constant 1 and stays that way
+    let standardise = |terms: &[f64; 4]| {
+        let mut scaled = *terms;
+        for ((x, m), s) in scaled.iter_mut().zip(&mean).zip(&spread).skip(1) {
+            *x = if *s > 1e-9 { (*x - m) / s } else { 0.0 };
+        }
+        scaled
+    };
+    let scaled: Vec<([f64; 4], bool)> = rows.iter().map(|(terms, won)| (standardise(terms), *won)).collect();
+
+    let mut coef = [0.0; 4];
+    for _ in 0..epochs {
+        let mut gradient = [0.0; 4];
+        for (x, won) in &scaled {
+            let error = winprob::logistic(&coef, x) - f64::from(u8::from(*won));
+            for (g, x) in gradient.iter_mut().zip(x) {
+                *g += error * x / n;
+            }
+        }
+        // No penalty on the bias
+        for (j, (c, g)) in coef.iter_mut().zip(&gradient).enumerate() {
+            let penalty = if j == 0 { 0.0 } else { l2 * *c };
+            *c -= rate * (g + penalty);
+        }
+    }
+
+    // Back to raw terms: the bias absorbs the means
+    let mut fitted = coef;
+    let mut bias = coef[0];
+    for (((w, c), m), s) in fitted.iter_mut().zip(&coef).zip(&mean).zip(&spread).skip(1) {
+        *w = if *s > 1e-9 { c / s } else { 0.0 };
+        bias -= *w * m;
+    }
+    fitted[0] = bias;
+
+    let log_loss = |weights: &[f64; 4]| -> f64 {
+        let total: f64 = rows
+            .iter()
+            .map(|(terms, won)| {
+                let p = winprob::logistic(weights, terms).clamp(1e-9, 1.0 - 1e-9);
+                -(if *won { p } else { 1.0 - p }).ln()
+            })
+            .sum();
+        total / n
+    };
+    println!(
+        "win probability: {} turns, log loss {:.4} -> {:.4}",
+        rows.len(),
+        log_loss(&winprob::WEIGHTS),
+        log_loss(&fitted)
+    );
+    println!(
+        "  WEIGHTS = [{:.2}, {:.2}, {:.2}, {:.2}]",
+        fitted[0], fitted[1], fitted[2], fitted[3]
+    );
+}
+
+/// Share of the winner's turns on which `weights` rank the move played at
+/// least as high as every other candidate, over the fitted terms.
+fn agreement(rows: &[&Sample], weights: &[i64]) -> f64 {
+    let score = |terms: &[f64]| terms.iter().zip(weights).map(|(t, &w)| t * w as f64).sum::<f64>();
+    let won: Vec<&&Sample> = rows.iter().filter(|r| r.won).collect();
+    if won.is_empty() {
+        return 0.0;
+    }
+    let agreed = won
+        .iter()
+        .filter(|r| {
+            let Some((played, others)) = r.candidates.split_first() else {
+                return false;
+            };
+            let played = score(played);
+            others.iter().all(|c| score(c) <= played)
+        })
+        .count();
+    agreed as f64 / won.len() as f64
+}
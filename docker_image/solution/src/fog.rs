@@ -0,0 +1,68 @@
+// src/fog.rs
+// Playing the fog-of-war variant, where cells out of sight arrive as '?'.
+// Every analysis treats those `Unknown` cells as walls, which is the worst
+// of both worlds: we neither expect to claim them nor fear the opponent
+// coming through them. Instead the heuristic evaluates a board with them
+// filled in according to a stance, and is pulled toward unseen cells so it
+// finds the opponent early. Legality still goes by the board we were shown.
+
+use std::str::FromStr;
+
+use crate::board::{Board, Owner};
+
+/// How far from a placement we expect to see once it's down. The referee's
+/// radius isn't announced, so this is a guess on the small side.
+pub const REVEAL_RADIUS: usize = 2;
+
+/// What to assume is behind the fog.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Stance {
+    /// Unseen cells are empty and open to whoever gets there first.
+    #[default]
+    Optimistic,
+    /// Unseen cells are already theirs.
+    Pessimistic,
+}
+
+impl FromStr for Stance {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "optimistic" => Ok(Stance::Optimistic),
+            "pessimistic" => Ok(Stance::Pessimistic),
+            other => Err(format!("unknown fog stance `{}`", other)),
+        }
+    }
+}
+
+impl Stance {
+    /// `board` with the unseen cells filled in, or `None` if it has none.
+    pub fn assume(self, board: &Board) -> Option<Board> {
+        let unknown = board.coords(Owner::Unknown);
+        if unknown.is_empty() {
+            return None;
+        }
+        let fill = match self {
+            Stance::Optimistic => Owner::Empty,
+            Stance::Pessimistic => Owner::Opponent,
+        };
+        let mut assumed = board.clone();
+        for (y, x) in unknown {
+            assumed.set(y, x, fill);
+        }
+        Some(assumed)
+    }
+}
+
+/// Unseen cells within `REVEAL_RADIUS` steps of any of `cells`.
+pub fn revealed(unknown: &[(usize, usize)], cells: &[(usize, usize)]) -> usize {
+    unknown
+        .iter()
+        .filter(|&&(uy, ux)| {
+            cells
+                .iter()
+                .any(|&(y, x)| uy.abs_diff(y) + ux.abs_diff(x) <= REVEAL_RADIUS)
+        })
+        .count()
+}
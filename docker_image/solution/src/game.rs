@@ -1,53 +1,855 @@
 // src/game.rs
 // Aggressive blocking strategy: Rush to enemy, block them, take the rest
 
-use crate::board::{Board, Owner};
-use crate::piece::Piece;
+
+use crate::analysis;
+use crate::anytime::Anytime;
+use crate::bandit::Bandit;
+use crate::beam::BeamSearch;
+use crate::board::{Board, Census, Holdings, Owner};
+use crate::book::Book;
+use crate::crawler;
+use crate::ensemble::Ensemble;
+use crate::fog::{self, Stance};
+use crate::greedy;
+use crate::grid::{self, Grid};
+use crate::mcts::Mcts;
+use crate::mirror;
+use crate::objective::{self, Objective, Plan};
+use crate::opponent::{Fingerprint, OpponentModel};
+use crate::patterns::{self, PatternLibrary};
+use crate::piece::{Piece, PieceStats, Shape};
+use crate::profiles::Profiles;
+use crate::rng::Rng;
+use crate::rollout;
+use crate::scratch;
+use crate::snapshot;
+use crate::solo;
+use crate::symmetry::{self, Symmetry};
+use crate::tempo::Tempo;
+use crate::timebank::TimeBank;
+use crate::ties::{TieBreak, Ties};
+use crate::weights::{PhaseWeights, Weights};
+use crate::winprob;
+
+pub enum Strategy {
+    /// The hand-tuned rush/block scorer below.
+    Heuristic,
+    /// Monte Carlo Tree Search, see mcts.rs.
+    Mcts(Mcts),
+    /// A few plies of beam search, see beam.rs.
+    Beam(BeamSearch),
+    /// Beam search deepened while the clock allows, see anytime.rs.
+    Anytime(Anytime),
+    /// Several strategies' proposals combined, see ensemble.rs.
+    Ensemble(Ensemble),
+    /// A scripted sparring partner that crawls along the walls, see
+    /// crawler.rs.
+    Crawler,
+    /// A sparring partner that mirrors the opponent's moves, see mirror.rs.
+    Mirror,
+    /// A sparring partner that maximizes the area it reaches first, see
+    /// greedy.rs.
+    GreedyArea,
+}
+
+impl Strategy {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Strategy::Heuristic => "heuristic",
+            Strategy::Mcts(_) => "mcts",
+            Strategy::Beam(_) => "beam",
+            Strategy::Anytime(_) => "anytime",
+            Strategy::Ensemble(_) => "ensemble",
+            Strategy::Crawler => "crawler",
+            Strategy::Mirror => "mirror",
+            Strategy::GreedyArea => "greedy-area",
+        }
+    }
+
+    /// Where the strategy's clock went, for the ones on a clock.
+    pub fn time_bank(&self) -> Option<&TimeBank> {
+        match self {
+            Strategy::Anytime(anytime) => Some(anytime.bank()),
+            _ => None,
+        }
+    }
+}
 
 pub struct Game {
     pub my_player: u8,
+    pub strategy: Strategy,
+    /// Set once the game is provably lost; from then on we only clean up.
+    pub resigned: bool,
+    /// Set while the opponent has stopped playing and we're ahead: the game
+    /// is decided, so we fill what's left alone (see `opponent_gone`).
+    pub finishing: bool,
+    pub sampling: SamplingConfig,
+    /// Per-step falloff of the influence map, in (0, 1).
+    pub influence_decay: f64,
+    pub weights: Weights,
+    /// Weights per kind of map; the first board picks one, if any fits
+    /// (see profiles.rs).
+    pub profiles: Profiles,
+    pub exploration: Exploration,
+    pub patterns: PatternLibrary,
+    pub book: Book,
+    /// What the heuristic chose this turn and why; `None` for other
+    /// strategies and book moves.
+    pub last_decision: Option<Decision>,
+    /// Break down every candidate, not just the chosen one, into
+    /// `Decision::ranking`. Costs about as much as scoring them again.
+    pub explain: bool,
+    /// Sizes of the pieces we've been dealt.
+    pub pieces: PieceStats,
+    /// The last `ROLLOUT_POOL` pieces we've been dealt, oldest first, for
+    /// playing the game out (see rollout.rs).
+    dealt: Vec<Piece>,
+    opponent: OpponentModel,
+    /// Cells held by each side on the last board, and that board.
+    census: Census,
+    seen: Option<Board>,
+    /// Each side's cells and our frontier on the board the heuristic last
+    /// scored (under fog, with the hidden cells assumed).
+    holdings: Holdings,
+    /// Cells gained per turn by each side, from the census.
+    tempo: Tempo,
+    /// Which aggression settings have paid off in contact this game.
+    bandit: Bandit,
+    /// The objective being pursued, and how earlier ones ended.
+    plan: Plan,
+    /// Moves asked for so far, this one included.
+    turn: usize,
+    /// Equidistant cells between the starting positions, found on the first
+    /// turn the opponent is visible.
+    midline: Vec<(usize, usize)>,
+    /// What the heuristic assumes about cells hidden by fog of war.
+    pub fog: Stance,
+    /// How placements with equal scores are settled.
+    pub ties: Ties,
+    rng: Rng,
+    /// The last turn's input, the board's Zobrist hash mixed with the
+    /// piece's, and what we answered, replayed if the referee sends the
+    /// same turn again.
+    replay: Option<(u64, Option<(usize, usize)>)>,
+}
+
+/// One weighted term of a placement's score, by weight name.
+pub type Term = (&'static str, i64);
+
+/// Why the heuristic chose its last move: the score broken down.
+#[derive(Clone)]
+pub struct Decision {
+    pub phase: Phase,
+    /// Legal placements scored.
+    pub candidates: usize,
+    /// Cells we and they can expect to claim over the next
+    /// `OUTLOOK_TURNS` turns, see `Game::territory_outlook`.
+    pub outlook: (f64, f64),
+    pub standing: Standing,
+    pub terms: Vec<Term>,
+    /// The objective the move was chosen to serve, as the strategic layer
+    /// put it, and how well it served it, 0 to 100.
+    pub objective: (String, i64),
+    /// Every candidate broken down the same way, best first, when
+    /// `Game::explain` is on (see control.rs).
+    pub ranking: Vec<Candidate>,
+}
+
+/// A candidate placement's score broken down, for explaining why it lost.
+#[derive(Clone)]
+pub struct Candidate {
+    pub placement: (usize, usize),
+    pub terms: Vec<Term>,
+    /// Dropped before the shortlist (see `SAFE_SHORTLIST`), so scored
+    /// without the safe area, denial or fragmentation.
+    pub passed_over: bool,
+}
+
+/// Self-play data generation only: now and then play a move other than the
+/// best, so generated games cover more than the one deterministic line.
+#[derive(Clone, Copy, Default)]
+pub struct Exploration {
+    /// Chance per turn of sampling instead of taking the best move. 0 is off.
+    pub epsilon: f64,
+    /// Softmax temperature, in score points: higher picks weaker moves more.
+    pub temperature: f64,
+}
+
+/// On boards too big to scan every offset in time, score a random sample of
+/// the offsets that put a piece cell on one of our anchor cells instead.
+#[derive(Clone, Copy)]
+pub struct SamplingConfig {
+    /// Sample once the board has more cells than this.
+    pub above_cells: usize,
+    /// Candidate offsets scored per turn when sampling.
+    pub samples: usize,
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        SamplingConfig {
+            // Bigger than any of the shipped maps
+            above_cells: 20_000,
+            samples: 4_000,
+        }
+    }
+}
+
+pub const DEFAULT_INFLUENCE_DECAY: f64 = 0.8;
+
+/// How far ahead `Game::territory_outlook` looks, in turns per player.
+pub const OUTLOOK_TURNS: usize = 5;
+
+/// Turns without a single new opponent cell before we look at whether
+/// they've stopped playing.
+const IDLE_TURNS: usize = 2;
+
+/// Turns without a new opponent cell after which they've stopped, moves
+/// left or not: crashed, timed out or forfeited.
+const FORFEIT_TURNS: usize = 4;
+
+/// Empty areas bigger than this aren't treated as pockets.
+const MAX_POCKET: usize = 12;
+
+/// A region we reach this many turns ahead of the opponent is as good as
+/// ours: not worth fighting over yet (see `analysis::region_race`).
+pub const RACE_MARGIN: i64 = 2;
+
+/// Turns spent racing for the midline instead of the nearest enemy cell.
+pub const MIDLINE_TURNS: usize = 6;
+
+/// Cells up to this many steps off the planned corridor still count as on
+/// it, less so the further off they are.
+const CORRIDOR_REACH: usize = 2;
+
+/// A line's cells up to this many steps from the opponent count as on the
+/// front (see `shape_fit`).
+const LINE_FRONT: usize = 2;
+
+/// Against a rush, the wall goes this many rows deep in front of our cells.
+const WALL_DEPTH: f64 = 2.0;
+
+/// The aggression dial (see `Weights::with_aggression`) once we've lost
+/// tempo for `tempo::LOSING_TURNS` turns, or trail on the outlook in
+/// contact: more cutting, less expanding.
+const BEHIND_AGGRESSION: f32 = 0.8;
+
+/// The aggression dial against each kind of player (see
+/// `OpponentModel::fingerprint`): a turtler leaves the open board to
+/// whoever races for it, an edge-expander has to be cut off from the
+/// edges before its channels get past. A rusher is walled off instead.
+const TURTLER_AGGRESSION: f32 = 0.3;
+const EDGE_EXPANDER_AGGRESSION: f32 = 0.7;
+
+/// Below this estimated win probability we check whether the loss is certain.
+const RESIGN_PROBABILITY: f64 = 0.05;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Phase {
+    /// No contact yet: race toward the opponent.
+    Opening,
+    /// In contact: surround and contain.
+    Midgame,
+    /// Board mostly full, or the opponent sealed off.
+    Endgame,
+}
+
+/// Where the score stands on a turn (see `Game::standing`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Standing {
+    /// Ahead by more than they can still catch up on, whatever they reach.
+    Won,
+    /// Level or ahead on the cells held plus the outlook.
+    Ahead,
+    /// Behind on them.
+    Behind,
+}
+
+impl Standing {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Standing::Won => "won",
+            Standing::Ahead => "ahead",
+            Standing::Behind => "behind",
+        }
+    }
+}
+
+/// Past this share of filled cells the game is in its endgame.
+const ENDGAME_FILL: f64 = 0.7;
+
+impl Phase {
+    /// The phase `board` is in from our side, worked out from scratch.
+    /// Used by the referee to label turns for any strategy.
+    pub fn of(board: &Board) -> Phase {
+        let mine = board.coords(Owner::Me);
+        let theirs = board.coords(Owner::Opponent);
+        if theirs.is_empty() {
+            return Phase::classify(board, usize::MAX, board.count(Owner::Empty) > 0);
+        }
+
+        let min_distance = mine
+            .iter()
+            .flat_map(|&(my, mx)| theirs.iter().map(move |&(ey, ex)| my.abs_diff(ey) + mx.abs_diff(ex)))
+            .min()
+            .unwrap_or(usize::MAX);
+        let dead = board.dead_zones();
+        let contested = board
+            .iter()
+            .any(|((y, x), owner)| owner == Owner::Empty && dead.get(y, x) == Some(false));
+
+        Phase::classify(board, min_distance, contested)
+    }
+
+    pub const ALL: [Phase; 3] = [Phase::Opening, Phase::Midgame, Phase::Endgame];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Opening => "opening",
+            Phase::Midgame => "contact",
+            Phase::Endgame => "endgame",
+        }
+    }
+
+    /// Opening until we're within contact distance of the enemy, which scales
+    /// with the board so big maps switch to containment early enough.
+    fn classify(board: &Board, min_distance: usize, contested: bool) -> Phase {
+        let size = board.rows() * board.cols();
+        let filled = size - board.count(Owner::Empty);
+        let contact_distance = ((board.rows() + board.cols()) / 14).max(5);
+
+        if !contested || filled as f64 >= ENDGAME_FILL * size as f64 {
+            Phase::Endgame
+        } else if min_distance > contact_distance {
+            Phase::Opening
+        } else {
+            Phase::Midgame
+        }
+    }
+
+}
+
+/// Inverse distance to the enemy, on a scale of 0 to 1,000,000.
+fn closeness(distance: usize) -> i64 {
+    if distance == usize::MAX {
+        0
+    } else {
+        1_000_000 / (distance as i64 + 1)
+    }
+}
+
+/// Chokepoints that only seal off fewer cells than this are ignored.
+const MIN_CHOKEPOINT_CUT: usize = 4;
+
+/// How many of the best candidates get the safe area, denial,
+/// fragmentation and symmetry break weighed in: they cost a copy of the
+/// board and two distance fields each, and a min cut for the safe area
+/// (see `analysis::safe_areas`).
+const SAFE_SHORTLIST: usize = 6;
+
+/// Placements scoring within this percentage of the best are too close to
+/// call on their terms, and the best few of them are played out instead
+/// (see rollout.rs). Scores run from hundreds to millions, so a fixed
+/// number of points is either nothing or everything.
+const ROLLOUT_MARGIN: i64 = 3;
+
+/// Placements played out per turn at most, the best included.
+const ROLLOUT_CANDIDATES: usize = 4;
+
+/// Pieces kept for playing the game out.
+const ROLLOUT_POOL: usize = 16;
+
+/// Per-turn facts shared by every candidate placement.
+#[derive(Clone, Copy)]
+struct TurnContext<'a> {
+    /// Steps the opponent needs to reach each cell (see
+    /// `Board::distance_field`), computed once per turn.
+    enemy_distance: &'a Grid<usize>,
+    frontier: &'a [(usize, usize)],
+    target_direction: (isize, isize),
+    /// The same for the nearest of our cells.
+    current_min_distance: usize,
+    closest_enemy: (usize, usize),
+    /// The still-empty midline cell nearest us, while racing for it.
+    midline_target: Option<(usize, usize)>,
+    /// Empty cells the opponent can never reach (see `Board::dead_zones`).
+    dead_zones: &'a Grid<bool>,
+    /// Whether any empty cell is still reachable by the opponent.
+    contested: bool,
+    /// Cells the opponent loses access to if we take a given cell.
+    chokepoints: &'a Grid<usize>,
+    /// Distance behind the front line (see `analysis::frontier_depth`).
+    depth: &'a Grid<usize>,
+    /// Turns we reach each region ahead of them (see `analysis::region_race`).
+    race: &'a Grid<Option<i64>>,
+    influence: &'a Grid<f64>,
+    /// The board maps onto itself with the sides swapped: dead even. Only
+    /// the shortlist weighs breaking it.
+    balanced: bool,
+    /// Cells hidden by fog of war, if any.
+    unknown: &'a [(usize, usize)],
+    /// While they rush us: the empty cells across their approach (see
+    /// `rush_wall`).
+    wall: Option<&'a Grid<bool>>,
+    /// With the opponent pushing on more than one front: each front's
+    /// share of the cells they'd take, and its distance field.
+    fronts: &'a [(f64, Grid<usize>)],
+    /// While rushing: steps off the planned corridor to them (see
+    /// `analysis::corridor`).
+    corridor: Option<&'a Grid<usize>>,
+    /// Steps from the opponent's latest placement (see `Board::latest`),
+    /// when the board marks one.
+    latest: Option<&'a Grid<usize>>,
+    /// What this turn's move is for (see objective.rs).
+    objective: &'a Objective,
+    /// The piece's shape (see `Piece::shape`).
+    shape: Shape,
+    /// Our safe area less theirs before placing (see `analysis::safe_areas`),
+    /// when this phase weighs it.
+    safe_margin: Option<i64>,
+    /// Empty cells the opponent reaches strictly before us, before placing,
+    /// when this phase weighs denial.
+    theirs_first: Option<&'a Grid<bool>>,
+    /// The same cells when this phase weighs gifts, for every pass.
+    their_side: Option<&'a Grid<bool>>,
+    /// Our territory outside its largest part before placing (see
+    /// `analysis::detached_territory`), when this phase weighs fragmentation.
+    detached: Option<i64>,
+    /// Empty cells one more average piece could cover from our cells before
+    /// placing (see `analysis::placeable_next`), when this phase weighs
+    /// follow-up.
+    placeable: Option<&'a Grid<bool>>,
+    phase: Phase,
+    /// This turn's weights for `phase` (see `Game::turn_weights`).
+    weights: PhaseWeights,
 }
 
 impl Game {
-    pub fn new(my_player: u8) -> Self {
-        Game { my_player }
+    pub fn new(my_player: u8, strategy: Strategy) -> Self {
+        Game {
+            my_player,
+            strategy,
+            resigned: false,
+            finishing: false,
+            sampling: SamplingConfig::default(),
+            influence_decay: DEFAULT_INFLUENCE_DECAY,
+            weights: Weights::default(),
+            profiles: Profiles::default(),
+            exploration: Exploration::default(),
+            patterns: PatternLibrary::builtin(),
+            book: Book::default(),
+            last_decision: None,
+            explain: false,
+            pieces: PieceStats::default(),
+            dealt: Vec::new(),
+            opponent: OpponentModel::default(),
+            census: Census::default(),
+            seen: None,
+            holdings: Holdings::default(),
+            tempo: Tempo::default(),
+            bandit: Bandit::default(),
+            plan: Plan::default(),
+            turn: 0,
+            midline: Vec::new(),
+            fog: Stance::default(),
+            ties: Ties::new(TieBreak::default()),
+            // Fixed seed so runs stay reproducible
+            rng: Rng::new(0x5EED + my_player as u64),
+            replay: None,
+        }
     }
 
-    pub fn choose_best_move(&self, board: &Board, piece: &Piece) -> Option<(usize, usize)> {
-        if piece.cells.is_empty() || board.rows == 0 || board.cols == 0 {
-            return None;
+    pub fn with_sampling(mut self, sampling: SamplingConfig) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
+    pub fn with_influence_decay(mut self, decay: f64) -> Self {
+        self.influence_decay = decay;
+        self
+    }
+
+    pub fn with_weights(mut self, weights: Weights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    pub fn with_profiles(mut self, profiles: Profiles) -> Self {
+        self.profiles = profiles;
+        self
+    }
+
+    pub fn with_patterns(mut self, patterns: PatternLibrary) -> Self {
+        self.patterns = patterns;
+        self
+    }
+
+    pub fn with_book(mut self, book: Book) -> Self {
+        self.book = book;
+        self
+    }
+
+    pub fn with_exploration(mut self, exploration: Exploration) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    pub fn with_fog(mut self, fog: Stance) -> Self {
+        self.fog = fog;
+        self
+    }
+
+    pub fn with_ties(mut self, rule: TieBreak) -> Self {
+        self.ties = Ties::new(rule);
+        self
+    }
+
+    /// Some referees resend the same board and piece when the opponent
+    /// forfeits a turn. Nothing has changed, so neither does the answer:
+    /// it's replayed without touching any per-turn state.
+    pub fn choose_best_move(&mut self, board: &Board, piece: &Piece) -> Option<(usize, usize)> {
+        let input = snapshot::mix(&[board.zobrist(), snapshot::piece_hash(piece)]);
+        if let Some((hash, chosen)) = self.replay {
+            if hash == input {
+                debug!("[DEBUG] Same input as last turn, replaying {:?}", chosen);
+                return chosen;
+            }
         }
 
-        // Precompute coordinates
-        let mut enemy_coords: Vec<(usize, usize)> = Vec::new();
-        let mut my_coords: Vec<(usize, usize)> = Vec::new();
-        
-        for y in 0..board.rows {
-            for x in 0..board.cols {
-                match board.cells[y][x] {
-                    Owner::Opponent => enemy_coords.push((y, x)),
-                    Owner::Me => my_coords.push((y, x)),
-                    _ => {}
+        let chosen = self.decide(board, piece);
+        self.replay = Some((input, chosen));
+        chosen
+    }
+
+    fn decide(&mut self, board: &Board, piece: &Piece) -> Option<(usize, usize)> {
+        self.turn += 1;
+        self.last_decision = None;
+        if self.turn == 1 {
+            if let Some(profile) = self.profiles.select(board) {
+                debug!("[DEBUG] Playing the {} map profile", profile.name);
+                self.weights = profile.weights;
+            }
+        }
+        self.pieces.record(piece);
+        if self.dealt.len() == ROLLOUT_POOL {
+            self.dealt.remove(0);
+        }
+        self.dealt.push(piece.clone());
+        self.opponent.observe(board);
+        self.take_census(board);
+        // The sparring partners stick to their scripts
+        let scripted = matches!(self.strategy, Strategy::Crawler | Strategy::Mirror | Strategy::GreedyArea);
+        if let Some(placement) = self.book.lookup(self.turn, board, piece).filter(|_| !scripted) {
+            debug!("[DEBUG] Book move for turn {}", self.turn);
+            return Some(placement);
+        }
+
+        if !self.resigned && self.loss_is_certain(board) {
+            debug!("[DEBUG] Game is lost territorially, switching to cleanup");
+            self.resigned = true;
+        }
+        let gone = self.opponent_gone();
+        if gone != self.finishing {
+            debug!("[DEBUG] Opponent {}", if gone { "has stopped playing, filling alone" } else { "is back" });
+            self.finishing = gone;
+        }
+        if self.resigned {
+            return self.cleanup_move(board, piece);
+        }
+        if self.finishing {
+            return solo::choose_move(board, piece, &self.pieces);
+        }
+
+        match &mut self.strategy {
+            Strategy::Heuristic => self.heuristic_move(board, piece),
+            Strategy::Mcts(mcts) => mcts.choose_move(board, piece),
+            Strategy::Beam(beam) => beam.choose_move(board, piece),
+            Strategy::Anytime(anytime) => anytime.choose_move(board, piece),
+            Strategy::Ensemble(ensemble) => ensemble.choose_move(board, piece),
+            Strategy::Crawler => crawler::choose_move(board, piece),
+            Strategy::Mirror => mirror::choose_move(board, piece),
+            Strategy::GreedyArea => greedy::choose_move(board, piece),
+        }
+    }
+
+    pub fn opponent(&self) -> &OpponentModel {
+        &self.opponent
+    }
+
+    /// Cells held on the last board we were shown.
+    pub fn census(&self) -> Census {
+        self.census
+    }
+
+    /// The opponent's fronts (see `analysis::fronts`) with their distance
+    /// fields, each weighted by its share of the empty cells they'd reach
+    /// first from it. Empty unless there are at least two.
+    fn front_stakes(&self, board: &Board) -> Vec<(f64, Grid<usize>)> {
+        let fronts = analysis::fronts(board);
+        if fronts.len() < 2 {
+            return Vec::new();
+        }
+
+        let fields: Vec<Grid<usize>> = fronts.into_iter().map(|front| board.distance_from(front)).collect();
+        let mine = board.distance_field(Owner::Me);
+        let mut stakes = vec![0usize; fields.len()];
+        for ((y, x), owner) in board.iter() {
+            if owner != Owner::Empty {
+                continue;
+            }
+            let nearest = fields
+                .iter()
+                .enumerate()
+                .filter_map(|(i, field)| field.get(y, x).map(|d| (d, i)))
+                .min();
+            if let Some((d, i)) = nearest {
+                if let Some(stake) = stakes.get_mut(i).filter(|_| d < mine.get(y, x).unwrap_or(usize::MAX)) {
+                    *stake += 1;
+                }
+            }
+        }
+
+        debug!("[DEBUG] {} enemy fronts, at stake {:?}", stakes.len(), stakes);
+        let total = stakes.iter().sum::<usize>().max(1) as f64;
+        stakes.into_iter().map(|s| s as f64 / total).zip(fields).collect()
+    }
+
+    /// Bring the counts up to date from what changed since the last board.
+    fn take_census(&mut self, board: &Board) {
+        match self.seen.replace(board.clone()) {
+            Some(previous) => {
+                let before = self.census;
+                self.census.update(&previous, board);
+                self.tempo.record(before, self.census);
+                if let Some(gain) = self.tempo.last() {
+                    self.bandit.reward(gain);
                 }
             }
+            None => self.census = Census::of(board),
         }
+        debug_assert_eq!(self.census, Census::of(board), "cell counts drifted from the board");
+    }
+
+    pub fn tempo(&self) -> &Tempo {
+        &self.tempo
+    }
+
+    pub fn plan(&self) -> &Plan {
+        &self.plan
+    }
+
+    /// Moves asked for so far.
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    /// The weights to play this turn with: the configured ones, turned
+    /// toward cutting them off while we keep losing tempo or, once in
+    /// contact, trail on the score. Otherwise, in contact, the bandit picks
+    /// how far to turn them (see bandit.rs), from the counter to the kind of
+    /// player they are, if they fit one.
+    fn turn_weights(&mut self, standing: Standing, phase: Phase) -> Weights {
+        let counter = match self.opponent.fingerprint() {
+            Some(Fingerprint::Turtler) => self.weights.with_aggression(TURTLER_AGGRESSION),
+            Some(Fingerprint::EdgeExpander) => self.weights.with_aggression(EDGE_EXPANDER_AGGRESSION),
+            Some(Fingerprint::Rusher) | None => self.weights,
+        };
+        if self.tempo.losing() {
+            debug!(
+                "[DEBUG] Lost tempo {} turns running (trend {:+}), cutting harder",
+                self.tempo.losing_streak(),
+                self.tempo.trend()
+            );
+            self.weights.with_aggression(BEHIND_AGGRESSION)
+        } else if standing == Standing::Behind && phase != Phase::Opening {
+            debug!("[DEBUG] Behind on the outlook, cutting harder");
+            self.weights.with_aggression(BEHIND_AGGRESSION)
+        } else if phase == Phase::Opening {
+            counter
+        } else {
+            let aggression = self.bandit.pull();
+            debug!("[DEBUG] Bandit plays aggression {} of {:?}", aggression, self.bandit.arms());
+            counter.with_aggression(aggression)
+        }
+    }
+
+    /// Cells each side can expect to claim over the next `OUTLOOK_TURNS`
+    /// turns: pieces drawn from the sizes seen so far, each side limited to
+    /// the empty cells it reaches first.
+    pub fn territory_outlook(&self, board: &Board) -> (f64, f64) {
+        let (mine, theirs) = analysis::voronoi_areas(board);
+        let my_area = mine.saturating_sub(self.census.me);
+        let their_area = theirs.saturating_sub(self.census.opponent);
+        (
+            self.pieces.expected_claim(my_area, OUTLOOK_TURNS),
+            self.pieces.expected_claim(their_area, OUTLOOK_TURNS),
+        )
+    }
+
+    /// Won once the cells we hold or alone can reach are more than they
+    /// could end up with, reaching everything they can (never under fog,
+    /// where some of it may be hidden). Otherwise ahead or behind on the
+    /// cells held plus what each side can expect to claim next.
+    fn standing(&self, board: &Board, outlook: (f64, f64)) -> Standing {
+        if board.count(Owner::Unknown) == 0 {
+            let ((my_min, _), (_, their_max)) = analysis::score_bounds(board);
+            if my_min > their_max {
+                return Standing::Won;
+            }
+        }
+        if self.census.me as f64 + outlook.0 >= self.census.opponent as f64 + outlook.1 {
+            Standing::Ahead
+        } else {
+            Standing::Behind
+        }
+    }
+
+    /// Lost means the estimate is grim and the board is partitioned so that
+    /// even filling every cell we can reach doesn't beat what they hold now.
+    fn loss_is_certain(&self, board: &Board) -> bool {
+        if winprob::Features::from_board(board).win_probability() >= RESIGN_PROBABILITY {
+            return false;
+        }
+        let their_cells = self.census.opponent;
+        match analysis::partition_bounds(board) {
+            Some((my_max, _)) => my_max < their_cells,
+            None => false,
+        }
+    }
+
+    /// The opponent has stopped playing and we're already ahead, so the
+    /// result can't change. Stopped means no new cell for `IDLE_TURNS` and
+    /// no live anchor left to place from, or none for `FORFEIT_TURNS`.
+    fn opponent_gone(&self) -> bool {
+        let idle = self.tempo.opponent_idle();
+        let Some(board) = &self.seen else {
+            return false;
+        };
+        // Under fog their new cells may just be out of sight
+        idle >= IDLE_TURNS
+            && self.census.me > self.census.opponent
+            && board.count(Owner::Unknown) == 0
+            && (idle >= FORFEIT_TURNS || analysis::live_anchors(board, Owner::Opponent) == 0)
+    }
+
+    /// Once the result is settled, every move still scores: take the one
+    /// that claims the most empty cells and leaves us the most empty cells
+    /// to reach afterwards, so there's room for the pieces still to come.
+    /// The first found wins a tie.
+    fn cleanup_move(&self, board: &Board, piece: &Piece) -> Option<(usize, usize)> {
+        let mut best: Option<((usize, usize), (usize, usize))> = None;
+        for (top_y, left_x) in board.anchored_offsets(piece, Owner::Me) {
+            if !board.can_place(piece, top_y, left_x, Owner::Me) {
+                continue;
+            }
+            let claimed = piece
+                .cells
+                .iter()
+                .filter(|&&(dy, dx)| board.is(top_y + dy, left_x + dx, Owner::Empty))
+                .count();
+            let mut after = board.clone();
+            after.place(piece, top_y, left_x, Owner::Me);
+            let reach = after.distance_field(Owner::Me);
+            let room = after
+                .iter()
+                .filter(|&((y, x), owner)| owner == Owner::Empty && reach.get(y, x).is_some_and(|d| d != usize::MAX))
+                .count();
+            if best.is_none_or(|(top, _)| (claimed, room) > top) {
+                best = Some(((claimed, room), (top_y, left_x)));
+            }
+        }
+        best.map(|(_, at)| at)
+    }
+
+    fn heuristic_move(&mut self, shown: &Board, piece: &Piece) -> Option<(usize, usize)> {
+        if piece.cells.is_empty() || shown.rows() == 0 || shown.cols() == 0 {
+            return None;
+        }
+
+        // Under fog, evaluate with the hidden cells filled in by our stance;
+        // what's legal still goes by the board we were shown
+        let unknown = shown.coords(Owner::Unknown);
+        let assumed = self.fog.assume(shown);
+        let board = assumed.as_ref().unwrap_or(shown);
+
+        // Coordinates, brought up to date from what changed since last turn
+        self.holdings.update(board);
+        let enemy_coords = self.holdings.theirs.clone();
+        let my_coords = self.holdings.mine.clone();
 
         if my_coords.is_empty() {
             return None;
         }
 
-        // Find the closest enemy cell to any of my cells
+        // A one-cell piece can only go back onto a cell of ours and claims
+        // nothing: every legal placement is the same, so take the first
+        if let [(dy, dx)] = *piece.cells.as_slice() {
+            return my_coords
+                .iter()
+                .filter(|&&(y, x)| y >= dy && x >= dx)
+                .map(|&(y, x)| (y - dy, x - dx))
+                .find(|&(top_y, left_x)| self.is_valid_placement(shown, piece, top_y, left_x));
+        }
+
+        // Nobody in sight and nothing hidden: there's only the board to fill
+        if enemy_coords.is_empty() && unknown.is_empty() {
+            return solo::choose_move(shown, piece, &self.pieces);
+        }
+
+        // Find the closest enemy cell to any of my cells, then lead it by
+        // where their play suggests they're heading
         let (closest_my, closest_enemy, min_distance) = self.find_closest_pair(&my_coords, &enemy_coords);
-        
-        // Calculate the direction vector from my closest cell to enemy's closest cell
-        let target_direction = if !enemy_coords.is_empty() {
+        let closest_enemy = self.opponent.lead(board, closest_enemy);
+
+        if self.midline.is_empty() && !enemy_coords.is_empty() {
+            self.midline = analysis::midline(board);
+        }
+
+        // A rush at our start is walled off rather than met head-on, and so
+        // is a rusher's approach for as long as there's room for a wall
+        if let Some(fingerprint) = self.opponent.fingerprint() {
+            debug!("[DEBUG] Opponent plays like a {}", fingerprint.name());
+        }
+        let rusher = self.opponent.fingerprint() == Some(Fingerprint::Rusher);
+        let wall = match (self.opponent.rushing(), self.opponent.home()) {
+            (Some(from), Some(home)) => {
+                debug!("[DEBUG] Rush from ({:+.2}, {:+.2}), walling off", from.0, from.1);
+                Some(rush_wall(board, home, from))
+            }
+            (None, Some(home)) if rusher => self
+                .opponent
+                .approach()
+                .map(|from| rush_wall(board, home, from))
+                .filter(|wall| wall.iter().any(|(_, open)| open)),
+            _ => None,
+        };
+
+        let midline_target = if self.turn <= MIDLINE_TURNS && wall.is_none() {
+            self.midline
+                .iter()
+                .copied()
+                .filter(|&(y, x)| board.is(y, x, Owner::Empty))
+                .min_by_key(|&(y, x)| closest_my.0.abs_diff(y) + closest_my.1.abs_diff(x))
+        } else {
+            None
+        };
+
+        // Calculate the direction vector from my closest cell to the target:
+        // the midline early on, then the enemy's closest cell
+        let target_direction = if let Some(target) = midline_target {
+            (
+                target.0 as isize - closest_my.0 as isize,
+                target.1 as isize - closest_my.1 as isize,
+            )
+        } else if !enemy_coords.is_empty() {
             (
                 closest_enemy.0 as isize - closest_my.0 as isize,
                 closest_enemy.1 as isize - closest_my.1 as isize,
             )
         } else {
-            // No enemy visible, head toward center
-            let center = (board.rows / 2, board.cols / 2);
+            // No enemy visible through the fog, head toward center
+            let center = (board.rows() / 2, board.cols() / 2);
             let my_center = self.calculate_centroid(&my_coords);
             (
                 center.0 as isize - my_center.0 as isize,
@@ -55,50 +857,358 @@ impl Game {
             )
         };
 
-        // Find the frontier cells (my cells that can have pieces placed adjacent to them)
-        let frontier = self.find_frontier(&my_coords, board);
+        // The frontier cells (my cells that can have pieces placed adjacent to them)
+        let frontier = self.holdings.frontier.clone();
+
+        // Without a visible enemy every empty cell would count as dead
+        let (dead_zones, chokepoints) = if enemy_coords.is_empty() {
+            (
+                Grid::new(board.rows(), board.cols(), false),
+                Grid::new(board.rows(), board.cols(), 0),
+            )
+        } else {
+            (board.dead_zones(), analysis::chokepoints(board))
+        };
+        let contested = board
+            .iter()
+            .any(|((y, x), owner)| owner == Owner::Empty && dead_zones.get(y, x) == Some(false));
+        // Once the lead can't be caught, contact only risks cells: fill
+        // safely instead, as on a sealed-off board
+        let outlook = self.territory_outlook(board);
+        let standing = self.standing(board, outlook);
+        debug!("[DEBUG] Standing: {}", standing.name());
+        let contested = contested && standing != Standing::Won;
+        let depth = analysis::frontier_depth(board, &dead_zones);
+        let race = analysis::region_race(board, self.pieces.typical_reach());
+        let influence = analysis::influence_map(board, self.influence_decay);
 
-        let mut best_pos: Option<(usize, usize)> = None;
-        let mut best_score: i64 = i64::MIN;
+        // How far the opponent has to go to each cell. Our own cells are a
+        // step past their nearest neighbour, as if they could step onto them
+        let reach = board.distance_field(Owner::Opponent);
+        let mut enemy_distance = reach.clone();
+        for &(y, x) in &my_coords {
+            let nearest = board.neighbors(y, x).filter_map(|(ny, nx)| reach.get(ny, nx)).min();
+            enemy_distance.set(y, x, nearest.map_or(usize::MAX, |d| d.saturating_add(1)));
+        }
+        let fronts = self.front_stakes(board);
+        let just_played = board.latest(Owner::Opponent);
+        let latest = (!just_played.is_empty()).then(|| board.distance_from(just_played));
+        let phase = Phase::classify(board, min_distance, contested);
 
-        // Search entire board for valid placements
-        let max_y = board.rows.saturating_sub(piece.height).saturating_add(1);
-        let max_x = board.cols.saturating_sub(piece.width).saturating_add(1);
-        
-        for top_y in 0..max_y {
-            for left_x in 0..max_x {
-                if !self.is_valid_placement(board, piece, top_y, left_x) {
-                    continue;
+        // While rushing, follow the actual shortest way to them
+        let corridor = if phase == Phase::Opening && wall.is_none() && !enemy_coords.is_empty() {
+            let path = analysis::corridor(board, closest_enemy);
+            (!path.is_empty()).then(|| board.distance_from(path))
+        } else {
+            None
+        };
+        // The strategic layer: what this move is for, kept from earlier
+        // turns while it lasts
+        let objective = self.plan.next(&objective::Facts {
+            board,
+            contested,
+            contact: phase != Phase::Opening,
+            rushed: wall.is_some(),
+            rushing: corridor.is_some(),
+            chokepoints: &chokepoints,
+            enemy_distance: &enemy_distance,
+            reach: self.pieces.typical_reach(),
+        }, self.turn);
+        let since = self.plan.current().map_or(self.turn, |pursuit| pursuit.since);
+        debug!("[DEBUG] Objective: {} (since turn {})", objective.describe(), since);
+        let front_distance = my_coords
+            .iter()
+            .filter_map(|&(y, x)| enemy_distance.get(y, x))
+            .min()
+            .unwrap_or(usize::MAX);
+        let weights = *self.turn_weights(standing, phase).phase(phase);
+        let safe_margin = (contested && weights.safe_area != 0).then(|| {
+            let (mine, theirs) = analysis::safe_areas(board);
+            mine as i64 - theirs as i64
+        });
+        let their_side = (contested && (weights.denial != 0 || weights.gift != 0)).then(|| {
+            let mine = board.distance_field(Owner::Me);
+            let mut their_side = Grid::new(board.rows(), board.cols(), false);
+            for ((y, x), d) in reach.iter() {
+                if board.is(y, x, Owner::Empty) && d < mine.get(y, x).unwrap_or(usize::MAX) {
+                    their_side.set(y, x, true);
                 }
+            }
+            their_side
+        });
+        let detached = (contested && weights.fragment != 0)
+            .then(|| analysis::detached_territory(board, &board.distance_field(Owner::Me), &reach) as i64);
+        let placeable = (contested && weights.follow_up != 0)
+            .then(|| analysis::placeable_next(board, self.pieces.typical_reach()));
+        let shape = piece.shape();
+        debug!("[DEBUG] Piece: {}", shape.name());
 
-                let score = self.score_placement(
-                    board, piece, top_y, left_x,
-                    &enemy_coords, &frontier,
-                    target_direction, min_distance, closest_enemy
-                );
+        let ctx = TurnContext {
+            enemy_distance: &enemy_distance,
+            frontier: &frontier,
+            target_direction,
+            current_min_distance: front_distance,
+            closest_enemy,
+            midline_target,
+            dead_zones: &dead_zones,
+            contested,
+            chokepoints: &chokepoints,
+            depth: &depth,
+            race: &race,
+            influence: &influence,
+            balanced: symmetry::find(board, true).is_some(),
+            unknown: &unknown,
+            wall: wall.as_ref(),
+            fronts: &fronts,
+            corridor: corridor.as_ref(),
+            latest: latest.as_ref(),
+            objective: &objective,
+            shape,
+            safe_margin,
+            theirs_first: their_side.as_ref().filter(|_| weights.denial != 0),
+            their_side: their_side.as_ref().filter(|_| weights.gift != 0),
+            detached,
+            placeable: placeable.as_ref(),
+            phase,
+            weights,
+        };
 
-                if score > best_score {
-                    best_score = score;
-                    best_pos = Some((top_y, left_x));
-                }
+        let exploring = self.exploration.epsilon > 0.0 && self.rng.unit() < self.exploration.epsilon;
+
+        // Search every offset that puts a piece cell on one of our cells,
+        // or a sample of them if the board is too big
+        let offsets = board.anchored_offsets(piece, Owner::Me);
+        let offsets = if board.rows() * board.cols() > self.sampling.above_cells {
+            self.sample_candidates(piece, offsets, closest_enemy, !enemy_coords.is_empty())
+        } else {
+            offsets
+        };
+
+        // If nothing fits in plain sight, gamble on placements reaching into
+        // the fog, as far as the stance says it's empty
+        let fits = |legal: &Board| {
+            offsets
+                .iter()
+                .any(|&(top_y, left_x)| self.is_valid_placement(legal, piece, top_y, left_x))
+        };
+        let legal = if fits(shown) { shown } else { board };
+
+        let shortlist = ctx.safe_margin.is_some()
+            || ctx.theirs_first.is_some()
+            || ctx.detached.is_some()
+            || (ctx.balanced && weights.symmetry_break != 0);
+        let only_best = contested && phase == Phase::Opening && !shortlist && !exploring && !self.explain;
+
+        // Everything gets a first pass without the shortlist's terms
+        let first_pass = TurnContext {
+            safe_margin: None,
+            theirs_first: None,
+            detached: None,
+            balanced: false,
+            ..ctx
+        };
+        // On a board that mirrors onto itself, a placement scores the same
+        // as its mirror image as long as everything else scoring reads does
+        // too: score one and reuse it for the other
+        let mirror = symmetry::find(board, false)
+            .filter(|&mirror| mirrors_onto_itself(&first_pass, board, mirror, &self.midline));
+        let mut scored = match mirror {
+            Some(mirror) => self.score_mirrored(board, legal, piece, &offsets, &first_pass, mirror),
+            // Nothing below looks past the best placement before contact
+            // (no shortlist, no rollouts): stop once the rest can't beat it
+            None if only_best => self.score_bounded(board, legal, piece, &offsets, &first_pass),
+            None => self.score_each(board, legal, piece, &offsets, &first_pass),
+        };
+        let candidates = scored.len();
+
+        // Then the shortlist is scored in full
+        let mut passed_over = Vec::new();
+        if shortlist {
+            scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+            passed_over = scored.split_off(SAFE_SHORTLIST.min(scored.len()));
+            for (score, (top_y, left_x)) in scored.iter_mut() {
+                *score = self.score_placement(board, piece, *top_y, *left_x, &ctx);
             }
         }
 
+        let best = self.ties.best(&scored, self.turn);
+        let best_score = best.map_or(i64::MIN, |(score, _)| score);
+        let mut best_pos = best.map(|(_, pos)| pos);
+        // Before contact the race decides, and rollouts only blur it
+        if let Some(chosen) = best_pos.filter(|_| contested && phase != Phase::Opening) {
+            best_pos = Some(self.play_out(board, piece, &scored, chosen, best_score));
+        }
+
+        if exploring && !scored.is_empty() {
+            best_pos = Some(self.softmax_pick(&scored, best_score));
+        }
+
+        // The tactical layer's answer: the best placement, and whether it
+        // serves the objective at all
+        let served = best_pos.map_or(0, |(top_y, left_x)| {
+            objective.service(&covered_cells(piece, top_y, left_x), wall.as_ref(), corridor.as_ref())
+        });
+        let executed = if served == 0 && objective != Objective::Fill {
+            Objective::Dump
+        } else {
+            objective.clone()
+        };
+        debug!("[DEBUG] Tactic: {:?} for {}, served {}%", best_pos, executed.name(), served);
+        self.plan.served(served);
+
+        self.last_decision = best_pos.map(|(top_y, left_x)| Decision {
+            phase: ctx.phase,
+            candidates,
+            outlook,
+            standing,
+            objective: (executed.describe(), served),
+            terms: self.score_terms(board, piece, top_y, left_x, &ctx),
+            ranking: if self.explain {
+                self.ranking(board, piece, &scored, &passed_over, &ctx, &first_pass)
+            } else {
+                Vec::new()
+            },
+        });
         best_pos
     }
 
+    /// Among the best placements within `ROLLOUT_MARGIN` of `best_score`, the
+    /// one whose played-out game ends with the widest lead. `chosen`, the
+    /// best by score, keeps its place against equal leads.
+    fn play_out(
+        &self,
+        board: &Board,
+        piece: &Piece,
+        scored: &[(i64, (usize, usize))],
+        chosen: (usize, usize),
+        best_score: i64,
+    ) -> (usize, usize) {
+        let margin = best_score.abs() * ROLLOUT_MARGIN / 100;
+        let mut rivals: Vec<(i64, (usize, usize))> = scored
+            .iter()
+            .copied()
+            .filter(|&(score, placement)| score >= best_score - margin && placement != chosen)
+            .collect();
+        rivals.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        let close: Vec<(usize, usize)> = std::iter::once(chosen)
+            .chain(rivals.into_iter().map(|(_, placement)| placement))
+            .take(ROLLOUT_CANDIDATES)
+            .collect();
+        if close.len() < 2 {
+            return chosen;
+        }
+
+        let mut best: Option<(i64, (usize, usize))> = None;
+        for &(top_y, left_x) in &close {
+            let mut after = board.clone();
+            after.place(piece, top_y, left_x, Owner::Me);
+            let seed = snapshot::mix(&[self.turn as u64, top_y as u64, left_x as u64]);
+            let (mine, theirs) = rollout::project(&after, &self.dealt, Owner::Opponent, seed);
+            let lead = mine as i64 - theirs as i64;
+            debug!("[DEBUG] Rollout {:?}: {} to {}", (top_y, left_x), mine, theirs);
+            if best.is_none_or(|(top, _)| lead > top) {
+                best = Some((lead, (top_y, left_x)));
+            }
+        }
+        best.map_or(chosen, |(_, placement)| placement)
+    }
+
+    /// Every candidate's terms in the order the search ranked them: the
+    /// shortlist by its full score, then what it passed over by the first.
+    fn ranking(
+        &self,
+        board: &Board,
+        piece: &Piece,
+        scored: &[(i64, (usize, usize))],
+        passed_over: &[(i64, (usize, usize))],
+        ctx: &TurnContext,
+        first_pass: &TurnContext,
+    ) -> Vec<Candidate> {
+        let mut ranked = scored.to_vec();
+        ranked.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        let full = ranked.into_iter().map(|(_, placement)| (placement, false, ctx));
+        let rest = passed_over.iter().map(|&(_, placement)| (placement, true, first_pass));
+        full.chain(rest)
+            .map(|((top_y, left_x), passed_over, ctx)| Candidate {
+                placement: (top_y, left_x),
+                terms: self.score_terms(board, piece, top_y, left_x, ctx),
+                passed_over,
+            })
+            .collect()
+    }
+
+    /// Pick a candidate with probability proportional to
+    /// exp((score - best) / temperature).
+    fn softmax_pick(&mut self, scored: &[(i64, (usize, usize))], best_score: i64) -> (usize, usize) {
+        let temperature = self.exploration.temperature.max(f64::MIN_POSITIVE);
+        let weights: Vec<f64> = scored
+            .iter()
+            .map(|&(score, _)| ((score - best_score) as f64 / temperature).exp())
+            .collect();
+
+        let mut pick = self.rng.unit() * weights.iter().sum::<f64>();
+        for (&(_, pos), w) in scored.iter().zip(&weights) {
+            if pick < *w {
+                return pos;
+            }
+            pick -= w;
+        }
+        scored.last().map_or((0, 0), |&(_, pos)| pos)
+    }
+
+    /// Sample `offsets` (see `Board::anchored_offsets`) without replacement,
+    /// weighting placements near the enemy more heavily.
+    fn sample_candidates(
+        &mut self,
+        piece: &Piece,
+        offsets: Vec<(usize, usize)>,
+        closest_enemy: (usize, usize),
+        has_enemy: bool,
+    ) -> Vec<(usize, usize)> {
+        let offsets: Vec<((usize, usize), f64)> = offsets
+            .into_iter()
+            .map(|(top_y, left_x)| {
+                let weight = if has_enemy {
+                    let (cy, cx) = (top_y + piece.height / 2, left_x + piece.width / 2);
+                    let d = cy.abs_diff(closest_enemy.0) + cx.abs_diff(closest_enemy.1);
+                    closeness(d) as f64 + 1.0
+                } else {
+                    1.0
+                };
+                ((top_y, left_x), weight)
+            })
+            .collect();
+        let total = offsets.len();
+
+        // Efraimidis-Spirakis: keep the largest u^(1/w)
+        let mut keyed: Vec<(f64, (usize, usize))> = offsets
+            .into_iter()
+            .map(|(pos, weight)| (self.rng.unit().powf(1.0 / weight), pos))
+            .collect();
+        keyed.sort_unstable_by(|a, b| b.0.total_cmp(&a.0));
+        keyed.truncate(self.sampling.samples);
+
+        debug!(
+            "[DEBUG] Sampled {} of {} candidate offsets ({:.0}%)",
+            keyed.len(),
+            total,
+            keyed.len() as f64 * 100.0 / total.max(1) as f64
+        );
+        keyed.into_iter().map(|(_, pos)| pos).collect()
+    }
+
     fn find_closest_pair(
         &self,
         my_coords: &[(usize, usize)],
         enemy_coords: &[(usize, usize)],
     ) -> ((usize, usize), (usize, usize), usize) {
-        if enemy_coords.is_empty() || my_coords.is_empty() {
+        let (Some(&first_my), Some(&first_enemy)) = (my_coords.first(), enemy_coords.first()) else {
             let my_first = my_coords.first().copied().unwrap_or((0, 0));
             return (my_first, (0, 0), usize::MAX);
-        }
+        };
 
-        let mut best_my = my_coords[0];
-        let mut best_enemy = enemy_coords[0];
+        let mut best_my = first_my;
+        let mut best_enemy = first_enemy;
         let mut best_dist = usize::MAX;
 
         for &(my, mx) in my_coords {
@@ -125,26 +1235,83 @@ impl Game {
         (sum_y / coords.len(), sum_x / coords.len())
     }
 
-    fn find_frontier(&self, my_coords: &[(usize, usize)], board: &Board) -> Vec<(usize, usize)> {
-        let mut frontier = Vec::new();
-        const DIRS: &[(isize, isize)] = &[(1, 0), (-1, 0), (0, 1), (0, -1)];
-        
-        for &(y, x) in my_coords {
-            for &(dy, dx) in DIRS {
-                let ny = y as isize + dy;
-                let nx = x as isize + dx;
-                
-                if ny >= 0 && nx >= 0 
-                    && (ny as usize) < board.rows 
-                    && (nx as usize) < board.cols
-                    && board.cells[ny as usize][nx as usize] == Owner::Empty 
-                {
-                    frontier.push((y, x));
-                    break;
-                }
-            }
-        }
-        frontier
+    /// Every legal placement among `offsets` on `legal`, scored on `board`,
+    /// in the order given.
+    #[cfg(not(feature = "parallel"))]
+    fn score_each(
+        &self,
+        board: &Board,
+        legal: &Board,
+        piece: &Piece,
+        offsets: &[(usize, usize)],
+        ctx: &TurnContext,
+    ) -> Vec<(i64, (usize, usize))> {
+        offsets
+            .iter()
+            .filter(|&&(top_y, left_x)| self.is_valid_placement(legal, piece, top_y, left_x))
+            .map(|&(top_y, left_x)| (self.score_placement(board, piece, top_y, left_x, ctx), (top_y, left_x)))
+            .collect()
+    }
+
+    /// The same spread over rayon's threads. Each candidate is scored on its
+    /// own, so the scores and their order don't change, only how long the
+    /// turn takes.
+    #[cfg(feature = "parallel")]
+    fn score_each(
+        &self,
+        board: &Board,
+        legal: &Board,
+        piece: &Piece,
+        offsets: &[(usize, usize)],
+        ctx: &TurnContext,
+    ) -> Vec<(i64, (usize, usize))> {
+        use rayon::prelude::*;
+        offsets
+            .par_iter()
+            .filter(|&&(top_y, left_x)| self.is_valid_placement(legal, piece, top_y, left_x))
+            .map(|&(top_y, left_x)| (self.score_placement(board, piece, top_y, left_x, ctx), (top_y, left_x)))
+            .collect()
+    }
+
+    /// `score_each` on a board that `mirror` maps onto itself, along with
+    /// everything in `ctx`: a placement whose image was scored already
+    /// takes its score.
+    fn score_mirrored(
+        &self,
+        board: &Board,
+        legal: &Board,
+        piece: &Piece,
+        offsets: &[(usize, usize)],
+        ctx: &TurnContext,
+        mirror: Symmetry,
+    ) -> Vec<(i64, (usize, usize))> {
+        let reflection = Reflection::of(piece, mirror);
+        let mut shared = scratch::filled(board.rows() * board.cols(), None);
+        offsets
+            .iter()
+            .filter(|&&(top_y, left_x)| self.is_valid_placement(legal, piece, top_y, left_x))
+            .map(|&at| (self.score_shared(board, piece, at, ctx, reflection, &mut shared), at))
+            .collect()
+    }
+
+    /// The score of `piece` at `at`, taken from `shared` if its image under
+    /// `reflection` was scored already, and kept there by top-left cell.
+    fn score_shared(
+        &self,
+        board: &Board,
+        piece: &Piece,
+        at: (usize, usize),
+        ctx: &TurnContext,
+        reflection: Option<Reflection>,
+        shared: &mut [Option<i64>],
+    ) -> i64 {
+        let index = |(y, x): (usize, usize)| y * board.cols() + x;
+        let known = reflection
+            .and_then(|reflection| reflection.image(board, at))
+            .and_then(|image| grid::read(shared, index(image), None));
+        let score = known.unwrap_or_else(|| self.score_placement(board, piece, at.0, at.1, ctx));
+        grid::write(shared, index(at), Some(score));
+        score
     }
 
     fn is_valid_placement(
@@ -154,29 +1321,295 @@ impl Game {
         top_y: usize,
         left_x: usize,
     ) -> bool {
-        let mut overlap_count = 0;
+        board.can_place(piece, top_y, left_x, Owner::Me)
+    }
 
-        for &(dy, dx) in &piece.cells {
-            let y = top_y + dy;
-            let x = left_x + dx;
+    /// Endgame scoring: cover as many cells as possible and leave no
+    /// single-cell holes that later pieces can't reach.
+    fn packing_score(&self, board: &Board, piece: &Piece, top_y: usize, left_x: usize) -> i64 {
+        const DIRS: &[(isize, isize)] = &[(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+        let mut covered = scratch::take::<(usize, usize)>();
+        covered.extend(piece.cells.iter().map(|&(dy, dx)| (top_y + dy, left_x + dx)));
+        let is_open = |(y, x): (usize, usize)| board.is(y, x, Owner::Empty) && !covered.contains(&(y, x));
+
+        let mut new_cells: i64 = 0;
+        let mut contact: i64 = 0;
+        let mut holes = scratch::take::<(usize, usize)>();
+
+        for &(y, x) in covered.iter() {
+            if board.is(y, x, Owner::Empty) {
+                new_cells += 1;
+            }
+            for &(dy, dx) in DIRS {
+                match board.offset(y, x, dy, dx) {
+                    Some(n) if is_open(n) => {
+                        let enclosed = !DIRS
+                            .iter()
+                            .any(|&(ey, ex)| board.offset(n.0, n.1, ey, ex).is_some_and(is_open));
+                        if enclosed && !holes.contains(&n) {
+                            holes.push(n);
+                        }
+                    }
+                    Some(n) if covered.contains(&n) => {}
+                    // Edges, walls and our own cells all make for tight packing
+                    _ => contact += 1,
+                }
+            }
+        }
+
+        let weights = &self.weights.packing;
+        new_cells * weights.new_cell + contact * weights.contact - holes.len() as i64 * weights.hole
+    }
 
-            if y >= board.rows || x >= board.cols {
-                return false;
+    /// How many enemy anchor cells (their cells with at least `min_empty`
+    /// empty neighbours, the cells they can place over) drop below that
+    /// once `covered` is ours.
+    fn anchors_removed(&self, board: &Board, covered: &[(usize, usize)], min_empty: usize) -> i64 {
+        let mut removed = scratch::take::<(usize, usize)>();
+        for &(y, x) in covered {
+            if !board.is(y, x, Owner::Empty) {
+                continue;
             }
+            for (ay, ax) in board.neighbors(y, x) {
+                if !board.is(ay, ax, Owner::Opponent) || removed.contains(&(ay, ax)) {
+                    continue;
+                }
+                let open_before = analysis::empty_neighbors(board, ay, ax);
+                let open_after = board
+                    .neighbors(ay, ax)
+                    .filter(|&(ny, nx)| board.is(ny, nx, Owner::Empty) && !covered.contains(&(ny, nx)))
+                    .count();
+                if open_before >= min_empty && open_after < min_empty {
+                    removed.push((ay, ax));
+                }
+            }
+        }
+        removed.len() as i64
+    }
 
-            match board.cells[y][x] {
-                Owner::Opponent => return false,
-                Owner::Me => {
-                    overlap_count += 1;
-                    if overlap_count > 1 {
-                        return false;
+    /// Our anchor count after taking `covered`, minus what it was before.
+    /// Only the covered cells and their neighbours can change.
+    fn anchor_change(&self, board: &Board, covered: &[(usize, usize)]) -> i64 {
+        let mut affected = scratch::take::<(usize, usize)>();
+        affected.extend_from_slice(covered);
+        for &(y, x) in covered {
+            affected.extend(board.neighbors(y, x));
+        }
+        affected.sort_unstable();
+        affected.dedup();
+
+        let mut change: i64 = 0;
+        for &(y, x) in affected.iter() {
+            let mine_after = covered.contains(&(y, x)) || board.is(y, x, Owner::Me);
+            let before = board.is(y, x, Owner::Me)
+                && board.neighbors(y, x).any(|(ny, nx)| board.is(ny, nx, Owner::Empty));
+            let after = mine_after
+                && board
+                    .neighbors(y, x)
+                    .any(|(ny, nx)| board.is(ny, nx, Owner::Empty) && !covered.contains(&(ny, nx)));
+            change += after as i64 - before as i64;
+        }
+        change
+    }
+
+    /// How much ragged frontier taking `covered` adds: the empty sides the
+    /// new cells expose minus the sides of ours they cover, plus one per
+    /// new cell left sticking out with three or more empty sides. Spikes
+    /// and long edges are where the opponent slices through.
+    fn raggedness(&self, board: &Board, covered: &[(usize, usize)]) -> i64 {
+        let mut ragged = 0;
+        for &(y, x) in covered {
+            if !board.is(y, x, Owner::Empty) {
+                continue;
+            }
+            let mut exposed = 0;
+            for (ny, nx) in board.neighbors(y, x) {
+                if covered.contains(&(ny, nx)) {
+                    continue;
+                }
+                match board.get(ny, nx) {
+                    Some(Owner::Empty) => exposed += 1,
+                    Some(Owner::Me) => ragged -= 1,
+                    _ => {}
+                }
+            }
+            ragged += exposed + i64::from(exposed >= 3);
+        }
+        ragged
+    }
+
+    /// Cells we can expect to waste in small pockets that taking `covered`
+    /// walls off behind our own cells: each pocket counts by its size times
+    /// the share of pieces too big to ever claim into it. Pockets the
+    /// opponent touches are still contested and don't count. `covered` is
+    /// sorted, as a placement's cells are.
+    fn pocket_waste(&self, board: &Board, covered: &[(usize, usize)]) -> f64 {
+        debug_assert!(covered.is_sorted());
+        let (Some(&(first, _)), Some(&(last, _))) = (covered.first(), covered.last()) else {
+            return 0.0;
+        };
+        // A flood gives up past MAX_POCKET cells, so it never gets further
+        // than this from the piece: mark what's been seen in that window
+        let margin = MAX_POCKET + 2;
+        let top = first.saturating_sub(margin);
+        let left = covered.iter().map(|c| c.1).min().unwrap_or(0).saturating_sub(margin);
+        let bottom = (last + margin).min(board.rows() - 1);
+        let right = (covered.iter().map(|c| c.1).max().unwrap_or(0) + margin).min(board.cols() - 1);
+        let width = right - left + 1;
+        let at = |(y, x): (usize, usize)| (y - top) * width + x - left;
+        let mut seen = scratch::filled((bottom - top + 1) * width, false);
+        let mut pocket = scratch::take::<(usize, usize)>();
+        let mut stack = scratch::take::<(usize, usize)>();
+        let mut waste = 0.0;
+
+        for &(y, x) in covered {
+            for start in board.neighbors(y, x) {
+                if !board.is(start.0, start.1, Owner::Empty) || covered.binary_search(&start).is_ok() || grid::read(&seen, at(start), true) {
+                    continue;
+                }
+
+                pocket.clear();
+                stack.clear();
+                pocket.push(start);
+                stack.push(start);
+                let mut enclosed = true;
+                grid::write(&mut seen, at(start), true);
+                while let Some((cy, cx)) = stack.pop() {
+                    for next in board.neighbors(cy, cx) {
+                        if covered.binary_search(&next).is_ok() || grid::read(&seen, at(next), true) {
+                            continue;
+                        }
+                        match board.get(next.0, next.1) {
+                            Some(Owner::Empty) => {
+                                grid::write(&mut seen, at(next), true);
+                                pocket.push(next);
+                                stack.push(next);
+                            }
+                            Some(Owner::Opponent) => enclosed = false,
+                            _ => {}
+                        }
+                    }
+                    if pocket.len() > MAX_POCKET {
+                        enclosed = false;
+                        break;
                     }
                 }
-                Owner::Empty => {}
+
+                if enclosed {
+                    waste += pocket.len() as f64 * self.pieces.share_larger_than(pocket.len());
+                }
+            }
+        }
+        waste
+    }
+
+    /// Every legal placement among `offsets` that could still be the best,
+    /// in the order given, for turns where only the best one matters. They
+    /// are scored in order of an upper bound on their score, and the scan
+    /// stops at the first bound below the best score found: nothing after
+    /// it can beat that, or tie with it. The bound is the distance and
+    /// contact terms, which are cheap, plus the most the rest can add (see
+    /// `score_ceiling`).
+    fn score_bounded(
+        &self,
+        board: &Board,
+        legal: &Board,
+        piece: &Piece,
+        offsets: &[(usize, usize)],
+        ctx: &TurnContext,
+    ) -> Vec<(i64, (usize, usize))> {
+        let ceiling = self.score_ceiling(board, piece, ctx);
+        let mut cells = scratch::take::<(usize, usize)>();
+        let mut bounded: Vec<(i64, usize)> = Vec::new();
+        for (i, &(top_y, left_x)) in offsets.iter().enumerate() {
+            if !self.is_valid_placement(legal, piece, top_y, left_x) {
+                continue;
             }
+            cells.clear();
+            cells.extend(piece.cells.iter().map(|&(dy, dx)| (top_y + dy, left_x + dx)));
+            cells.sort_unstable();
+            bounded.push((score_bound(board, &cells, ctx, &ceiling), i));
         }
+        bounded.sort_by_key(|&(bound, _)| std::cmp::Reverse(bound));
+
+        let mut best = i64::MIN;
+        let mut scored: Vec<(usize, i64)> = Vec::new();
+        for &(bound, i) in &bounded {
+            if bound < best {
+                break;
+            }
+            let Some(&(top_y, left_x)) = offsets.get(i) else {
+                continue;
+            };
+            let score = self.score_placement(board, piece, top_y, left_x, ctx);
+            best = best.max(score);
+            scored.push((i, score));
+        }
+        debug!("[DEBUG] Scored {} of {} placements before the bound fell short", scored.len(), bounded.len());
+
+        // Back in scan order, which tie-breaking can depend on
+        scored.sort_unstable_by_key(|&(i, _)| i);
+        scored
+            .into_iter()
+            .filter_map(|(i, score)| Some((score, *offsets.get(i)?)))
+            .collect()
+    }
 
-        overlap_count == 1
+    /// The most the terms left out of `score_bounded`'s exact part can add
+    /// in the first pass, for a placement of `piece` whose nearest cell is
+    /// `d` steps from them: each weight times whichever end of its term's
+    /// range it favours.
+    fn score_ceiling(&self, board: &Board, piece: &Piece, ctx: &TurnContext) -> impl Fn(usize) -> i64 {
+        let weights = ctx.weights;
+        let n = piece.cells.len() as i64;
+        let fixed = self.term_ranges(board, piece, ctx).into_iter().map(most).fold(0, i64::saturating_add);
+        move |d| contact_ranges(n, d, &weights).into_iter().map(most).fold(fixed, i64::saturating_add)
+    }
+
+    /// The range of each term `score_ceiling` bounds, and its weight, bar
+    /// those in `contact_ranges`. The ranges only go by the piece's size
+    /// and this turn's maps, and are empty for terms without their map
+    /// this turn.
+    fn term_ranges(&self, board: &Board, piece: &Piece, ctx: &TurnContext) -> [TermRange; 23] {
+        let weights = ctx.weights;
+        let n = piece.cells.len() as i64;
+        let span = (board.rows() + board.cols()) as i64;
+        // Longer than any path on the board, for terms counting steps
+        let steps = (board.rows() * board.cols()) as i64 + span;
+        let depth = ctx.depth.iter().map(|(_, d)| d).filter(|&d| d != usize::MAX).max().unwrap_or(0);
+        let (bonus_low, bonus_high) = patterns::bonus_range(&self.patterns.patterns);
+        let reach = self.pieces.typical_reach().saturating_sub(1);
+        let around = ((piece.height + 2 * reach) * (piece.width + 2 * reach)) as i64;
+        let midline = if ctx.balanced { self.midline.len() as i64 } else { 0 };
+        // The size of a term's range, when it has what it needs this turn
+        let given = |present: bool, size: i64| if present { size } else { 0 };
+
+        [
+            ("territory", (0, n), weights.territory),
+            ("gift", (-given(ctx.their_side.is_some(), n), 0), weights.gift),
+            ("dead_cell", (-n, 0), weights.dead_cell),
+            ("settled", (-n, 0), weights.settled),
+            ("self_mobility", (-5 * n, 5 * n), weights.self_mobility),
+            ("waste", (-n * depth as i64, 0), weights.waste),
+            ("compactness", (-5 * n, 4 * n), weights.compactness),
+            ("pocket", (-4 * n * (MAX_POCKET as i64 + 4), 0), weights.pocket),
+            ("wall_hug", (0, 4 * n), weights.wall_hug),
+            ("influence", (0, 2000 * n), weights.influence),
+            ("target_drift", (-span, 0), weights.target_drift),
+            ("midline", (-span, 0), weights.midline),
+            ("tactics", (n * bonus_low, n * bonus_high), weights.tactics),
+            ("symmetry_break", (-midline, midline), weights.symmetry_break),
+            ("explore", (0, ctx.unknown.len() as i64), weights.explore),
+            ("defense", (0, given(ctx.wall.is_some(), n)), weights.defense),
+            ("fronts", (0, given(!ctx.fronts.is_empty(), closeness(0))), weights.fronts),
+            ("corridor", (0, given(ctx.corridor.is_some(), n * (CORRIDOR_REACH as i64 + 1))), weights.corridor),
+            ("latest", (-given(ctx.latest.is_some(), steps), 0), weights.latest),
+            ("anchor", (-steps, 0), weights.anchor),
+            ("objective", (0, 100), weights.objective),
+            ("shape", (0, n), weights.shape),
+            ("follow_up", (0, given(ctx.placeable.is_some(), around)), weights.follow_up),
+        ]
     }
 
     fn score_placement(
@@ -185,84 +1618,136 @@ impl Game {
         piece: &Piece,
         top_y: usize,
         left_x: usize,
-        enemy_coords: &[(usize, usize)],
-        frontier: &[(usize, usize)],
-        target_direction: (isize, isize),
-        current_min_distance: usize,
-        closest_enemy: (usize, usize),
+        ctx: &TurnContext,
     ) -> i64 {
-        let rows = board.rows;
-        let cols = board.cols;
-        
+        let mut score = 0;
+        self.each_term(board, piece, top_y, left_x, ctx, |(_, value)| score += value);
+        score
+    }
+
+    /// The weighted terms `score_placement` adds up, by weight name.
+    fn score_terms(
+        &self,
+        board: &Board,
+        piece: &Piece,
+        top_y: usize,
+        left_x: usize,
+        ctx: &TurnContext,
+    ) -> Vec<Term> {
+        let mut terms = Vec::new();
+        self.each_term(board, piece, top_y, left_x, ctx, |term| terms.push(term));
+        terms
+    }
+
+    /// Hand each weighted term of a placement's score to `add`. Runs for
+    /// every candidate, so it allocates nothing: working vectors come from
+    /// the scratch pools (see scratch.rs). A new term needs a row in
+    /// `term_ranges` too, or `score_bounded` can skip the best placement.
+    fn each_term(
+        &self,
+        board: &Board,
+        piece: &Piece,
+        top_y: usize,
+        left_x: usize,
+        ctx: &TurnContext,
+        mut add: impl FnMut(Term),
+    ) {
+        let TurnContext {
+            enemy_distance,
+            frontier: _,
+            target_direction: _,
+            current_min_distance,
+            closest_enemy,
+            midline_target,
+            dead_zones,
+            contested,
+            chokepoints,
+            depth,
+            race,
+            influence,
+            balanced,
+            unknown,
+            wall,
+            fronts,
+            corridor,
+            latest,
+            objective,
+            shape,
+            safe_margin,
+            theirs_first,
+            their_side,
+            detached,
+            placeable,
+            phase: _,
+            weights,
+        } = *ctx;
+
+        // The opponent is sealed off: everything left is ours, pack it tight
+        if !contested {
+            add(("packing", self.packing_score(board, piece, top_y, left_x)));
+            return;
+        }
+
         // Calculate where this placement puts us
-        let mut piece_cells: Vec<(usize, usize)> = Vec::new();
+        let mut piece_cells = scratch::take::<(usize, usize)>();
         let mut new_territory: i64 = 0;
-        let mut adjacent_to_enemy: i64 = 0;
-        
-        const DIRS: &[(isize, isize)] = &[(1, 0), (-1, 0), (0, 1), (0, -1)];
+        let mut dead_cells: i64 = 0;
+        let mut settled: i64 = 0;
+        let mut wasted: i64 = 0;
+        let mut influence_gained: f64 = 0.0;
+        let mut hugging: i64 = 0;
+        let mut anchor_lag: i64 = 0;
 
         for &(dy, dx) in &piece.cells {
             let ay = top_y + dy;
             let ax = left_x + dx;
             piece_cells.push((ay, ax));
 
-            if board.cells[ay][ax] == Owner::Empty {
-                new_territory += 1;
-            }
-
-            // Check for enemy adjacency
-            for &(dyy, dxx) in DIRS {
-                let ny = ay as isize + dyy;
-                let nx = ax as isize + dxx;
-                
-                if ny >= 0 && nx >= 0 && (ny as usize) < rows && (nx as usize) < cols {
-                    if board.cells[ny as usize][nx as usize] == Owner::Opponent {
-                        adjacent_to_enemy += 1;
-                    }
-                }
-            }
-        }
-
-        // Calculate the "most forward" point of this placement
-        let mut best_advance: i64 = i64::MIN;
-        let mut min_dist_to_enemy: usize = usize::MAX;
-        
-        for &(py, px) in &piece_cells {
-            // How much does this cell advance toward target?
-            // Use dot product with normalized direction
-            let advance = if target_direction.0 != 0 || target_direction.1 != 0 {
-                let norm = ((target_direction.0 * target_direction.0 + target_direction.1 * target_direction.1) as f64).sqrt();
-                if norm > 0.0 {
-                    // Project movement onto target direction
-                    let move_y = py as isize - frontier.first().map(|f| f.0 as isize).unwrap_or(0);
-                    let move_x = px as isize - frontier.first().map(|f| f.1 as isize).unwrap_or(0);
-                    ((move_y * target_direction.0 + move_x * target_direction.1) as f64 / norm) as i64
+            if board.is(ay, ax, Owner::Empty) {
+                // Dead-zone cells are mine anyway; they're only worth
+                // taking once there's nothing contested left
+                if dead_zones.get(ay, ax).unwrap_or(false) {
+                    dead_cells += 1;
+                } else if race.get(ay, ax).flatten().is_some_and(|lead| lead >= RACE_MARGIN) {
+                    settled += 1;
                 } else {
-                    0
+                    new_territory += 1;
                 }
-            } else {
-                0
-            };
-            
-            if advance > best_advance {
-                best_advance = advance;
-            }
-
-            // Distance to closest enemy
-            for &(ey, ex) in enemy_coords {
-                let d = (py as isize - ey as isize).unsigned_abs()
-                    + (px as isize - ex as isize).unsigned_abs();
-                if d < min_dist_to_enemy {
-                    min_dist_to_enemy = d;
+                // Only finite while something is contested, which it is here
+                if let Some(d) = depth.get(ay, ax).filter(|&d| d != usize::MAX) {
+                    wasted += d as i64;
                 }
+                // Taking a cell makes its influence +1
+                influence_gained += 1.0 - influence.get(ay, ax).unwrap_or(0.0);
+                // Sides against the board edge, or against one of our own
+                // cells that is itself on the edge (extending that wall)
+                hugging += 4 - board.neighbors(ay, ax).count() as i64;
+                hugging += board
+                    .neighbors(ay, ax)
+                    .filter(|&(ny, nx)| board.is(ny, nx, Owner::Me) && board.neighbors(ny, nx).count() < 4)
+                    .count() as i64;
+            } else if current_min_distance != usize::MAX {
+                // The one cell of ours this covers: how far behind our
+                // front toward them it sits
+                let lag = match enemy_distance.get(ay, ax) {
+                    Some(d) if d != usize::MAX => d.saturating_sub(current_min_distance),
+                    _ => board.rows() + board.cols(),
+                };
+                anchor_lag = lag as i64;
             }
         }
 
+        // In order, so the flood fills below can binary-search it
+        piece_cells.sort_unstable();
+
+        // The "most forward" point of this placement, and how close it gets
+        let (best_advance, min_dist_to_enemy) = advance_and_reach(piece_cells.iter().copied(), ctx);
+
         // Distance to the closest enemy cell we identified
         let dist_to_target = {
             let (ty, tx) = closest_enemy;
             let mut min_d = usize::MAX;
-            for &(py, px) in &piece_cells {
+            for &(py, px) in piece_cells.iter() {
                 let d = (py as isize - ty as isize).unsigned_abs()
                     + (px as isize - tx as isize).unsigned_abs();
                 if d < min_d {
@@ -272,30 +1757,709 @@ impl Game {
             min_d
         };
 
-        // SCORING STRATEGY:
-        // 1. If far from enemy (distance > 5): RUSH - minimize distance
-        // 2. If close to enemy (distance <= 5): BLOCK - stay adjacent, expand around them
-        
-        if current_min_distance > 5 {
-            // RUSH MODE: Get to enemy ASAP
-            // Heavily reward reducing distance
-            let distance_reduction = current_min_distance as i64 - min_dist_to_enemy as i64;
-            let closeness_score = 1000000 / (min_dist_to_enemy as i64 + 1);
-            
-            closeness_score * 100           // Getting close is everything
-            + distance_reduction * 50000    // Reward reducing distance
-            + best_advance * 1000           // Reward advancing toward target
-            + new_territory * 10            // Territory is almost irrelevant
-            + adjacent_to_enemy * 100000    // If we can touch enemy, amazing!
+        let dist_to_midline = midline_target
+            .and_then(|(ty, tx)| piece_cells.iter().map(|&(py, px)| py.abs_diff(ty) + px.abs_diff(tx)).min())
+            .unwrap_or(0);
+
+        let ragged = self.raggedness(board, &piece_cells);
+        let pockets = self.pocket_waste(board, &piece_cells);
+        let mobility_cut = self.anchors_removed(board, &piece_cells, 1);
+        let live_cut = self.anchors_removed(board, &piece_cells, analysis::LIVE_ANCHOR_EMPTY);
+        let mobility_kept = self.anchor_change(board, &piece_cells);
+
+        let tactics = if weights.tactics == 0 {
+            0
         } else {
-            // BLOCK MODE: We're close - now surround and contain
-            let closeness_score = 100000 / (min_dist_to_enemy as i64 + 1);
-            
-            adjacent_to_enemy * 50000       // Stay glued to enemy
-            + closeness_score * 50          // Stay close
-            + new_territory * 2000          // Now territory matters
-            + best_advance * 500            // Still advance when possible
-            - (dist_to_target as i64) * 100 // Don't drift away from target
+            patterns::score(&self.patterns.patterns, board, &piece_cells)
+        };
+        // Tilt a dead-even race our way: split-line cells we'd get to first,
+        // less the ones they would
+        let symmetry_break = if balanced && weights.symmetry_break != 0 {
+            let mut after = board.clone();
+            after.place(piece, top_y, left_x, Owner::Me);
+            let mine = after.distance_field(Owner::Me);
+            let theirs = after.distance_field(Owner::Opponent);
+            self.midline
+                .iter()
+                .map(|&(y, x)| match mine.get(y, x).cmp(&theirs.get(y, x)) {
+                    std::cmp::Ordering::Less => 1,
+                    std::cmp::Ordering::Greater => -1,
+                    std::cmp::Ordering::Equal => 0,
+                })
+                .sum()
+        } else {
+            0
+        };
+        let [adjacency, cut_off, split] = contact_terms(board, &piece_cells, ctx);
+        // The shortlist's terms, on the board as it would be and with both
+        // sides' distances on it
+        let after = (safe_margin.is_some() || theirs_first.is_some() || detached.is_some()).then(|| {
+            let mut after = board.clone();
+            after.place(piece, top_y, left_x, Owner::Me);
+            let mine = after.distance_field(Owner::Me);
+            let theirs = after.distance_field(Owner::Opponent);
+            (after, mine, theirs)
+        });
+        // What we can count on once it's down, against what they can
+        let safe_gain = safe_margin.zip(after.as_ref()).map_or(0, |(before, (after, mine, theirs))| {
+            let (mine, theirs) = analysis::safe_areas_from(after, mine, theirs);
+            mine as i64 - theirs as i64 - before
+        });
+        // Cells they'd have got to first that we now get to first
+        let denied = theirs_first.zip(after.as_ref()).map_or(0, |(theirs_first, (_, mine, theirs))| {
+            theirs_first
+                .iter()
+                .filter(|&((y, x), was_theirs)| {
+                    was_theirs && mine.get(y, x).unwrap_or(usize::MAX) < theirs.get(y, x).unwrap_or(usize::MAX)
+                })
+                .count() as i64
+        });
+        // Territory it leaves cut off from the rest, or joins back up
+        let fragmented = detached.zip(after.as_ref()).map_or(0, |(before, (after, mine, theirs))| {
+            analysis::detached_territory(after, mine, theirs) as i64 - before
+        });
+        let gifts = their_side.map_or(0, |side| gifted(board, &piece_cells, side, self.pieces.typical_reach()));
+        let follow = placeable.map_or(0, |before| {
+            follow_up(board, &piece_cells, before, dead_zones, self.pieces.typical_reach())
+        });
+        let explore = fog::revealed(unknown, &piece_cells) as i64;
+        let on_corridor: i64 = corridor.map_or(0, |near| {
+            piece_cells
+                .iter()
+                .filter_map(|&(y, x)| near.get(y, x))
+                .map(|d| (CORRIDOR_REACH + 1).saturating_sub(d) as i64)
+                .sum()
+        });
+        // Close on every front, the more so the more it threatens to take
+        let front_block: f64 = fronts
+            .iter()
+            .map(|(share, field)| {
+                let d = piece_cells.iter().filter_map(|&(y, x)| field.get(y, x)).min();
+                share * closeness(d.unwrap_or(usize::MAX)) as f64
+            })
+            .sum();
+        // Lean toward where they just played: that's the front they're
+        // pushing on now. Out of their reach counts as the far side
+        let from_latest = latest.map_or(0, |field| {
+            piece_cells
+                .iter()
+                .filter_map(|&(y, x)| field.get(y, x))
+                .filter(|&d| d != usize::MAX)
+                .min()
+                .unwrap_or(board.rows() + board.cols()) as i64
+        });
+        let fit = if weights.shape == 0 {
+            0
+        } else {
+            shape_fit(board, shape, &piece_cells, enemy_distance, chokepoints, dead_zones)
+        };
+        let served = if weights.objective == 0 {
+            0
+        } else {
+            objective.service(&piece_cells, wall, corridor)
+        };
+        // Walling off a rush: cells across their path instead of toward them
+        let (walled, head_on) = match wall {
+            Some(wall) => {
+                let walled = piece_cells.iter().filter(|&&(y, x)| wall.get(y, x) == Some(true)).count();
+                (walled as i64, 0)
+            }
+            None => (0, 1),
+        };
+        let terms = [
+            ("territory", new_territory * weights.territory),
+            ("safe_area", safe_gain * weights.safe_area),
+            ("denial", denied * weights.denial),
+            ("fragment", -fragmented * weights.fragment),
+            ("gift", -gifts * weights.gift),
+            adjacency,
+            ("dead_cell", -dead_cells * weights.dead_cell),
+            ("settled", -settled * weights.settled),
+            cut_off,
+            split,
+            ("mobility", mobility_cut * weights.mobility),
+            ("anchor_pressure", live_cut * weights.anchor_pressure),
+            ("self_mobility", mobility_kept * weights.self_mobility),
+            ("waste", -wasted * weights.waste),
+            ("compactness", -ragged * weights.compactness),
+            ("pocket", -(pockets * weights.pocket as f64) as i64),
+            ("wall_hug", hugging * weights.wall_hug),
+            ("influence", (influence_gained * 1000.0) as i64 * weights.influence),
+            ("target_drift", -(dist_to_target as i64) * weights.target_drift),
+            ("midline", -(dist_to_midline as i64) * weights.midline),
+            ("tactics", tactics * weights.tactics),
+            ("symmetry_break", symmetry_break * weights.symmetry_break),
+            ("explore", explore * weights.explore),
+            ("defense", walled * weights.defense),
+            ("fronts", front_block as i64 * weights.fronts),
+            ("corridor", on_corridor * weights.corridor),
+            ("latest", -from_latest * weights.latest),
+            ("anchor", -anchor_lag * weights.anchor),
+            ("objective", served * weights.objective),
+            ("shape", fit * weights.shape),
+            ("follow_up", follow * weights.follow_up),
+        ];
+        distance_terms(best_advance, min_dist_to_enemy, head_on, ctx)
+            .into_iter()
+            .chain(terms)
+            .for_each(add);
+    }
+}
+
+/// Whether everything `each_term` reads this turn maps onto itself under
+/// `mirror`, as the board does. Single cells picked in scan order, like
+/// the enemy cell we head for or the midline target, seldom sit on the
+/// axis, and then a placement and its image score differently.
+fn mirrors_onto_itself(ctx: &TurnContext, board: &Board, mirror: Symmetry, midline: &[(usize, usize)]) -> bool {
+    let fixed = |cell: (usize, usize)| mirror.apply(board, cell) == cell;
+    let weights = &ctx.weights;
+    let (dy, dx) = ctx.target_direction;
+    // Advance is measured along the direction, which the image flips
+    let along = match mirror {
+        Symmetry::MirrorRows => dy == 0,
+        Symmetry::MirrorCols => dx == 0,
+        Symmetry::Rotate180 => dy == 0 && dx == 0,
+    };
+    let grids_hold = mirror.preserves(board, ctx.enemy_distance)
+        && mirror.preserves(board, ctx.dead_zones)
+        && mirror.preserves(board, ctx.chokepoints)
+        && mirror.preserves(board, ctx.depth)
+        && mirror.preserves(board, ctx.race)
+        && mirror.preserves(board, ctx.influence)
+        && [ctx.wall, ctx.theirs_first, ctx.their_side, ctx.placeable]
+            .into_iter()
+            .flatten()
+            .all(|grid| mirror.preserves(board, grid))
+        && [ctx.corridor, ctx.latest]
+            .into_iter()
+            .flatten()
+            .chain(ctx.fronts.iter().map(|(_, field)| field))
+            .all(|grid| mirror.preserves(board, grid));
+    grids_hold
+        && ctx.unknown.is_empty()
+        && (weights.advance == 0 || along)
+        && (weights.target_drift == 0 || fixed(ctx.closest_enemy))
+        && (weights.midline == 0 || ctx.midline_target.is_none_or(fixed))
+        && (weights.objective == 0 || ctx.objective.fixed_under(|cell| mirror.apply(board, cell)))
+        && (!ctx.balanced
+            || weights.symmetry_break == 0
+            || midline.iter().all(|&cell| midline.contains(&mirror.apply(board, cell))))
+}
+
+/// `score_bounded`'s upper bound on the score of a placement covering
+/// `cells`, sorted: its distance and contact terms plus `ceiling` at its
+/// reach.
+fn score_bound(board: &Board, cells: &[(usize, usize)], ctx: &TurnContext, ceiling: &impl Fn(usize) -> i64) -> i64 {
+    let head_on = ctx.wall.is_none() as i64;
+    let (advance, reach) = advance_and_reach(cells.iter().copied(), ctx);
+    distance_terms(advance, reach, head_on, ctx)
+        .into_iter()
+        .chain(contact_terms(board, cells, ctx))
+        .map(|(_, value)| value)
+        .fold(0, i64::saturating_add)
+        .saturating_add(ceiling(reach))
+}
+
+/// How far the furthest of `cells` gets toward `target_direction`, from
+/// the first frontier cell, and the fewest steps the opponent needs to
+/// reach any of them.
+fn advance_and_reach(cells: impl Iterator<Item = (usize, usize)>, ctx: &TurnContext) -> (i64, usize) {
+    let TurnContext {
+        enemy_distance,
+        frontier,
+        target_direction,
+        ..
+    } = *ctx;
+    let mut best_advance: i64 = i64::MIN;
+    let mut min_dist_to_enemy: usize = usize::MAX;
+
+    for (py, px) in cells {
+        // How much does this cell advance toward target?
+        // Use dot product with normalized direction
+        let advance = if target_direction.0 != 0 || target_direction.1 != 0 {
+            let norm = ((target_direction.0 * target_direction.0 + target_direction.1 * target_direction.1) as f64).sqrt();
+            if norm > 0.0 {
+                // Project movement onto target direction
+                let move_y = py as isize - frontier.first().map(|f| f.0 as isize).unwrap_or(0);
+                let move_x = px as isize - frontier.first().map(|f| f.1 as isize).unwrap_or(0);
+                ((move_y * target_direction.0 + move_x * target_direction.1) as f64 / norm) as i64
+            } else {
+                0
+            }
+        } else {
+            0
+        };
+
+        if advance > best_advance {
+            best_advance = advance;
+        }
+
+        // Steps the opponent needs to get here
+        let d = enemy_distance.get(py, px).unwrap_or(usize::MAX);
+        min_dist_to_enemy = min_dist_to_enemy.min(d);
+    }
+    (best_advance, min_dist_to_enemy)
+}
+
+/// The terms for closing in on them, from `advance_and_reach`. `head_on`
+/// is 0 while walling off a rush, when they don't count.
+fn distance_terms(best_advance: i64, min_dist_to_enemy: usize, head_on: i64, ctx: &TurnContext) -> [Term; 3] {
+    let weights = &ctx.weights;
+    let distance_reduction = if ctx.current_min_distance == usize::MAX || min_dist_to_enemy == usize::MAX {
+        0
+    } else {
+        ctx.current_min_distance as i64 - min_dist_to_enemy as i64
+    };
+    [
+        ("closeness", head_on * closeness(min_dist_to_enemy) * weights.closeness),
+        ("distance_reduction", head_on * distance_reduction * weights.distance_reduction),
+        ("advance", head_on * best_advance * weights.advance),
+    ]
+}
+
+/// The terms for pressing on them: sides touching their cells, chokepoints
+/// taken, and what splitting the empty region cuts off from them on our
+/// side. `cells` is sorted, as a placement's cells are.
+fn contact_terms(board: &Board, cells: &[(usize, usize)], ctx: &TurnContext) -> [Term; 3] {
+    let weights = &ctx.weights;
+    let mut adjacent_to_enemy: i64 = 0;
+    let mut cut_off: i64 = 0;
+    for &(y, x) in cells {
+        // Tiny pockets along the contact line aren't worth a detour
+        let cut = ctx.chokepoints.get(y, x).unwrap_or(0);
+        if board.is(y, x, Owner::Empty) && cut >= MIN_CHOKEPOINT_CUT {
+            cut_off += cut as i64;
+        }
+        adjacent_to_enemy += board
+            .neighbors(y, x)
+            .filter(|&(ny, nx)| board.is(ny, nx, Owner::Opponent))
+            .count() as i64;
+    }
+    let split = if weights.split == 0 {
+        0
+    } else {
+        analysis::sealed_by(board, cells, ctx.dead_zones, ctx.enemy_distance) as i64
+    };
+    [
+        ("adjacency", adjacent_to_enemy * weights.adjacency),
+        ("cut_off", cut_off * weights.cut_off),
+        ("split", split * weights.split),
+    ]
+}
+
+/// A term's name, the range `(low, high)` of what its weight multiplies,
+/// and its weight.
+type TermRange = (&'static str, (i64, i64), i64);
+
+/// The ranges of the terms for pressing on them that `score_bounded`
+/// leaves to the ceiling, for `n` cells whose nearest is `d` steps from
+/// theirs: taking their anchors takes a cell next to theirs, which a
+/// placement more than a step away can't.
+fn contact_ranges(n: i64, d: usize, weights: &PhaseWeights) -> [TermRange; 2] {
+    let touching = if d <= 1 { 4 * n } else { 0 };
+    [
+        ("mobility", (0, touching), weights.mobility),
+        ("anchor_pressure", (0, touching), weights.anchor_pressure),
+    ]
+}
+
+/// A term's weight times whichever end of its range `(low, high)` it
+/// favours: the most the term can add.
+fn most((_, (low, high), weight): TermRange) -> i64 {
+    low.saturating_mul(weight).max(high.saturating_mul(weight))
+}
+
+/// New cells left sticking out into open space the opponent reaches first
+/// (`their_side`): touching it, even corner to corner, with at least a
+/// typical piece's worth of it within a piece's `reach`. Their pieces can
+/// wrap around such a cell and cut it off from the rest of ours.
+fn gifted(board: &Board, cells: &[(usize, usize)], their_side: &Grid<bool>, reach: usize) -> i64 {
+    let open = |y: usize, x: usize| their_side.get(y, x) == Some(true) && !cells.contains(&(y, x));
+    let open_within = |(y, x): (usize, usize), r: usize| {
+        (y.saturating_sub(r)..=y + r)
+            .flat_map(|ny| (x.saturating_sub(r)..=x + r).map(move |nx| (ny, nx)))
+            .filter(|&(ny, nx)| open(ny, nx))
+            .count()
+    };
+    cells
+        .iter()
+        .filter(|&&(y, x)| board.is(y, x, Owner::Empty))
+        .filter(|&&cell| open_within(cell, 1) > 0 && open_within(cell, reach) >= reach * reach)
+        .count() as i64
+}
+
+/// Empty cells the new ones among `cells` bring within one more piece's
+/// `reach`, past what `before` already had in it: the room a placement opens
+/// up for the next one. Dead zones are ours whatever we do and don't count.
+fn follow_up(
+    board: &Board,
+    cells: &[(usize, usize)],
+    before: &Grid<bool>,
+    dead_zones: &Grid<bool>,
+    reach: usize,
+) -> i64 {
+    let span = reach.saturating_sub(1);
+    let mut new = scratch::take::<(usize, usize)>();
+    new.extend(cells.iter().copied().filter(|&(y, x)| board.is(y, x, Owner::Empty)));
+    let (Some(top), Some(left)) = (new.iter().map(|c| c.0).min(), new.iter().map(|c| c.1).min()) else {
+        return 0;
+    };
+    let (bottom, right) = (new.iter().map(|c| c.0).max().unwrap_or(top), new.iter().map(|c| c.1).max().unwrap_or(left));
+
+    // Only the piece's surroundings can change: dilate them alone
+    let origin = (top.saturating_sub(span), left.saturating_sub(span));
+    let (rows, cols) = (bottom + span + 1 - origin.0, right + span + 1 - origin.1);
+    let mut near = scratch::filled(rows * cols, false);
+    for &(y, x) in new.iter() {
+        grid::write(&mut near, (y - origin.0) * cols + x - origin.1, true);
+    }
+    analysis::dilate_cells(&mut near, rows, cols, span);
+    near.iter()
+        .enumerate()
+        .filter(|&(_, &reached)| reached)
+        .map(|(i, _)| (origin.0 + i / cols, origin.1 + i % cols))
+        .filter(|&(y, x)| {
+            board.is(y, x, Owner::Empty)
+                && before.get(y, x) == Some(false)
+                && dead_zones.get(y, x) == Some(false)
+                && cells.binary_search(&(y, x)).is_err()
+        })
+        .count() as i64
+}
+
+/// New cells a placement puts where its shape is at its best: a line's on
+/// the front, to wall them off; a scatter's away from our own cells, out in
+/// contested space, to claim area; a blob's on chokepoints, to plug them.
+/// Other shapes have no particular strength.
+fn shape_fit(
+    board: &Board,
+    shape: Shape,
+    cells: &[(usize, usize)],
+    enemy_distance: &Grid<usize>,
+    chokepoints: &Grid<usize>,
+    dead_zones: &Grid<bool>,
+) -> i64 {
+    let fits = |&&(y, x): &&(usize, usize)| match shape {
+        Shape::Line => enemy_distance.get(y, x).is_some_and(|d| d <= LINE_FRONT),
+        Shape::Scatter => {
+            dead_zones.get(y, x) == Some(false) && !board.neighbors(y, x).any(|(ny, nx)| board.is(ny, nx, Owner::Me))
         }
+        Shape::Blob => chokepoints.get(y, x).is_some_and(|cut| cut >= MIN_CHOKEPOINT_CUT),
+        Shape::L | Shape::T | Shape::SZ => false,
+    };
+    cells
+        .iter()
+        .filter(|&&(y, x)| board.is(y, x, Owner::Empty))
+        .filter(fits)
+        .count() as i64
+}
+
+/// The cells a placement covers, sorted.
+fn covered_cells(piece: &Piece, top_y: usize, left_x: usize) -> Vec<(usize, usize)> {
+    let mut cells: Vec<(usize, usize)> = piece.cells.iter().map(|&(dy, dx)| (top_y + dy, left_x + dx)).collect();
+    cells.sort_unstable();
+    cells
+}
+
+/// A piece whose shape is its own image under a board symmetry, so the
+/// image of any placement of it is another placement of it. `span` is the
+/// least plus the most offset of its cells, down and across.
+#[derive(Clone, Copy)]
+struct Reflection {
+    mirror: Symmetry,
+    span: (usize, usize),
+}
+
+impl Reflection {
+    /// `None` when `mirror` turns the piece into a different shape.
+    fn of(piece: &Piece, mirror: Symmetry) -> Option<Reflection> {
+        let ys = piece.cells.iter().map(|&(dy, _)| dy);
+        let xs = piece.cells.iter().map(|&(_, dx)| dx);
+        let span = (ys.clone().min()? + ys.max()?, xs.clone().min()? + xs.max()?);
+        let reflected = |(dy, dx): (usize, usize)| match mirror {
+            Symmetry::MirrorRows => (span.0 - dy, dx),
+            Symmetry::MirrorCols => (dy, span.1 - dx),
+            Symmetry::Rotate180 => (span.0 - dy, span.1 - dx),
+        };
+        piece
+            .cells
+            .iter()
+            .all(|&cell| piece.cells.contains(&reflected(cell)))
+            .then_some(Reflection { mirror, span })
+    }
+
+    /// The top-left cell of the placement covering the image of the one
+    /// at `(top_y, left_x)`.
+    fn image(self, board: &Board, (top_y, left_x): (usize, usize)) -> Option<(usize, usize)> {
+        let flip_y = || (board.rows() - 1).checked_sub(top_y + self.span.0);
+        let flip_x = || (board.cols() - 1).checked_sub(left_x + self.span.1);
+        match self.mirror {
+            Symmetry::MirrorRows => Some((flip_y()?, left_x)),
+            Symmetry::MirrorCols => Some((top_y, flip_x()?)),
+            Symmetry::Rotate180 => Some((flip_y()?, flip_x()?)),
+        }
+    }
+}
+
+/// The empty cells of a wall across a rush coming from direction `from` of
+/// `home`: a band `WALL_DEPTH` deep just in front of our cells, short of
+/// theirs, as wide as the gap between the two sides.
+fn rush_wall(board: &Board, home: (f64, f64), from: (f64, f64)) -> Grid<bool> {
+    let along = |(y, x): (usize, usize)| (y as f64 - home.0) * from.0 + (x as f64 - home.1) * from.1;
+    let across = |(y, x): (usize, usize)| ((y as f64 - home.0) * from.1 - (x as f64 - home.1) * from.0).abs();
+    let ours = board.coords(Owner::Me).into_iter().map(along).fold(f64::MIN, f64::max);
+    let theirs = board.coords(Owner::Opponent).into_iter().map(along).fold(f64::MAX, f64::min);
+    let half_width = (theirs - ours).max(WALL_DEPTH * 2.0);
+
+    let mut wall = Grid::new(board.rows(), board.cols(), false);
+    for ((y, x), owner) in board.iter() {
+        let depth = along((y, x));
+        let in_band = depth > ours && depth <= ours + WALL_DEPTH && depth < theirs;
+        wall.set(y, x, owner == Owner::Empty && in_band && across((y, x)) <= half_width);
+    }
+    wall
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::allocations;
+    use crate::config::Config;
+    use crate::scenario;
+
+    /// p1's board and piece once the game is in contact.
+    fn contact_position() -> (Board, Piece) {
+        let (mut game, mut players) = scenario::shipped_game("map01", 7);
+        loop {
+            let turn = game.step(&mut players).expect("game lasts into contact");
+            if turn.player == 1 && turn.number >= 41 {
+                return (turn.board, turn.piece);
+            }
+        }
+    }
+
+    /// What a `TurnContext` borrows, worked out from `board` the way
+    /// `heuristic_move` does, with every optional map present so every term
+    /// runs. The enemy cell, midline target and objective all point at
+    /// `toward`.
+    struct Inputs {
+        toward: (usize, usize),
+        theirs: Grid<usize>,
+        frontier: Vec<(usize, usize)>,
+        dead_zones: Grid<bool>,
+        chokepoints: Grid<usize>,
+        depth: Grid<usize>,
+        race: Grid<Option<i64>>,
+        influence: Grid<f64>,
+        their_side: Grid<bool>,
+        wall: Grid<bool>,
+        fronts: Vec<(f64, Grid<usize>)>,
+        near: Grid<usize>,
+        placeable: Grid<bool>,
+        objective: Objective,
+    }
+
+    impl Inputs {
+        fn of(board: &Board, game: &Game, toward: (usize, usize)) -> Inputs {
+            let reach = game.pieces.typical_reach();
+            let theirs = board.distance_field(Owner::Opponent);
+            let dead_zones = board.dead_zones();
+            Inputs {
+                toward,
+                fronts: vec![(1.0, theirs.clone())],
+                theirs,
+                frontier: board.anchors(Owner::Me),
+                chokepoints: analysis::chokepoints(board),
+                depth: analysis::frontier_depth(board, &dead_zones),
+                dead_zones,
+                race: analysis::region_race(board, reach),
+                influence: analysis::influence_map(board, DEFAULT_INFLUENCE_DECAY),
+                their_side: Grid::new(board.rows(), board.cols(), true),
+                wall: Grid::new(board.rows(), board.cols(), false),
+                near: board.distance_from(board.coords(Owner::Opponent)),
+                placeable: analysis::placeable_next(board, reach),
+                objective: Objective::Expand { toward },
+            }
+        }
+
+        fn ctx(&self, piece: &Piece, weights: PhaseWeights) -> TurnContext<'_> {
+            let from = self.frontier.first().copied().unwrap_or_default();
+            TurnContext {
+                enemy_distance: &self.theirs,
+                frontier: &self.frontier,
+                target_direction: (
+                    self.toward.0 as isize - from.0 as isize,
+                    self.toward.1 as isize - from.1 as isize,
+                ),
+                current_min_distance: 1,
+                closest_enemy: self.toward,
+                midline_target: Some(self.toward),
+                dead_zones: &self.dead_zones,
+                contested: true,
+                chokepoints: &self.chokepoints,
+                depth: &self.depth,
+                race: &self.race,
+                influence: &self.influence,
+                balanced: false,
+                unknown: &[],
+                wall: Some(&self.wall),
+                fronts: &self.fronts,
+                corridor: Some(&self.near),
+                latest: Some(&self.near),
+                objective: &self.objective,
+                shape: piece.shape(),
+                // The shortlist's terms play the placement out on a copy of
+                // the board: a handful of candidates a turn pay for that
+                safe_margin: None,
+                theirs_first: None,
+                their_side: Some(&self.their_side),
+                detached: None,
+                placeable: Some(&self.placeable),
+                phase: Phase::Midgame,
+                weights,
+            }
+        }
+    }
+
+    fn board(rows: &[&str]) -> Board {
+        let mut lines = vec![format!("Anfield {} {}:", rows[0].len(), rows.len())];
+        lines.extend(rows.iter().enumerate().map(|(y, row)| format!("{:03} {}", y, row)));
+        Board::from_anfield_lines(&lines, 1).expect("valid board")
+    }
+
+    #[test]
+    fn scoring_candidates_allocates_nothing() {
+        let (board, piece) = contact_position();
+        let game = Config::default().build_game(1);
+        let inputs = Inputs::of(&board, &game, board.coords(Owner::Opponent)[0]);
+        let ctx = inputs.ctx(&piece, game.weights.midgame);
+
+        let legal = board.legal_placements(&piece, Owner::Me);
+        assert!(!legal.is_empty(), "no placement to score");
+        let score_all = || {
+            for &(top_y, left_x) in &legal {
+                std::hint::black_box(game.score_placement(&board, &piece, top_y, left_x, &ctx));
+            }
+        };
+        // The first pass fills the scratch pools
+        score_all();
+        assert_eq!(allocations::during(score_all), 0, "allocated while scoring {} placements", legal.len());
+
+        // A board that mirrors onto itself shares scores between images
+        let mirrored = self::board(&["...@...", ".......", ".......", ".......", "..$.$.."]);
+        let piece = Piece::from_piece_lines(&["Piece 2 1:".to_string(), "OO".to_string()]).expect("valid piece");
+        let inputs = Inputs::of(&mirrored, &game, (4, 3));
+        let ctx = inputs.ctx(&piece, game.weights.midgame);
+        let mirror = symmetry::find(&mirrored, false).expect("board mirrors onto itself");
+        assert!(mirrors_onto_itself(&ctx, &mirrored, mirror, &[]));
+        let reflection = Reflection::of(&piece, mirror);
+        assert!(reflection.is_some(), "piece is its own mirror image");
+
+        let legal = mirrored.legal_placements(&piece, Owner::Me);
+        assert!(!legal.is_empty(), "no placement to score");
+        let share_all = || {
+            let mut shared = scratch::filled(mirrored.rows() * mirrored.cols(), None);
+            for &at in &legal {
+                std::hint::black_box(game.score_shared(&mirrored, &piece, at, &ctx, reflection, &mut shared));
+            }
+        };
+        share_all();
+        assert_eq!(allocations::during(share_all), 0, "allocated while sharing {} placements", legal.len());
+    }
+
+    /// `term_ranges` is kept by hand next to `each_term`: on real positions
+    /// no term may go past its range nor placement score above its bound,
+    /// and pruning by it must keep every best placement.
+    #[test]
+    fn score_bound_holds_on_shipped_positions() {
+        let (mut game, mut players) = scenario::shipped_game("map01", 3);
+        let mut checked = 0;
+        while let Some(turn) = game.step(&mut players) {
+            if turn.player != 1 || turn.number % 10 != 1 {
+                continue;
+            }
+            let (player, board, piece) = (&players[0], &turn.board, &turn.piece);
+            let Some(&toward) = board.coords(Owner::Opponent).first() else {
+                continue;
+            };
+            let inputs = Inputs::of(board, player, toward);
+            let offsets = board.legal_placements(piece, Owner::Me);
+            for weights in [player.weights.opening, player.weights.midgame, player.weights.endgame] {
+                let ctx = inputs.ctx(piece, weights);
+                let ceiling = player.score_ceiling(board, piece, &ctx);
+                let ranges = player.term_ranges(board, piece, &ctx);
+                for &(top_y, left_x) in &offsets {
+                    let cells = covered_cells(piece, top_y, left_x);
+                    let bound = score_bound(board, &cells, &ctx, &ceiling);
+                    let score = player.score_placement(board, piece, top_y, left_x, &ctx);
+                    assert!(bound >= score, "turn {}: {:?} scores {} over its bound {}", turn.number, (top_y, left_x), score, bound);
+
+                    // Term by term, as the total has plenty of slack
+                    let (_, reach) = advance_and_reach(cells.iter().copied(), &ctx);
+                    let contact = contact_ranges(piece.cells.len() as i64, reach, &weights);
+                    let exact: Vec<&str> = distance_terms(0, 0, 0, &ctx)
+                        .into_iter()
+                        .chain(contact_terms(board, &cells, &ctx))
+                        .map(|(name, _)| name)
+                        .collect();
+                    for (name, value) in player.score_terms(board, piece, top_y, left_x, &ctx) {
+                        match ranges.iter().chain(&contact).find(|&&(row, _, _)| row == name) {
+                            Some(&range) => assert!(value <= most(range), "turn {}: {} is {} over {:?}", turn.number, name, value, range),
+                            None => assert!(value == 0 || exact.contains(&name), "{} has no row in term_ranges", name),
+                        }
+                    }
+                }
+
+                let best = |scored: Vec<(i64, (usize, usize))>| {
+                    let top = scored.iter().map(|&(score, _)| score).max();
+                    scored.into_iter().filter(|&(score, _)| Some(score) == top).collect::<Vec<_>>()
+                };
+                assert_eq!(
+                    best(player.score_bounded(board, board, piece, &offsets, &ctx)),
+                    best(player.score_each(board, board, piece, &offsets, &ctx)),
+                    "turn {}",
+                    turn.number
+                );
+            }
+            checked += 1;
+        }
+        assert!(checked > 5, "only {} positions checked", checked);
+    }
+
+    /// Left and right mirror each other, but the enemy cell we head for is
+    /// the first found in scan order, left of the axis: a placement and its
+    /// image don't score the same, so nothing may be shared. Heading for a
+    /// cell on the axis they do.
+    #[test]
+    fn mirror_images_share_only_what_is_symmetric() {
+        let board = board(&["...@...", ".......", ".......", ".......", "..$.$.."]);
+        let piece = Piece::from_piece_lines(&["Piece 2 1:".to_string(), "OO".to_string()]).expect("valid piece");
+        let game = Config::default().build_game(1);
+        let weights = game.weights.midgame;
+        let mirror = symmetry::find(&board, false).expect("board mirrors onto itself");
+        // (0, 2) covers (0, 2)-(0, 3), and its image (0, 3) covers (0, 3)-(0, 4)
+        assert_eq!(mirror.apply(&board, (0, 2)), (0, 4));
+
+        let scan_order = Inputs::of(&board, &game, (4, 2));
+        let ctx = scan_order.ctx(&piece, weights);
+        assert!(!mirrors_onto_itself(&ctx, &board, mirror, &[]));
+        assert_ne!(
+            game.score_placement(&board, &piece, 0, 2, &ctx),
+            game.score_placement(&board, &piece, 0, 3, &ctx)
+        );
+
+        let on_axis = Inputs::of(&board, &game, (4, 3));
+        let ctx = on_axis.ctx(&piece, weights);
+        assert!(mirrors_onto_itself(&ctx, &board, mirror, &[]));
+        assert_eq!(
+            game.score_placement(&board, &piece, 0, 2, &ctx),
+            game.score_placement(&board, &piece, 0, 3, &ctx)
+        );
+        let offsets = board.legal_placements(&piece, Owner::Me);
+        assert_eq!(
+            game.score_mirrored(&board, &board, &piece, &offsets, &ctx, mirror),
+            game.score_each(&board, &board, &piece, &offsets, &ctx)
+        );
     }
 }
@@ -0,0 +1,24 @@
+// src/greedy.rs
+// A sparring partner that only counts territory. Each placement is tried
+// on a copy of the board and scored by the cells we'd then reach before
+// the opponent (`analysis::voronoi_areas`), fewer cells for them breaking
+// ties. No lookahead, no patterns, no plan: whatever a strategy gains over
+// it comes from something other than grabbing area one move at a time.
+
+use crate::analysis;
+use crate::board::{Board, Owner};
+use crate::piece::Piece;
+
+pub fn choose_move(board: &Board, piece: &Piece) -> Option<(usize, usize)> {
+    let mut best: Option<((i64, i64), (usize, usize))> = None;
+    for (top_y, left_x) in board.legal_placements(piece, Owner::Me) {
+        let mut next = board.clone();
+        next.place(piece, top_y, left_x, Owner::Me);
+        let (mine, theirs) = analysis::voronoi_areas(&next);
+        let score = (mine as i64, -(theirs as i64));
+        if best.is_none_or(|(top, _)| score > top) {
+            best = Some((score, (top_y, left_x)));
+        }
+    }
+    best.map(|(_, at)| at)
+}
@@ -0,0 +1,102 @@
+// src/grid.rs
+// A rectangular grid with checked access only. The fields are private so
+// that nothing outside this file can index out of bounds: every read goes
+// through `get`, which returns `None` off the board, and neighbours are
+// only ever produced in bounds. Competition play must never panic.
+
+const DIRS: [(isize, isize); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid<T> {
+    rows: usize,
+    cols: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Copy> Grid<T> {
+    pub fn new(rows: usize, cols: usize, fill: T) -> Self {
+        Grid {
+            rows,
+            cols,
+            cells: vec![fill; rows * cols],
+        }
+    }
+
+    /// Build from rows of possibly different lengths. Short rows are padded
+    /// with `fill` and long ones cut to the width of the first row.
+    pub fn from_rows(rows: Vec<Vec<T>>, fill: T) -> Self {
+        let cols = rows.first().map_or(0, |r| r.len());
+        let mut grid = Grid::new(rows.len(), cols, fill);
+        for (y, row) in rows.into_iter().enumerate() {
+            for (x, value) in row.into_iter().take(cols).enumerate() {
+                grid.set(y, x, value);
+            }
+        }
+        grid
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get(&self, y: usize, x: usize) -> Option<T> {
+        if y < self.rows && x < self.cols {
+            self.cells.get(y * self.cols + x).copied()
+        } else {
+            None
+        }
+    }
+
+    /// Write a cell. Out-of-bounds writes are ignored and return false.
+    pub fn set(&mut self, y: usize, x: usize, value: T) -> bool {
+        if y >= self.rows || x >= self.cols {
+            return false;
+        }
+        match self.cells.get_mut(y * self.cols + x) {
+            Some(cell) => {
+                *cell = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The cell at (y + dy, x + dx), if that's on the grid.
+    pub fn offset(&self, y: usize, x: usize, dy: isize, dx: isize) -> Option<(usize, usize)> {
+        let ny = y.checked_add_signed(dy)?;
+        let nx = x.checked_add_signed(dx)?;
+        (ny < self.rows && nx < self.cols).then_some((ny, nx))
+    }
+
+    /// The up to four orthogonal neighbours of (y, x) that are on the grid.
+    pub fn neighbors(&self, y: usize, x: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        DIRS.iter().filter_map(move |&(dy, dx)| self.offset(y, x, dy, dx))
+    }
+
+    /// Every cell with its coordinates, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), T)> + '_ {
+        let cols = self.cols.max(1);
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, &value)| ((i / cols, i % cols), value))
+    }
+}
+
+/// `cells[at]`, or `fill` past the end: the same checked access for flat
+/// arrays, like scratch buffers kept a row at a time or graph vertices,
+/// that aren't worth a `Grid`.
+pub fn read<T: Copy>(cells: &[T], at: usize, fill: T) -> T {
+    cells.get(at).copied().unwrap_or(fill)
+}
+
+/// Set `cells[at]`. Writes past the end are ignored.
+pub fn write<T>(cells: &mut [T], at: usize, value: T) {
+    if let Some(cell) = cells.get_mut(at) {
+        *cell = value;
+    }
+}
@@ -0,0 +1,133 @@
+// src/guardrail.rs
+// Worst-case latency checks. Positions are sampled from a self-play game
+// on the biggest map we ship, then every piece in `pieces/awkward.txt` is
+// handed to a fresh player in each of them. Each decision has to come back
+// within `BUDGET` with a legal placement, or `None` only when there is none.
+// A feature that makes some turn quietly slow fails here, not in a match.
+// The incremental bookkeeping the game loop relies on for speed (reparse,
+// held cell lists) is checked against collecting from scratch here too.
+
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::board::{Board, Holdings, Owner};
+use crate::config::Config;
+use crate::referee::parse_piece_script;
+use crate::scenario::shipped_game;
+
+/// Time allowed for one decision. Tests run unoptimised, which is several
+/// times slower than the release build we play with.
+const BUDGET: Duration = if cfg!(debug_assertions) {
+    Duration::from_secs(1)
+} else {
+    Duration::from_millis(250)
+};
+
+/// Self-play turns whose position is kept: the open board, first contact
+/// and a crowded middle game. p1 moves on the odd turns.
+const SAMPLES: [usize; 3] = [1, 21, 61];
+
+const SEED: u64 = 7;
+
+/// Positions from p1's side, as `(turn, board)`.
+fn positions() -> Vec<(usize, Board)> {
+    let (mut game, mut players) = shipped_game("map02", SEED);
+    let last = SAMPLES[SAMPLES.len() - 1];
+
+    let mut kept = Vec::new();
+    while let Some(turn) = game.step(&mut players) {
+        if SAMPLES.contains(&turn.number) {
+            kept.push((turn.number, turn.board));
+        }
+        if turn.number >= last {
+            break;
+        }
+    }
+    kept
+}
+
+#[test]
+fn awkward_pieces_decide_within_budget() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    let pieces = parse_piece_script(&fs::read_to_string(root.join("pieces/awkward.txt")).expect("readable pieces"))
+        .expect("valid pieces");
+
+    let positions = positions();
+    assert_eq!(positions.len(), SAMPLES.len(), "game ended before every sample");
+
+    let mut report = Vec::new();
+    for (turn, board) in &positions {
+        for piece in &pieces {
+            let shape = format!("turn {} piece {}x{}", turn, piece.width, piece.height);
+            let mut player = Config::default().build_game(1);
+
+            let started = Instant::now();
+            let chosen = player.choose_best_move(board, piece);
+            let elapsed = started.elapsed();
+
+            if elapsed > BUDGET {
+                report.push(format!("{}: took {:?}, budget {:?}", shape, elapsed, BUDGET));
+            }
+            match chosen {
+                Some((y, x)) if !board.can_place(piece, y, x, Owner::Me) => {
+                    report.push(format!("{}: illegal placement ({}, {})", shape, y, x));
+                }
+                None if !board.legal_placements(piece, Owner::Me).is_empty() => {
+                    report.push(format!("{}: passed with a legal placement available", shape));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    assert!(report.is_empty(), "guardrail failures:\n{}", report.join("\n"));
+}
+
+/// `Board::reparse` against a full parse of the same lines, every turn of a
+/// whole game from p1's side, chained the way the game loop chains it.
+#[test]
+fn reparse_matches_full_parse() {
+    let (mut game, mut players) = shipped_game("map01", SEED);
+
+    let mut lines = game.anfield_lines();
+    let mut board = Board::from_anfield_lines(&lines, 1).expect("valid board");
+    while let Some(turn) = game.step(&mut players) {
+        let next = game.anfield_lines();
+        board = board.reparse(&lines, &next, 1).expect("reparsed board");
+        lines = next;
+
+        let full = Board::from_anfield_lines(&lines, 1).expect("valid board");
+        assert_eq!((board.rows(), board.cols()), (full.rows(), full.cols()), "turn {}", turn.number);
+        for y in 0..full.rows() {
+            for x in 0..full.cols() {
+                assert_eq!(board.get(y, x), full.get(y, x), "turn {} cell ({}, {})", turn.number, y, x);
+            }
+        }
+        for owner in [Owner::Me, Owner::Opponent] {
+            let (mut got, mut want) = (board.latest(owner), full.latest(owner));
+            got.sort_unstable();
+            want.sort_unstable();
+            assert_eq!(got, want, "turn {} latest {:?}", turn.number, owner);
+        }
+        assert_eq!(board.zobrist(), full.zobrist(), "turn {}", turn.number);
+    }
+}
+
+/// `Holdings::update` against `Holdings::of` on the same board, every turn
+/// of a whole game from p1's side.
+#[test]
+fn holdings_update_matches_collecting() {
+    let (mut game, mut players) = shipped_game("map01", SEED);
+
+    let mut holdings = Holdings::default();
+    while let Some(turn) = game.step(&mut players) {
+        let board = Board::from_anfield_lines(&game.anfield_lines(), 1).expect("valid board");
+        holdings.update(&board);
+
+        let fresh = Holdings::of(&board);
+        assert_eq!(holdings.mine, fresh.mine, "turn {} mine", turn.number);
+        assert_eq!(holdings.theirs, fresh.theirs, "turn {} theirs", turn.number);
+        assert_eq!(holdings.frontier, fresh.frontier, "turn {} frontier", turn.number);
+    }
+}
@@ -0,0 +1,73 @@
+// src/latency.rs
+// Decision times collected by the referee, broken down by game phase for the
+// post-game report. Contact is where the time pressure and the stakes peak,
+// so an average over the whole game hides exactly the turns that matter.
+
+use std::time::Duration;
+
+use crate::game::Phase;
+
+const PHASES: [Phase; 3] = Phase::ALL;
+
+/// Upper bounds of the histogram buckets; the last bucket is open-ended.
+const BUCKETS_MS: [u64; 4] = [1, 10, 100, 1000];
+
+#[derive(Default)]
+pub struct LatencyReport {
+    /// Samples per phase, in the order of `PHASES`.
+    samples: [Vec<Duration>; 3],
+}
+
+impl LatencyReport {
+    pub fn record(&mut self, phase: Phase, elapsed: Duration) {
+        let index = PHASES.iter().position(|&p| p == phase).unwrap_or(0);
+        if let Some(samples) = self.samples.get_mut(index) {
+            samples.push(elapsed);
+        }
+    }
+
+    /// One line per phase that saw any turns.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (phase, samples) in PHASES.iter().zip(&self.samples) {
+            if samples.is_empty() {
+                continue;
+            }
+            let mut sorted = samples.clone();
+            sorted.sort_unstable();
+            let total: Duration = sorted.iter().sum();
+            let mean = total / sorted.len() as u32;
+
+            let mut counts = [0usize; BUCKETS_MS.len() + 1];
+            for d in &sorted {
+                let ms = d.as_millis() as u64;
+                let bucket = BUCKETS_MS.iter().position(|&b| ms < b).unwrap_or(BUCKETS_MS.len());
+                if let Some(count) = counts.get_mut(bucket) {
+                    *count += 1;
+                }
+            }
+
+            lines.push(format!(
+                "{:<8} n={:<4} mean={:>8.2?} p50={:>8.2?} p95={:>8.2?} max={:>8.2?}  <1ms:{} <10ms:{} <100ms:{} <1s:{} >=1s:{}",
+                phase.name(),
+                sorted.len(),
+                mean,
+                percentile(&sorted, 50),
+                percentile(&sorted, 95),
+                sorted.last().copied().unwrap_or_default(),
+                counts[0],
+                counts[1],
+                counts[2],
+                counts[3],
+                counts[4],
+            ));
+        }
+        lines
+    }
+}
+
+/// Nearest-rank percentile of a sorted, non-empty slice.
+fn percentile(sorted: &[Duration], p: usize) -> Duration {
+    let rank = (p * sorted.len()).div_ceil(100).max(1);
+    sorted.get(rank.min(sorted.len()) - 1).copied().unwrap_or_default()
+}
@@ -1,18 +1,159 @@
 // src/main.rs
+// Raw indexing can panic mid-game, and a panic forfeits the match: go
+// through `Grid`, `.get()` or iterators instead (see grid.rs). Tests may
+// index, a panic there is just a failure.
+#![cfg_attr(not(test), deny(clippy::indexing_slicing))]
+
+/// Debug output, only when FILLER_DEBUG or FILLER_TELEMETRY is set. It goes
+/// to stderr unless FILLER_TELEMETRY names another sink (see telemetry.rs).
+/// The engine never drains our stderr pipe, so a chatty bot fills it on big
+/// maps, blocks on the next write and times out.
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if $crate::debug_enabled() {
+            $crate::telemetry::emit(format_args!($($arg)*));
+        }
+    };
+}
 
 mod parser;
+mod patterns;
+mod promote;
+mod anytime;
+mod bandit;
+mod beam;
+mod bench;
+mod bits;
 mod board;
+mod book;
+mod grid;
+mod latency;
 mod piece;
+mod fit;
+mod fog;
+mod fallback;
 mod game;
+mod greedy;
+mod analysis;
+mod cmaes;
+mod config;
+mod crawler;
+mod ensemble;
+mod eval;
+mod control;
+mod mcts;
+mod memory;
+mod mirror;
+mod objective;
+mod opponent;
+mod package;
+mod profiles;
+mod rng;
+mod rollout;
+mod scratch;
+mod snapshot;
+mod solo;
+mod symmetry;
+mod tempo;
+mod timebank;
+mod ties;
+mod transposition;
+mod telemetry;
+mod tune;
+mod referee;
+mod weights;
+mod winprob;
+#[cfg(test)]
+mod scenario;
+#[cfg(test)]
+mod allocations;
+#[cfg(test)]
+mod guardrail;
 
-use std::io::{self, BufRead, Write};
+use std::io::{self, BufRead};
+use std::time::Instant;
 
 use crate::parser::parse_player_number;
 use crate::board::Board;
 use crate::piece::Piece;
-use crate::game::Game;
+use crate::config::Config;
+use crate::fallback::{Failure, FallbackMovePolicy, Watchdog};
+
+fn debug_enabled() -> bool {
+    telemetry::enabled()
+}
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let rest = args.get(2..).unwrap_or_default();
+    match args.get(1).map(|a| a.as_str()) {
+        Some("play") => {
+            if let Err(e) = referee::run_cli(rest) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some("tune") => {
+            if let Err(e) = tune::run_cli(rest) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some("fit") => {
+            if let Err(e) = fit::run_cli(rest) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some("book") => {
+            if let Err(e) = book::run_cli(rest) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some("bench") => {
+            if let Err(e) = bench::run_cli(rest) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some("opponents") => {
+            if let Err(e) = memory::run_cli(rest) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some("promote") => {
+            if let Err(e) = promote::run_cli(rest) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some("strategies") => {
+            config::print_strategies();
+            return;
+        }
+        Some("package") => {
+            if let Err(e) = package::run_cli(rest) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some("version") => {
+            println!("{}", package::fingerprint());
+            return;
+        }
+        _ => {}
+    }
+
     let stdin = io::stdin();
     let mut lines = stdin.lock().lines();
 
@@ -32,11 +173,26 @@ fn main() {
         }
     };
 
-    //unused??
-    let game = Game::new(my_player); 
-    
+    let config = Config::from_env();
+    let watchdog = Watchdog::new(io::stdout(), config.deadline);
+    let mut policy = FallbackMovePolicy::default();
+    fallback::quiet_panics();
+
+    let mut game = config.build_game(my_player);
+    debug!("[DEBUG] {}", package::fingerprint());
+    debug!("[DEBUG] Playing as p{} with the {} strategy", game.my_player, game.strategy.name());
+
+    let control = control::Control::from_env();
+    game.explain = control.is_some();
+    let mut turn: usize = 0;
+    let mut last_board: Option<Board> = None;
+    // The Anfield block `last_board` was parsed from
+    let mut last_lines: Vec<String> = Vec::new();
+
     // 2) Main game loop: each iteration = one turn
     'game_loop: loop {
+        turn += 1;
+
         // Collect Anfield block
         let mut anfield_lines: Vec<String> = Vec::new();
 
@@ -51,7 +207,7 @@ fn main() {
                 }
                 _ => {
                     // No more data, game over
-                    return;
+                    break 'game_loop;
                 }
             }
         };
@@ -72,33 +228,44 @@ fn main() {
                 }
                 _ => {
                     // EOF before piece, stop
-                    return;
+                    break 'game_loop;
                 }
             }
         }
 
-        // Parse the board from the collected lines
-        let board = match Board::from_anfield_lines(&anfield_lines, my_player) {
-            Some(b) => b,
-            None => break 'game_loop,
+        // A board that doesn't parse still has its piece block read, so the
+        // next turn starts in the right place. After the first turn only the
+        // rows that changed since the last board are parsed
+        let board = match &last_board {
+            Some(last) => last.reparse(&last_lines, &anfield_lines, my_player),
+            None => Board::from_anfield_lines(&anfield_lines, my_player),
         };
 
+        if let (true, Some(board)) = (debug_enabled(), &board) {
+            let features = winprob::Features::from_board(board);
+            debug!(
+                "[DEBUG] Win probability: {:.0}% (cells {:+.3}, reach {:+.3}, filled {:.0}%)",
+                features.win_probability() * 100.0,
+                features.cell_diff,
+                features.reach_diff,
+                features.phase * 100.0
+            );
+            debug!(
+                "[DEBUG] Live anchors: mine {}, theirs {}",
+                analysis::live_anchors(board, board::Owner::Me),
+                analysis::live_anchors(board, board::Owner::Opponent)
+            );
+        }
+
         // Collect piece block: header + height lines
         let mut piece_lines: Vec<String> = Vec::new();
         piece_lines.push(piece_header.clone());
 
-        let height = {
-            let trimmed = piece_header.trim();
-            let parts: Vec<&str> = trimmed.split_whitespace().collect();
-            if parts.len() >= 3 {
-                parts[2]
-                    .trim_end_matches(':')
-                    .parse::<usize>()
-                    .unwrap_or(0)
-            } else {
-                0
-            }
-        };
+        let height = piece_header
+            .split_whitespace()
+            .nth(2)
+            .and_then(|height| height.trim_end_matches(':').parse::<usize>().ok())
+            .unwrap_or(0);
         
         for _ in 0..height {
             match lines.next() {
@@ -111,31 +278,102 @@ fn main() {
         }
         
 
-        let piece = match Piece::from_piece_lines(&piece_lines) {
-            Some(p) => p,
-            None => break 'game_loop,
+        let (board, piece) = match (board, Piece::from_piece_lines(&piece_lines)) {
+            (Some(board), Some(piece)) => (board, piece),
+            (board, piece) => {
+                let placement = policy.recover(Failure::Parse, board.as_ref(), piece.as_ref());
+                let answer = match &piece {
+                    Some(piece) => piece.declared(placement),
+                    None => (placement.0 as i64, placement.1 as i64),
+                };
+                watchdog.answer(turn, answer);
+                continue;
+            }
         };
 
-        // Ask the strategy for the best move
-        // choose_best_move returns (row, col) but we need to output "X Y" format
-        // where X is column and Y is row
-        let (out_row, out_col) = match game.choose_best_move(&board, &piece) {
-            Some((y, x)) => {
-                eprintln!("[DEBUG] Found placement at row={}, col={}", y, x);
-                (y, x)
-            }
-            None => {
-                eprintln!("[DEBUG] No valid placement found! Board: {}x{}, Piece: {}x{}", 
-                    board.rows, board.cols, piece.height, piece.width);
-                eprintln!("[DEBUG] Piece cells: {} filled", piece.cells.len());
-                eprintln!("[DEBUG] My territory cells: {}", 
-                    board.cells.iter().flatten().filter(|&&c| c == crate::board::Owner::Me).count());
-                (0usize, 0usize) // fallback if no valid placement
+        // Ask the strategy for the best move. The watchdog answers with the
+        // fallback if it takes too long
+        watchdog.arm(turn, piece.declared(policy.fallback(&board, &piece).unwrap_or(fallback::PASS)));
+        let started = Instant::now();
+        let chosen = fallback::guarded(|| game.choose_best_move(&board, &piece));
+        if let Some(control) = &control {
+            let placement = chosen.ok().flatten();
+            control.record(turn, &board, started.elapsed(), placement, game.census(), game.last_decision.clone());
+        }
+        if debug_enabled() {
+            debug!(
+                "[DEBUG] Turn {} snapshot {:016x}",
+                turn,
+                snapshot::turn_hash(&board, &piece, chosen.ok().flatten())
+            );
+            let census = game.census();
+            debug!("[DEBUG] Cells: us {}, them {}", census.me, census.opponent);
+            debug!(
+                "[DEBUG] Tempo: behind {} turns running, trend {:+}",
+                game.tempo().losing_streak(),
+                game.tempo().trend()
+            );
+            let (mine, theirs) = game.territory_outlook(&board);
+            debug!(
+                "[DEBUG] Outlook over {} turns: us ~{:.0} cells, them ~{:.0}",
+                game::OUTLOOK_TURNS,
+                mine,
+                theirs
+            );
+        }
+
+        match chosen {
+            Ok(Some((y, x))) => debug!("[DEBUG] Found placement at row={}, col={}", y, x),
+            Ok(None) => {
+                debug!("[DEBUG] No valid placement found! Board: {}x{}, Piece: {}x{}", 
+                    board.rows(), board.cols(), piece.height, piece.width);
+                debug!("[DEBUG] Piece cells: {} filled", piece.cells.len());
+                debug!("[DEBUG] My territory cells: {}", 
+                    board.count(crate::board::Owner::Me));
             }
-        };
+            Err(_) => {}
+        }
 
-        // Output in "X Y" format where X=column, Y=row
-        println!("{} {}", out_col, out_row);
-        let _ = io::stdout().flush();
+        // Anything unusable is replaced by the fallback; either way exactly
+        // one "X Y" line goes out
+        let placement = policy.settle(&board, &piece, chosen);
+        watchdog.answer(turn, piece.declared(placement));
+        last_board = Some(board);
+        last_lines = anfield_lines;
+    }
+
+    debug!("[DEBUG] Objectives: {}", game.plan().summary(game.turn()));
+    if let Some(bank) = game.strategy.time_bank() {
+        debug!("[DEBUG] Time bank: {}", bank.summary());
+    }
+    if let Some(board) = last_board {
+        remember_game(&game, &board);
+    }
+}
+
+/// Fold the finished game into the opponent memory, when FILLER_MEMORY and
+/// FILLER_OPPONENT are both set (see memory.rs). The result is judged from
+/// the last board we were shown.
+fn remember_game(game: &game::Game, board: &Board) {
+    let (Ok(path), Ok(opponent)) = (std::env::var("FILLER_MEMORY"), std::env::var("FILLER_OPPONENT")) else {
+        return;
+    };
+    let mut memory = match memory::Memory::load(&path) {
+        Ok(memory) => memory,
+        Err(e) => {
+            eprintln!("not updating opponent memory: {}", e);
+            return;
+        }
+    };
+    memory.record(
+        &opponent,
+        &memory::Observation {
+            won: board.count(board::Owner::Me) > board.count(board::Owner::Opponent),
+            style: game.opponent().style(),
+            weights: game.weights,
+        },
+    );
+    if let Err(e) = memory.save(&path) {
+        eprintln!("not updating opponent memory: {}", e);
     }
 }
@@ -0,0 +1,374 @@
+// src/mcts.rs
+// Monte Carlo Tree Search: an alternative to the heuristic scorer in game.rs.
+//
+// We never see the opponent's pieces, so the tree is "open loop": a node is
+// identified by the cells its move claimed, and every piece below the root is
+// sampled from the ones we've been dealt so far. Rollouts use a cheap greedy
+// policy and are scored by who owns more of the board once they finish, or
+// by a model if one is loaded (see eval.rs).
+
+use std::collections::HashMap;
+
+use crate::analysis;
+use crate::board::{Board, Owner};
+use crate::eval::Evaluator;
+use crate::piece::Piece;
+use crate::rng::Rng;
+
+#[derive(Clone, Copy, Debug)]
+pub struct MctsConfig {
+    /// Playouts per turn.
+    pub playouts: usize,
+    /// UCB1 exploration constant.
+    pub exploration: f64,
+    /// Plies of greedy play after leaving the tree.
+    pub rollout_depth: usize,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        MctsConfig {
+            playouts: 200,
+            exploration: 1.4,
+            rollout_depth: 4,
+        }
+    }
+}
+
+/// Legal moves for one piece: claimed cells -> top-left offset.
+type Moves = HashMap<Vec<(usize, usize)>, (usize, usize)>;
+
+/// Only the most recent pieces are kept as the sampling pool.
+const PIECE_POOL_SIZE: usize = 64;
+
+struct Node {
+    /// Cells claimed by the move leading here, sorted. Empty means a pass.
+    cells: Vec<(usize, usize)>,
+    /// Top-left offset of that move. Only meaningful for the root's children.
+    anchor: (usize, usize),
+    visits: u32,
+    /// Summed rewards, from the point of view of the player who moved here.
+    reward: f64,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn new(cells: Vec<(usize, usize)>, anchor: (usize, usize)) -> Self {
+        Node {
+            cells,
+            anchor,
+            visits: 0,
+            reward: 0.0,
+            children: Vec::new(),
+        }
+    }
+}
+
+pub struct Mcts {
+    config: MctsConfig,
+    rng: Rng,
+    /// Pieces we've been dealt; the engine draws both players' pieces alike.
+    seen_pieces: Vec<Piece>,
+    /// Subtree under the move we played last turn, with the board right after it.
+    kept: Option<(Node, Board)>,
+    evaluator: Evaluator,
+}
+
+impl Mcts {
+    pub fn new(config: MctsConfig) -> Self {
+        Mcts {
+            config,
+            rng: Rng::from_time(),
+            seen_pieces: Vec::new(),
+            kept: None,
+            evaluator: Evaluator::Voronoi,
+        }
+    }
+
+    pub fn with_evaluator(mut self, evaluator: Evaluator) -> Self {
+        self.evaluator = evaluator;
+        self
+    }
+
+    pub fn choose_move(&mut self, board: &Board, piece: &Piece) -> Option<(usize, usize)> {
+        if self.seen_pieces.len() == PIECE_POOL_SIZE {
+            self.seen_pieces.remove(0);
+        }
+        self.seen_pieces.push(piece.clone());
+
+        let mut root = self.reroot(board);
+
+        // The root's children are the real legal moves for the real piece.
+        // Reuse statistics from the previous turn where the cells line up.
+        let mut old_children: HashMap<Vec<(usize, usize)>, Node> = root
+            .children
+            .drain(..)
+            .map(|child| (child.cells.clone(), child))
+            .collect();
+
+        for (top_y, left_x) in board.legal_placements(piece, Owner::Me) {
+            let cells = placed_cells(piece, top_y, left_x);
+            let mut child = old_children
+                .remove(&cells)
+                .unwrap_or_else(|| Node::new(cells, (0, 0)));
+            child.anchor = (top_y, left_x);
+            root.children.push(child);
+        }
+
+        if root.children.is_empty() {
+            self.kept = None;
+            return None;
+        }
+
+        for _ in 0..self.config.playouts {
+            let mut sim = board.clone();
+            let index = self.select(&root, None);
+            let Some(child) = root.children.get_mut(index) else {
+                break;
+            };
+            claim(&mut sim, &child.cells, Owner::Me);
+            let reward = self.simulate(child, &mut sim, Owner::Opponent);
+            child.visits += 1;
+            child.reward += reward;
+            root.visits += 1;
+        }
+
+        let best = root
+            .children
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, child)| child.visits)
+            .map_or(0, |(i, _)| i);
+        let chosen = root.children.swap_remove(best);
+        let anchor = chosen.anchor;
+
+        let mut after = board.clone();
+        claim(&mut after, &chosen.cells, Owner::Me);
+        self.kept = Some((chosen, after));
+
+        Some(anchor)
+    }
+
+    /// Find the node for the position we're in now by matching the cells the
+    /// opponent claimed since our last move. Falls back to a fresh tree.
+    fn reroot(&mut self, board: &Board) -> Node {
+        let fresh = Node::new(Vec::new(), (0, 0));
+
+        let (mut kept, previous) = match self.kept.take() {
+            Some(kept) => kept,
+            None => return fresh,
+        };
+
+        if previous.rows() != board.rows() || previous.cols() != board.cols() {
+            return fresh;
+        }
+
+        let opponent_cells: Vec<(usize, usize)> = board
+            .iter()
+            .filter(|&((y, x), owner)| owner == Owner::Opponent && previous.is(y, x, Owner::Empty))
+            .map(|(pos, _)| pos)
+            .collect();
+
+        match kept.children.iter().position(|c| c.cells == opponent_cells) {
+            Some(i) => kept.children.swap_remove(i),
+            None => fresh,
+        }
+    }
+
+    /// UCB1 over the children of `node`. When `legal` is given, only children
+    /// whose cells are in it may be picked.
+    fn select(&self, node: &Node, legal: Option<&Moves>) -> usize {
+        let parent_visits = (node.visits.max(1) as f64).ln();
+        let mut best_index = 0;
+        let mut best_value = f64::MIN;
+
+        for (i, child) in node.children.iter().enumerate() {
+            if let Some(legal) = legal {
+                if !legal.contains_key(&child.cells) {
+                    continue;
+                }
+            }
+
+            let value = if child.visits == 0 {
+                f64::MAX
+            } else {
+                let visits = child.visits as f64;
+                child.reward / visits + self.config.exploration * (parent_visits / visits).sqrt()
+            };
+
+            if value > best_value {
+                best_value = value;
+                best_index = i;
+            }
+        }
+
+        best_index
+    }
+
+    /// One step of tree descent from `node`, where `to_move` plays next.
+    /// Returns the playout reward from my point of view.
+    fn simulate(&mut self, node: &mut Node, board: &mut Board, to_move: Owner) -> f64 {
+        let Some(piece) = self.sample_piece() else {
+            return self.evaluator.score(board);
+        };
+        let legal: Moves = board
+            .legal_placements(&piece, to_move)
+            .into_iter()
+            .map(|(y, x)| (placed_cells(&piece, y, x), (y, x)))
+            .collect();
+
+        let unexplored: Vec<&Vec<(usize, usize)>> = legal
+            .keys()
+            .filter(|cells| !node.children.iter().any(|c| &c.cells == *cells))
+            .collect();
+
+        let pick = (!unexplored.is_empty()).then(|| self.rng.below(unexplored.len()));
+        let (index, expanded) = if legal.is_empty() {
+            // Passing is a move like any other, so it gets a node too
+            match node.children.iter().position(|c| c.cells.is_empty()) {
+                Some(i) => (i, false),
+                None => {
+                    node.children.push(Node::new(Vec::new(), (0, 0)));
+                    (node.children.len() - 1, true)
+                }
+            }
+        } else if let Some(&cells) = pick.and_then(|pick| unexplored.get(pick)) {
+            node.children.push(Node::new(cells.clone(), (0, 0)));
+            (node.children.len() - 1, true)
+        } else {
+            (self.select(node, Some(&legal)), false)
+        };
+
+        let Some(child) = node.children.get_mut(index) else {
+            return self.evaluator.score(board);
+        };
+        claim(board, &child.cells, to_move);
+
+        let next = other(to_move);
+        let reward = if expanded {
+            self.rollout(board, next)
+        } else if child.cells.is_empty() && node.cells.is_empty() {
+            // Two passes in a row: nobody can move, the game is over
+            self.evaluator.score(board)
+        } else {
+            self.simulate(child, board, next)
+        };
+
+        child.visits += 1;
+        child.reward += if to_move == Owner::Me { reward } else { 1.0 - reward };
+        node.visits += 1;
+
+        reward
+    }
+
+    /// Greedy playout from `board` with `to_move` to play, then evaluate.
+    fn rollout(&mut self, board: &mut Board, mut to_move: Owner) -> f64 {
+        let mut passes = 0;
+
+        for _ in 0..self.config.rollout_depth {
+            let Some(piece) = self.sample_piece() else {
+                break;
+            };
+            match greedy_move(board, &piece, to_move, &mut self.rng) {
+                Some((top_y, left_x)) => {
+                    board.place(&piece, top_y, left_x, to_move);
+                    passes = 0;
+                }
+                None => {
+                    passes += 1;
+                    if passes == 2 {
+                        break;
+                    }
+                }
+            }
+            to_move = other(to_move);
+        }
+
+        self.evaluator.score(board)
+    }
+
+    fn sample_piece(&mut self) -> Option<Piece> {
+        let index = self.rng.below(self.seen_pieces.len());
+        self.seen_pieces.get(index).cloned()
+    }
+}
+
+fn other(owner: Owner) -> Owner {
+    if owner == Owner::Me {
+        Owner::Opponent
+    } else {
+        Owner::Me
+    }
+}
+
+fn placed_cells(piece: &Piece, top_y: usize, left_x: usize) -> Vec<(usize, usize)> {
+    let mut cells: Vec<(usize, usize)> = piece
+        .cells
+        .iter()
+        .map(|&(dy, dx)| (top_y + dy, left_x + dx))
+        .collect();
+    cells.sort_unstable();
+    cells
+}
+
+fn claim(board: &mut Board, cells: &[(usize, usize)], who: Owner) {
+    for &(y, x) in cells {
+        board.set(y, x, who);
+    }
+}
+
+/// Rollout policy: touch the other player if possible, otherwise head for
+/// their centroid. Ties are broken randomly so playouts stay varied.
+pub fn greedy_move(board: &Board, piece: &Piece, who: Owner, rng: &mut Rng) -> Option<(usize, usize)> {
+    let placements = board.legal_placements(piece, who);
+    if placements.is_empty() {
+        return None;
+    }
+
+    let them = other(who);
+    let (mut sum_y, mut sum_x, mut n) = (0usize, 0usize, 0usize);
+    for (y, x) in board.coords(them) {
+        sum_y += y;
+        sum_x += x;
+        n += 1;
+    }
+    let target = match (sum_y.checked_div(n), sum_x.checked_div(n)) {
+        (Some(y), Some(x)) => (y, x),
+        _ => (board.rows() / 2, board.cols() / 2),
+    };
+
+    let mut best: Vec<(usize, usize)> = Vec::new();
+    let mut best_score = i64::MIN;
+
+    for &(top_y, left_x) in &placements {
+        let mut score: i64 = 0;
+        for &(dy, dx) in &piece.cells {
+            let y = top_y + dy;
+            let x = left_x + dx;
+            score -= ((y as isize - target.0 as isize).abs() + (x as isize - target.1 as isize).abs()) as i64;
+
+            score += 20 * board.neighbors(y, x).filter(|&(ny, nx)| board.is(ny, nx, them)).count() as i64;
+        }
+
+        if score > best_score {
+            best_score = score;
+            best.clear();
+        }
+        if score == best_score {
+            best.push((top_y, left_x));
+        }
+    }
+
+    best.get(rng.below(best.len())).copied()
+}
+
+/// Share of the contested board I'd get if both sides raced for every cell.
+pub fn evaluate(board: &Board) -> f64 {
+    let (my_area, their_area) = analysis::voronoi_areas(board);
+
+    if my_area + their_area == 0 {
+        0.5
+    } else {
+        my_area as f64 / (my_area + their_area) as f64
+    }
+}
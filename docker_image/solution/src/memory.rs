@@ -0,0 +1,265 @@
+// src/memory.rs
+// What the bot remembers about each opponent across games. The engine never
+// tells us who we're playing, so the name comes from FILLER_OPPONENT, set by
+// whoever launches the match; FILLER_MEMORY names the file. With both set,
+// every finished game is folded into that opponent's profile.
+//
+// The file is the same small TOML subset as the weights, one section per
+// opponent:
+//
+//     [terminator]
+//     updated = 1760572800        # unix seconds of the last game
+//     games = 6.42                # decayed count
+//     wins = 1.87                 # decayed count
+//     aggression = 0.38           # share of their cells placed into contact
+//     drift_y = -0.21             # average direction of their growth
+//     drift_x = 0.65
+//     weight.contact.adjacency = 60000
+//
+// Old games fade: every count and average is weighted by one half per
+// `HALF_LIFE` since it was recorded, so a robot that changed its play
+// stops being judged on what it used to do. `weight.*` keys are the
+// overrides of the last game we won against them.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::weights::Weights;
+
+/// Observations lose half their weight over this long.
+const HALF_LIFE_SECS: f64 = 14.0 * 24.0 * 3600.0;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Profile {
+    pub updated: u64,
+    pub games: f64,
+    pub wins: f64,
+    pub aggression: f64,
+    pub drift: (f64, f64),
+    /// `section.key` weight overrides from the last win against them.
+    pub weights: Vec<(String, i64)>,
+}
+
+/// One finished game against an opponent.
+pub struct Observation {
+    pub won: bool,
+    /// Style estimates from `OpponentModel::style`, if it saw enough moves.
+    pub style: Option<(f64, (f64, f64))>,
+    pub weights: Weights,
+}
+
+impl Profile {
+    /// How much the stored numbers still count at `now`.
+    fn decay(&self, now: u64) -> f64 {
+        0.5f64.powf(now.saturating_sub(self.updated) as f64 / HALF_LIFE_SECS)
+    }
+
+    /// This profile as it stands at `now`, with the counts decayed.
+    pub fn at(&self, now: u64) -> Profile {
+        let decay = self.decay(now);
+        Profile {
+            games: self.games * decay,
+            wins: self.wins * decay,
+            ..self.clone()
+        }
+    }
+
+    fn record(&mut self, game: &Observation, now: u64) {
+        let decay = self.decay(now);
+        let old = self.games * decay;
+        if let Some((aggression, drift)) = game.style {
+            let blend = |past: f64, new: f64| (past * old + new) / (old + 1.0);
+            self.aggression = blend(self.aggression, aggression);
+            self.drift = (blend(self.drift.0, drift.0), blend(self.drift.1, drift.1));
+        }
+        self.games = old + 1.0;
+        self.wins = self.wins * decay + if game.won { 1.0 } else { 0.0 };
+        if game.won {
+            self.weights = game.weights.overrides();
+        }
+        self.updated = now;
+    }
+}
+
+#[derive(Default)]
+pub struct Memory {
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl Memory {
+    /// A missing file is an empty memory.
+    pub fn load(path: &str) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(text) => Memory::parse(&text).map_err(|e| format!("{}: {}", path, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Memory::default()),
+            Err(e) => Err(format!("{}: {}", path, e)),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut memory = Memory::default();
+        let mut current: Option<String> = None;
+
+        for (n, raw) in text.lines().enumerate() {
+            let line = raw.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let name = name.trim().to_string();
+                memory.profiles.entry(name.clone()).or_default();
+                current = Some(name);
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected `key = value`", n + 1))?;
+            let (key, value) = (key.trim(), value.trim());
+            let profile = current
+                .as_ref()
+                .and_then(|name| memory.profiles.get_mut(name))
+                .ok_or_else(|| format!("line {}: `{}` is outside any opponent", n + 1, key))?;
+            let bad = || format!("line {}: bad value for `{}`", n + 1, key);
+            let number = || value.parse::<f64>().map_err(|_| bad());
+
+            match key {
+                "updated" => profile.updated = value.parse().map_err(|_| bad())?,
+                "games" => profile.games = number()?,
+                "wins" => profile.wins = number()?,
+                "aggression" => profile.aggression = number()?,
+                "drift_y" => profile.drift.0 = number()?,
+                "drift_x" => profile.drift.1 = number()?,
+                _ => match key.strip_prefix("weight.") {
+                    Some(weight) => profile.weights.push((weight.to_string(), value.parse().map_err(|_| bad())?)),
+                    None => return Err(format!("line {}: unknown key `{}`", n + 1, key)),
+                },
+            }
+        }
+
+        Ok(memory)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let mut lines = Vec::new();
+        for (name, profile) in &self.profiles {
+            lines.push(format!("[{}]", name));
+            lines.push(format!("updated = {}", profile.updated));
+            lines.push(format!("games = {:.4}", profile.games));
+            lines.push(format!("wins = {:.4}", profile.wins));
+            lines.push(format!("aggression = {:.4}", profile.aggression));
+            lines.push(format!("drift_y = {:.4}", profile.drift.0));
+            lines.push(format!("drift_x = {:.4}", profile.drift.1));
+            for (key, value) in &profile.weights {
+                lines.push(format!("weight.{} = {}", key, value));
+            }
+            lines.push(String::new());
+        }
+        fs::write(path, lines.join("\n")).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn record(&mut self, opponent: &str, game: &Observation) {
+        let now = now();
+        self.profiles.entry(opponent.to_string()).or_default().record(game, now);
+    }
+}
+
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// `filler opponents [FILE]`: what the memory holds, decayed to today.
+pub fn run_cli(args: &[String]) -> Result<(), String> {
+    let path = match args.first() {
+        Some(path) => path.clone(),
+        None => std::env::var("FILLER_MEMORY").map_err(|_| "usage: filler opponents [FILE] (or set FILLER_MEMORY)")?,
+    };
+    let memory = Memory::load(&path)?;
+    if memory.profiles.is_empty() {
+        println!("{}: no opponents yet", path);
+        return Ok(());
+    }
+
+    let now = now();
+    for (name, stored) in &memory.profiles {
+        let profile = stored.at(now);
+        let days = now.saturating_sub(profile.updated) as f64 / 86400.0;
+        let rate = if profile.games > 0.0 { profile.wins / profile.games * 100.0 } else { 0.0 };
+        println!("{}", name);
+        println!("  games {:.1} (decayed), won {:.0}%, last seen {:.1} days ago", profile.games, rate, days);
+        println!(
+            "  aggression {:.2}, drift ({:+.2}, {:+.2})",
+            profile.aggression, profile.drift.0, profile.drift.1
+        );
+        if profile.weights.is_empty() {
+            println!("  no weight overrides recorded");
+        }
+        for (key, value) in &profile.weights {
+            println!("  override {} = {}", key, value);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HALF_LIFE: u64 = HALF_LIFE_SECS as u64;
+
+    /// A game with one weight off its default, so a win has an override
+    /// to keep.
+    fn game(won: bool, aggression: f64) -> Observation {
+        let mut weights = Weights::default();
+        weights.midgame.adjacency += 1;
+        Observation {
+            won,
+            style: Some((aggression, (-aggression, aggression))),
+            weights,
+        }
+    }
+
+    #[test]
+    fn saved_memory_parses_back() {
+        let mut memory = Memory::default();
+        let terminator = memory.profiles.entry("terminator".to_string()).or_default();
+        terminator.record(&game(true, 0.25), 1_000);
+        terminator.record(&game(false, 0.75), 1_000);
+        assert_eq!((terminator.games, terminator.wins, terminator.aggression), (2.0, 1.0, 0.5));
+        assert_eq!(terminator.drift, (-0.5, 0.5));
+        assert_eq!(terminator.weights, game(true, 0.0).weights.overrides());
+        memory.profiles.entry("wall_e".to_string()).or_default().record(&game(false, 0.5), 2_000);
+
+        let path = std::env::temp_dir().join(format!("filler-memory-{}.toml", std::process::id()));
+        let path = path.to_str().expect("utf-8 temp path");
+        memory.save(path).expect("memory saves");
+        let loaded = Memory::load(path).expect("memory loads");
+        fs::remove_file(path).ok();
+        assert_eq!(loaded.profiles, memory.profiles);
+    }
+
+    #[test]
+    fn counts_halve_every_half_life() {
+        let profile = Profile {
+            updated: 1_000,
+            games: 4.0,
+            wins: 2.0,
+            aggression: 0.5,
+            ..Profile::default()
+        };
+        assert_eq!(profile.at(1_000), profile);
+        let later = profile.at(1_000 + HALF_LIFE);
+        assert_eq!((later.games, later.wins, later.updated), (2.0, 1.0, 1_000));
+        let later = profile.at(1_000 + 2 * HALF_LIFE);
+        assert_eq!((later.games, later.wins), (1.0, 0.5));
+
+        // Recorded a half-life on, the four games before count as two, so
+        // the new one is a third of the blend
+        let mut recorded = profile.clone();
+        recorded.record(&game(false, 0.8), 1_000 + HALF_LIFE);
+        assert_eq!((recorded.games, recorded.wins, recorded.updated), (3.0, 1.0, 1_000 + HALF_LIFE));
+        assert!((recorded.aggression - 0.6).abs() < 1e-9, "aggression {}", recorded.aggression);
+        assert!(recorded.weights.is_empty(), "a loss keeps no overrides");
+    }
+}
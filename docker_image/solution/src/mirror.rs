@@ -0,0 +1,44 @@
+// src/mirror.rs
+// A sparring partner that copies the opponent. Each turn it aims for the
+// mirror image, through the centre of the board, of the cells the opponent
+// just took. Most maps put the two starts opposite each other, so on them
+// the mirror keeps the position as balanced as the pieces allow, and a
+// strategy has to actually out-play the symmetric reply to get ahead of it.
+//
+// The placement covering the most of the mirrored cells wins, then the one
+// nearest them. Before the opponent has moved (or when their move can't be
+// told apart on the board) it mirrors everything they hold instead.
+
+use crate::board::{Board, Owner};
+use crate::piece::Piece;
+use crate::symmetry::Symmetry;
+
+pub fn choose_move(board: &Board, piece: &Piece) -> Option<(usize, usize)> {
+    let mut source = board.latest(Owner::Opponent);
+    if source.is_empty() {
+        source = board.coords(Owner::Opponent);
+    }
+    let target: Vec<(usize, usize)> =
+        source.into_iter().map(|pos| Symmetry::Rotate180.apply(board, pos)).collect();
+
+    let mut best: Option<((usize, i64), (usize, usize))> = None;
+    for (top_y, left_x) in board.legal_placements(piece, Owner::Me) {
+        let mut copied = 0;
+        let mut distance = 0;
+        for &(dy, dx) in &piece.cells {
+            let (y, x) = (top_y + dy, left_x + dx);
+            if !board.is(y, x, Owner::Empty) {
+                continue;
+            }
+            if target.contains(&(y, x)) {
+                copied += 1;
+            }
+            distance += target.iter().map(|&(ty, tx)| y.abs_diff(ty) + x.abs_diff(tx)).min().unwrap_or(0) as i64;
+        }
+        let score = (copied, -distance);
+        if best.is_none_or(|(top, _)| score > top) {
+            best = Some((score, (top_y, left_x)));
+        }
+    }
+    best.map(|(_, at)| at)
+}
@@ -0,0 +1,471 @@
+// src/objective.rs
+// The strategic half of the heuristic's decision. Each turn one objective
+// is chosen from the position before any placement is scored: seal the
+// best corridor in reach, expand toward the cells the race is closest on,
+// rush or wall off the opponent, or just fill. The tactical half is the
+// usual placement scorer with one more term, `objective`, for how well a
+// placement serves it (see `Objective::service`). Once in contact there is
+// one more: encircle them, completing a ring of cells a fixed number of
+// steps out from their blob, the tightest one we win the race to. And once
+// they're walling us in, there's escaping: breaking out into the largest
+// region we still reach first rather than pressing a front that's lost.
+// When nothing legal serves it the piece is dumped wherever the other
+// terms like best.
+//
+// Both choices go to the debug output and into `Decision::objective`, so a
+// move can be read as "expand toward (12, 30), served 50%" instead of as
+// thirty weighted numbers.
+//
+// An objective is kept across turns by a `Plan` rather than re-chosen every
+// turn, so a seal isn't dropped for a slightly better one halfway through.
+// It ends when it's done, when its precondition stops holding (they took
+// the cell first), when it goes unserved for `STALL_TURNS` turns, or when
+// something more urgent comes up. Every ending is recorded, and the game's
+// tally goes to the debug output when it's over.
+
+use crate::board::{Board, Owner};
+use crate::grid::Grid;
+
+/// Chokepoints sealing off fewer cells than this aren't worth a turn.
+const MIN_SEAL: usize = 8;
+
+/// Turns in a row an objective may go unserved before it's given up.
+const STALL_TURNS: usize = 3;
+
+/// Steps out from their blob an encircling ring may be drawn.
+const RING_RADII: std::ops::RangeInclusive<usize> = 2..=6;
+
+/// Rings shorter than this have already closed on their own.
+const MIN_RING: usize = 8;
+
+/// Regions smaller than this aren't worth breaking out to.
+const MIN_ESCAPE: usize = 8;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Objective {
+    /// Nothing is contested any more: pack what's left.
+    Fill,
+    /// They're rushing us: build the wall across their approach.
+    Wall,
+    /// Race along the planned corridor to them.
+    Rush,
+    /// Take the chokepoint at `at`, cutting them off from `cut` cells.
+    Seal { at: (usize, usize), cut: usize },
+    /// Grow toward `toward`, the nearest cell we only just reach first.
+    Expand { toward: (usize, usize) },
+    /// They're walling us in: break out toward `toward`, our nearest cell
+    /// of the largest region we still reach first, `area` cells.
+    Escape { toward: (usize, usize), area: usize },
+    /// Close a ring round them `radius` steps out: `ring` is what's still
+    /// open of the `size` cells it was drawn with, in order.
+    Encircle { ring: Vec<(usize, usize)>, size: usize, radius: usize },
+    /// No legal placement serves the objective chosen: get rid of the
+    /// piece. Only ever the outcome of the tactical layer, never chosen.
+    Dump,
+}
+
+/// What the strategic layer looks at, all computed for scoring anyway.
+pub struct Facts<'a> {
+    pub board: &'a Board,
+    pub contested: bool,
+    /// The two sides have met.
+    pub contact: bool,
+    pub rushed: bool,
+    pub rushing: bool,
+    pub chokepoints: &'a Grid<usize>,
+    /// Steps the opponent needs to each cell.
+    pub enemy_distance: &'a Grid<usize>,
+    /// Roughly how far one piece reaches (see `PieceStats::typical_reach`).
+    pub reach: usize,
+}
+
+impl Objective {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Objective::Fill => "fill",
+            Objective::Wall => "wall",
+            Objective::Rush => "rush",
+            Objective::Seal { .. } => "seal",
+            Objective::Expand { .. } => "expand",
+            Objective::Escape { .. } => "escape",
+            Objective::Encircle { .. } => "encircle",
+            Objective::Dump => "dump",
+        }
+    }
+
+    /// Which objective wins when a new one comes up while another is being
+    /// pursued: higher pre-empts lower.
+    fn urgency(&self) -> u8 {
+        match self {
+            Objective::Wall => 6,
+            Objective::Rush => 5,
+            Objective::Seal { .. } => 4,
+            Objective::Escape { .. } => 3,
+            Objective::Encircle { .. } => 2,
+            Objective::Expand { .. } => 1,
+            Objective::Fill | Objective::Dump => 0,
+        }
+    }
+
+    /// The precondition: whether it's still worth pursuing.
+    pub fn holds(&self, facts: &Facts) -> bool {
+        let board = facts.board;
+        match *self {
+            // While we still win the race to most of what's left of it
+            Objective::Encircle { ref ring, .. } => {
+                facts.contested && 2 * ring_lead(ring, &board.distance_field(Owner::Me), facts) >= ring.len()
+            }
+            Objective::Fill | Objective::Dump => true,
+            Objective::Wall | Objective::Rush => facts.contested,
+            Objective::Seal { at: (y, x), .. } => {
+                facts.contested
+                    && board.is(y, x, Owner::Empty)
+                    && facts.chokepoints.get(y, x).is_some_and(|cut| cut >= MIN_SEAL)
+            }
+            Objective::Expand { toward: (y, x) } | Objective::Escape { toward: (y, x), .. } => {
+                let mine = board.distance_field(Owner::Me).get(y, x).unwrap_or(usize::MAX);
+                let theirs = facts.enemy_distance.get(y, x).unwrap_or(usize::MAX);
+                facts.contested && board.is(y, x, Owner::Empty) && mine != usize::MAX && mine <= theirs
+            }
+        }
+    }
+
+    /// Whether it's been achieved. Filling is never done, only over.
+    pub fn done(&self, facts: &Facts) -> bool {
+        match *self {
+            Objective::Wall => !facts.rushed,
+            Objective::Rush => !facts.rushing,
+            Objective::Seal { at: (y, x), .. }
+            | Objective::Expand { toward: (y, x) }
+            | Objective::Escape { toward: (y, x), .. } => facts.board.is(y, x, Owner::Me),
+            Objective::Encircle { ref ring, .. } => ring.is_empty(),
+            Objective::Fill | Objective::Dump => false,
+        }
+    }
+
+    /// Bring a kept objective up to date with the board: a ring drops the
+    /// cells that have been taken since.
+    pub fn refresh(&mut self, facts: &Facts) {
+        if let Objective::Encircle { ring, .. } = self {
+            ring.retain(|&(y, x)| facts.board.is(y, x, Owner::Empty));
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Objective::Seal { at, cut } => format!("seal corridor at {:?}, cutting off {} cells", at, cut),
+            Objective::Expand { toward } => format!("expand toward {:?}", toward),
+            Objective::Escape { toward, area } => format!("escape toward {:?}, into {} cells", toward, area),
+            Objective::Encircle { ring, size, radius } => {
+                format!("encircle them {} steps out, {} of {} ring cells open", radius, ring.len(), size)
+            }
+            Objective::Fill => "fill what's left".to_string(),
+            Objective::Wall => "wall off their rush".to_string(),
+            Objective::Rush => "rush along the corridor".to_string(),
+            Objective::Dump => "dump the piece".to_string(),
+        }
+    }
+
+    /// The strategic layer: one objective for this turn, in order of
+    /// urgency.
+    pub fn choose(facts: &Facts) -> Objective {
+        if !facts.contested {
+            return Objective::Fill;
+        }
+        if facts.rushed {
+            return Objective::Wall;
+        }
+        if facts.rushing {
+            return Objective::Rush;
+        }
+
+        let board = facts.board;
+        let mine = board.distance_field(Owner::Me);
+        let in_reach = |y: usize, x: usize| mine.get(y, x).is_some_and(|d| d <= facts.reach);
+        let seal = facts
+            .chokepoints
+            .iter()
+            .filter(|&((y, x), cut)| cut >= MIN_SEAL && in_reach(y, x))
+            .max_by_key(|&((y, x), cut)| (cut, std::cmp::Reverse((y, x))));
+        if let Some((at, cut)) = seal {
+            return Objective::Seal { at, cut };
+        }
+        if facts.contact {
+            if let Some((toward, area)) = escape(facts, &mine) {
+                return Objective::Escape { toward, area };
+            }
+        }
+        if facts.contact {
+            if let Some((radius, ring)) = ring(facts) {
+                return Objective::Encircle { size: ring.len(), ring, radius };
+            }
+        }
+
+        // The cells we win the race to by the least, nearest first
+        let toward = mine
+            .iter()
+            .filter(|&((y, x), d)| d != usize::MAX && d > 0 && board.is(y, x, Owner::Empty))
+            .filter_map(|((y, x), d)| {
+                let theirs = facts.enemy_distance.get(y, x)?;
+                (theirs != usize::MAX && theirs >= d).then(|| ((theirs - d, d), (y, x)))
+            })
+            .min()
+            .map(|(_, at)| at);
+        match toward {
+            Some(toward) => Objective::Expand { toward },
+            None => Objective::Fill,
+        }
+    }
+
+    /// Whether `image` leaves what this objective heads for where it is,
+    /// so a placement and its image serve it alike. The wall and the
+    /// corridor are the caller's to check.
+    pub fn fixed_under(&self, image: impl Fn((usize, usize)) -> (usize, usize)) -> bool {
+        match self {
+            Objective::Seal { at, .. } => image(*at) == *at,
+            Objective::Expand { toward } | Objective::Escape { toward, .. } => image(*toward) == *toward,
+            Objective::Encircle { ring, .. } => ring.iter().all(|&at| ring.contains(&image(at))),
+            Objective::Fill | Objective::Wall | Objective::Rush | Objective::Dump => true,
+        }
+    }
+
+    /// The tactical layer's measure: how well covering `cells` serves this
+    /// objective, from 0 to 100.
+    pub fn service(&self, cells: &[(usize, usize)], wall: Option<&Grid<bool>>, corridor: Option<&Grid<usize>>) -> i64 {
+        let share = |hits: usize| (100 * hits / cells.len().max(1)) as i64;
+        let near = |(ty, tx): (usize, usize)| {
+            let d = cells.iter().map(|&(y, x)| y.abs_diff(ty) + x.abs_diff(tx)).min().unwrap_or(usize::MAX);
+            100 / d.saturating_add(1).min(101) as i64
+        };
+        match self {
+            Objective::Wall => share(wall.map_or(0, |wall| {
+                cells.iter().filter(|&&(y, x)| wall.get(y, x) == Some(true)).count()
+            })),
+            Objective::Rush => share(corridor.map_or(0, |near| {
+                cells.iter().filter(|&&(y, x)| near.get(y, x) == Some(0)).count()
+            })),
+            Objective::Seal { at, .. } => near(*at),
+            Objective::Expand { toward } | Objective::Escape { toward, .. } => near(*toward),
+            // Toward the nearest open ring cell, all the way when it takes one
+            Objective::Encircle { ring, .. } => ring.iter().map(|&at| near(at)).max().unwrap_or(0),
+            Objective::Fill | Objective::Dump => 0,
+        }
+    }
+}
+
+/// The ring to encircle them with, and how far out it is: the empty cells
+/// some number of steps from their nearest cell. The tightest one in
+/// `RING_RADII` that's long enough and that we win the race to most of.
+fn ring(facts: &Facts) -> Option<(usize, Vec<(usize, usize)>)> {
+    let dead = facts.board.dead_zones();
+    let mine = facts.board.distance_field(Owner::Me);
+    let mut rings: Vec<Vec<(usize, usize)>> = vec![Vec::new(); RING_RADII.end() + 1];
+    for ((y, x), d) in facts.enemy_distance.iter() {
+        if RING_RADII.contains(&d) && facts.board.is(y, x, Owner::Empty) && dead.get(y, x) == Some(false) {
+            if let Some(ring) = rings.get_mut(d) {
+                ring.push((y, x));
+            }
+        }
+    }
+    rings
+        .into_iter()
+        .enumerate()
+        .skip(*RING_RADII.start())
+        .find(|(_, ring)| ring.len() >= MIN_RING && 2 * ring_lead(ring, &mine, facts) >= ring.len())
+}
+
+/// Ring cells we reach no later than they do.
+fn ring_lead(ring: &[(usize, usize)], mine: &Grid<usize>, facts: &Facts) -> usize {
+    ring.iter()
+        .filter(|&&(y, x)| {
+            let d = mine.get(y, x).unwrap_or(usize::MAX);
+            d != usize::MAX && d <= facts.enemy_distance.get(y, x).unwrap_or(usize::MAX)
+        })
+        .count()
+}
+
+/// Where to break out to once they're walling us in, meaning they reach
+/// more of the empty cells first than we do: our nearest cell of the
+/// largest region of empty cells we still reach no later than them, and
+/// how big that is.
+fn escape(facts: &Facts, mine: &Grid<usize>) -> Option<((usize, usize), usize)> {
+    let board = facts.board;
+    let ours = |y: usize, x: usize| {
+        let d = mine.get(y, x).unwrap_or(usize::MAX);
+        board.is(y, x, Owner::Empty) && d != usize::MAX && d <= facts.enemy_distance.get(y, x).unwrap_or(usize::MAX)
+    };
+    let (mut won, mut lost) = (0, 0);
+    for ((y, x), d) in facts.enemy_distance.iter() {
+        if ours(y, x) {
+            won += 1;
+        } else if board.is(y, x, Owner::Empty) && d != usize::MAX {
+            lost += 1;
+        }
+    }
+    if won >= lost {
+        return None;
+    }
+
+    let mut seen = Grid::new(board.rows(), board.cols(), false);
+    let mut best: Option<(usize, (usize, usize))> = None;
+    for ((y, x), _) in board.iter() {
+        if !ours(y, x) || seen.get(y, x) == Some(true) {
+            continue;
+        }
+        seen.set(y, x, true);
+        let mut region = vec![(y, x)];
+        let mut i = 0;
+        while let Some(&(ry, rx)) = region.get(i) {
+            i += 1;
+            for (ny, nx) in board.neighbors(ry, rx) {
+                if ours(ny, nx) && seen.get(ny, nx) == Some(false) {
+                    seen.set(ny, nx, true);
+                    region.push((ny, nx));
+                }
+            }
+        }
+        let nearest = region.iter().copied().min_by_key(|&(ry, rx)| (mine.get(ry, rx), (ry, rx)));
+        if let Some(nearest) = nearest.filter(|_| best.is_none_or(|(size, _)| region.len() > size)) {
+            best = Some((region.len(), nearest));
+        }
+    }
+    best.filter(|&(size, _)| size >= MIN_ESCAPE).map(|(size, at)| (at, size))
+}
+
+/// Why an objective stopped being pursued.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Ending {
+    Completed,
+    /// Its precondition stopped holding.
+    Lost,
+    /// Unserved for `STALL_TURNS` turns.
+    Stalled,
+    /// Something more urgent came up.
+    Preempted,
+    /// Still being pursued when the game ended.
+    Open,
+}
+
+impl Ending {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Ending::Completed => "completed",
+            Ending::Lost => "lost",
+            Ending::Stalled => "stalled",
+            Ending::Preempted => "pre-empted",
+            Ending::Open => "open",
+        }
+    }
+}
+
+/// The objective being pursued and how it's going.
+#[derive(Clone, Debug)]
+pub struct Pursuit {
+    pub objective: Objective,
+    /// Turn it was adopted.
+    pub since: usize,
+    /// Turns a placement served it.
+    pub served: usize,
+    /// Turns in a row, up to now, nothing did.
+    pub stalled: usize,
+}
+
+/// The strategic layer's memory across turns: the current objective and
+/// how every earlier one ended this game.
+#[derive(Default)]
+pub struct Plan {
+    current: Option<Pursuit>,
+    /// Objective name, turns pursued and how it ended, oldest first.
+    pub history: Vec<(&'static str, usize, Ending)>,
+}
+
+impl Plan {
+    pub fn current(&self) -> Option<&Pursuit> {
+        self.current.as_ref()
+    }
+
+    /// This turn's objective: the current one while it lasts, else a fresh
+    /// choice.
+    pub fn next(&mut self, facts: &Facts, turn: usize) -> Objective {
+        let fresh = Objective::choose(facts);
+        if let Some(pursuit) = &mut self.current {
+            pursuit.objective.refresh(facts);
+            let objective = &pursuit.objective;
+            let ending = if objective.done(facts) {
+                Ending::Completed
+            } else if !objective.holds(facts) {
+                Ending::Lost
+            } else if pursuit.stalled >= STALL_TURNS {
+                Ending::Stalled
+            } else if fresh.urgency() > objective.urgency() {
+                Ending::Preempted
+            } else {
+                return objective.clone();
+            };
+            self.close(ending, turn);
+        }
+        self.current = Some(Pursuit {
+            objective: fresh.clone(),
+            since: turn,
+            served: 0,
+            stalled: 0,
+        });
+        fresh
+    }
+
+    /// Record how well this turn's move served the current objective.
+    /// Filling has no measure, so it never stalls.
+    pub fn served(&mut self, served: i64) {
+        if let Some(pursuit) = &mut self.current {
+            if pursuit.objective == Objective::Fill {
+                return;
+            }
+            if served > 0 {
+                pursuit.served += 1;
+                pursuit.stalled = 0;
+            } else {
+                pursuit.stalled += 1;
+            }
+        }
+    }
+
+    fn close(&mut self, ending: Ending, turn: usize) {
+        if let Some(pursuit) = self.current.take() {
+            let turns = turn - pursuit.since;
+            debug!("[DEBUG] Objective {} {} after {} turns", pursuit.objective.describe(), ending.name(), turns);
+            self.history.push((pursuit.objective.name(), turns, ending));
+        }
+    }
+
+    /// How each kind of objective fared this game, e.g.
+    /// "seal: 2 completed, 1 lost; fill: 1 open". The current one counts
+    /// as open.
+    pub fn summary(&self, turn: usize) -> String {
+        let open = self
+            .current
+            .as_ref()
+            .map(|pursuit| (pursuit.objective.name(), turn + 1 - pursuit.since, Ending::Open));
+        let mut tally: Vec<(&str, Vec<(Ending, usize)>)> = Vec::new();
+        for (name, _, ending) in self.history.iter().copied().chain(open) {
+            let Some((_, counts)) = tally.iter_mut().find(|(n, _)| *n == name) else {
+                tally.push((name, vec![(ending, 1)]));
+                continue;
+            };
+            match counts.iter_mut().find(|(e, _)| *e == ending) {
+                Some((_, n)) => *n += 1,
+                None => counts.push((ending, 1)),
+            }
+        }
+        if tally.is_empty() {
+            return "none".to_string();
+        }
+        tally
+            .iter()
+            .map(|(name, counts)| {
+                let counts: Vec<String> = counts.iter().map(|(e, n)| format!("{} {}", n, e.name())).collect();
+                format!("{}: {}", name, counts.join(", "))
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
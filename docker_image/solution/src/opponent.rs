@@ -0,0 +1,237 @@
+// src/opponent.rs
+// What the opponent tends to do, learned from their placements. The engine
+// only shows boards, so each turn's board is diffed against the last one:
+// the new opponent cells are their move.
+//
+// Two tendencies are tracked as running averages: the direction their
+// placements grow in, and how often they play into contact with us. The
+// heuristic uses them to lead its target: against an opponent that spreads
+// away from us it aims where they're heading rather than where they are.
+//
+// A third, how fast their nearest cell closes on our start, tells a rush:
+// growth pointed at us that eats the gap faster than pieces usually do.
+//
+// After `FINGERPRINT_MOVES` of their moves the model settles what kind of
+// player they are, once and for all: a rusher if they were rushing us on
+// most boards and closed most of the gap, an edge-expander if a good share
+// of their cells went down along the board's edges, a turtler if they
+// barely closed the gap and hardly came into contact. The heuristic plays a
+// counter to each (see `Game::turn_weights`).
+
+use crate::board::{Board, Owner};
+
+/// Weight of the newest placement in the running averages.
+const SMOOTHING: f64 = 0.3;
+/// Cells to lead the target by, against an opponent that never engages.
+const MAX_LEAD: f64 = 4.0;
+/// Placements seen before the model is trusted.
+const MIN_MOVES: usize = 2;
+/// A rush grows within this cosine of straight at our start...
+const RUSH_ALIGNMENT: f64 = 0.8;
+/// ...and closes the gap by at least this many cells per move.
+const RUSH_CLOSING: f64 = 1.5;
+/// Their moves seen before they're fingerprinted.
+pub const FINGERPRINT_MOVES: usize = 10;
+/// A rusher was rushing on at least half the boards, and closed at least
+/// this share of the gap to our start.
+const RUSH_CLOSED: f64 = 0.6;
+/// An edge-expander puts at least this share of its cells on the edge.
+const EDGE_SHARE: f64 = 0.25;
+/// A turtler closes less than this share of the gap to our start...
+const TURTLE_CLOSED: f64 = 0.25;
+/// ...and puts less than this share of its cells into contact.
+const TURTLE_TOUCHING: f64 = 0.05;
+
+/// What kind of player the opponent is, from their first
+/// `FINGERPRINT_MOVES` moves.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Fingerprint {
+    /// Came straight at our start.
+    Rusher,
+    /// Grew along the board's edges.
+    EdgeExpander,
+    /// Stayed home.
+    Turtler,
+}
+
+impl Fingerprint {
+    pub fn name(self) -> &'static str {
+        match self {
+            Fingerprint::Rusher => "rusher",
+            Fingerprint::EdgeExpander => "edge-expander",
+            Fingerprint::Turtler => "turtler",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct OpponentModel {
+    previous: Option<Board>,
+    /// Average step from their old centroid to their new cells, (dy, dx).
+    drift: (f64, f64),
+    /// Average share of their new cells touching ours, 0 to 1.
+    aggression: f64,
+    moves: usize,
+    /// Their new cells over the whole game, and how many touched ours or
+    /// the board's edge.
+    placed: usize,
+    touching: usize,
+    edge: usize,
+    /// Our centroid on the first board, their centroid on the last one.
+    home: Option<(f64, f64)>,
+    centroid: Option<(f64, f64)>,
+    /// Steps from our start to their nearest cell, on the first board and
+    /// the last, and its average drop.
+    first_gap: Option<f64>,
+    gap: Option<f64>,
+    closing: f64,
+    /// Boards on which they were rushing us, and what they were taken for
+    /// once `FINGERPRINT_MOVES` moves were in. `None` inside is a player
+    /// that fits no profile.
+    rushes: usize,
+    fingerprint: Option<Option<Fingerprint>>,
+}
+
+impl OpponentModel {
+    /// Learn from whatever the opponent placed since the last board.
+    pub fn observe(&mut self, board: &Board) {
+        if self.home.is_none() {
+            self.home = centroid(&board.coords(Owner::Me));
+        }
+        let theirs = board.coords(Owner::Opponent);
+        self.centroid = centroid(&theirs);
+        let gap = self.home.and_then(|(hy, hx)| {
+            theirs
+                .iter()
+                .map(|&(y, x)| (y as f64 - hy).abs() + (x as f64 - hx).abs())
+                .reduce(f64::min)
+        });
+        if let (Some(before), Some(now)) = (self.gap, gap) {
+            self.closing += SMOOTHING * (before - now - self.closing);
+        }
+        self.gap = gap;
+        self.first_gap = self.first_gap.or(gap);
+
+        if let Some(previous) = self.previous.replace(board.clone()) {
+            let placed: Vec<(usize, usize)> = board
+                .changes(&previous)
+                .filter(|&(_, _, now)| now == Owner::Opponent)
+                .map(|(pos, _, _)| pos)
+                .collect();
+            if let (Some(from), Some(to)) = (centroid(&previous.coords(Owner::Opponent)), centroid(&placed)) {
+                self.learn(board, &placed, (to.0 - from.0, to.1 - from.1));
+            }
+        }
+
+        self.rushes += self.rushing().is_some() as usize;
+        if self.moves == FINGERPRINT_MOVES && self.fingerprint.is_none() {
+            self.fingerprint = Some(self.classify());
+        }
+    }
+
+    fn classify(&self) -> Option<Fingerprint> {
+        let placed = self.placed.max(1) as f64;
+        let closed = match (self.first_gap, self.gap) {
+            (Some(first), Some(now)) if first > 0.0 => (first - now) / first,
+            _ => 0.0,
+        };
+        if self.rushes * 2 >= self.moves && closed >= RUSH_CLOSED {
+            Some(Fingerprint::Rusher)
+        } else if self.edge as f64 / placed >= EDGE_SHARE {
+            Some(Fingerprint::EdgeExpander)
+        } else if closed < TURTLE_CLOSED && (self.touching as f64 / placed) < TURTLE_TOUCHING {
+            Some(Fingerprint::Turtler)
+        } else {
+            None
+        }
+    }
+
+    fn learn(&mut self, board: &Board, placed: &[(usize, usize)], step: (f64, f64)) {
+        let touching = placed
+            .iter()
+            .filter(|&&(y, x)| board.neighbors(y, x).any(|(ny, nx)| board.is(ny, nx, Owner::Me)))
+            .count();
+        let aggression = touching as f64 / placed.len() as f64;
+        let edge = placed
+            .iter()
+            .filter(|&&(y, x)| y == 0 || x == 0 || y + 1 == board.rows() || x + 1 == board.cols())
+            .count();
+
+        self.drift.0 += SMOOTHING * (step.0 - self.drift.0);
+        self.drift.1 += SMOOTHING * (step.1 - self.drift.1);
+        self.aggression += SMOOTHING * (aggression - self.aggression);
+        self.moves += 1;
+        self.placed += placed.len();
+        self.touching += touching;
+        self.edge += edge;
+    }
+
+    /// Recent aggression and drift direction, once enough moves were seen.
+    fn recent(&self) -> Option<(f64, (f64, f64))> {
+        let length = (self.drift.0 * self.drift.0 + self.drift.1 * self.drift.1).sqrt();
+        if self.moves < MIN_MOVES || length < f64::EPSILON {
+            return None;
+        }
+        Some((self.aggression, (self.drift.0 / length, self.drift.1 / length)))
+    }
+
+    /// Their style over the game so far: the share of all their cells
+    /// placed into contact, and their recent drift direction.
+    pub fn style(&self) -> Option<(f64, (f64, f64))> {
+        let (_, drift) = self.recent()?;
+        Some((self.touching as f64 / self.placed.max(1) as f64, drift))
+    }
+
+    /// If they're rushing our start, the unit direction they're coming
+    /// from, (dy, dx) from our start toward them.
+    pub fn rushing(&self) -> Option<(f64, f64)> {
+        let (_, (uy, ux)) = self.recent()?;
+        let (dy, dx) = self.approach()?;
+        // Their drift points back along that line, at us
+        let aligned = -(uy * dy + ux * dx) >= RUSH_ALIGNMENT;
+        (aligned && self.closing >= RUSH_CLOSING).then_some((dy, dx))
+    }
+
+    /// What kind of player they are, once `FINGERPRINT_MOVES` of their
+    /// moves are in and they fit a profile.
+    pub fn fingerprint(&self) -> Option<Fingerprint> {
+        self.fingerprint.flatten()
+    }
+
+    /// The unit direction from our start toward their cells, (dy, dx).
+    pub fn approach(&self) -> Option<(f64, f64)> {
+        let ((hy, hx), (cy, cx)) = (self.home?, self.centroid?);
+        let (dy, dx) = (cy - hy, cx - hx);
+        let length = (dy * dy + dx * dx).sqrt();
+        (length >= f64::EPSILON).then(|| (dy / length, dx / length))
+    }
+
+    /// Our centroid on the first board we saw.
+    pub fn home(&self) -> Option<(f64, f64)> {
+        self.home
+    }
+
+    /// `target` moved ahead along their drift, less so the more they
+    /// engage. Unchanged until the model has seen enough moves.
+    pub fn lead(&self, board: &Board, target: (usize, usize)) -> (usize, usize) {
+        let Some((aggression, (uy, ux))) = self.recent() else {
+            return target;
+        };
+
+        let lead = MAX_LEAD * (1.0 - aggression);
+        let dy = (uy * lead).round() as isize;
+        let dx = (ux * lead).round() as isize;
+        let y = (target.0 as isize + dy).clamp(0, board.rows() as isize - 1);
+        let x = (target.1 as isize + dx).clamp(0, board.cols() as isize - 1);
+        (y as usize, x as usize)
+    }
+}
+
+fn centroid(cells: &[(usize, usize)]) -> Option<(f64, f64)> {
+    if cells.is_empty() {
+        return None;
+    }
+    let n = cells.len() as f64;
+    let (sy, sx) = cells.iter().fold((0, 0), |(sy, sx), &(y, x)| (sy + y, sx + x));
+    Some((sy as f64 / n, sx as f64 / n))
+}
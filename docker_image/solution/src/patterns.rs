@@ -0,0 +1,272 @@
+// src/patterns.rs
+// Tactical shapes along the contact line. Global features (distance,
+// territory, chokepoints) don't see local tricks like a pincer or a one-cell
+// gap left in our own wall, so each candidate placement is also matched
+// against a small library of 5x5 neighbourhoods.
+//
+// Patterns are data: patterns/contact.pat is compiled in, and FILLER_PATTERNS
+// can point at another file in the same format (documented at the top of
+// that file). Under `filler play` the file is re-read whenever it changes,
+// so patterns can be authored while replaying games.
+//
+// Symbols are relative to the player to move, so one library serves both
+// sides.
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::board::{Board, Owner};
+
+const SIZE: usize = 5;
+const CENTER: usize = SIZE / 2;
+const SYMBOLS: &[u8] = b"XO.xo*#?";
+
+const BUILTIN: &str = include_str!("../patterns/contact.pat");
+
+type Shape = [[u8; SIZE]; SIZE];
+
+pub struct Pattern {
+    pub name: String,
+    /// Added to the score once per piece cell the pattern matches on.
+    pub bonus: i64,
+    /// Distinct rotations and reflections of the shape.
+    variants: Vec<Shape>,
+}
+
+impl Pattern {
+    fn new(name: String, bonus: i64, shape: Shape) -> Self {
+        let mut variants: Vec<Shape> = Vec::new();
+        let mut current = shape;
+        for _ in 0..4 {
+            for candidate in [current, mirror(&current)] {
+                if !variants.contains(&candidate) {
+                    variants.push(candidate);
+                }
+            }
+            current = rotate(&current);
+        }
+
+        Pattern { name, bonus, variants }
+    }
+
+    fn matches(&self, cell: &impl Fn(isize, isize) -> Option<Owner>, y: usize, x: usize) -> bool {
+        self.variants.iter().any(|shape| {
+            shape.iter().enumerate().all(|(py, row)| {
+                row.iter().enumerate().all(|(px, &symbol)| {
+                    let dy = py as isize - CENTER as isize;
+                    let dx = px as isize - CENTER as isize;
+                    fits(symbol, cell(y as isize + dy, x as isize + dx))
+                })
+            })
+        })
+    }
+}
+
+/// The patterns in use and, when they came from a file, where to reload
+/// them from.
+pub struct PatternLibrary {
+    pub patterns: Vec<Pattern>,
+    source: Option<(PathBuf, Option<SystemTime>)>,
+}
+
+impl PatternLibrary {
+    /// The compiled-in library.
+    pub fn builtin() -> Self {
+        PatternLibrary {
+            patterns: parse(BUILTIN).unwrap_or_default(),
+            source: None,
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let mut library = PatternLibrary {
+            patterns: Vec::new(),
+            source: Some((PathBuf::from(path), None)),
+        };
+        library.reload()?;
+        Ok(library)
+    }
+
+    /// Re-read the source file if it changed since the last read. A file
+    /// that doesn't parse is reported and the previous patterns are kept.
+    pub fn refresh(&mut self) {
+        let Some((path, seen)) = &self.source else {
+            return;
+        };
+        let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+        if modified.is_some() && modified != *seen {
+            match self.reload() {
+                Ok(()) => eprintln!("reloaded {} patterns", self.patterns.len()),
+                Err(e) => eprintln!("keeping previous patterns: {}", e),
+            }
+        }
+    }
+
+    fn reload(&mut self) -> Result<(), String> {
+        let Some((path, seen)) = &mut self.source else {
+            return Ok(());
+        };
+        let shown = path.display().to_string();
+        *seen = fs::metadata(&*path).and_then(|m| m.modified()).ok();
+        let text = fs::read_to_string(&*path).map_err(|e| format!("{}: {}", shown, e))?;
+        self.patterns = parse(&text).map_err(|e| format!("{}: {}", shown, e))?;
+        Ok(())
+    }
+}
+
+/// Parse a pattern file: a "<name> <bonus>" line directly followed by five
+/// rows of five symbols. Blank lines and '#' comments may appear between
+/// patterns, not inside them ('#' is also a symbol).
+pub fn parse(text: &str) -> Result<Vec<Pattern>, String> {
+    let mut lines = text.lines().enumerate().map(|(n, l)| (n + 1, l.trim()));
+
+    let mut patterns = Vec::new();
+    while let Some((n, header)) = lines.next() {
+        if header.is_empty() || header.starts_with('#') {
+            continue;
+        }
+        let (name, bonus) = header
+            .rsplit_once(char::is_whitespace)
+            .and_then(|(name, bonus)| Some((name.trim(), bonus.parse::<i64>().ok()?)))
+            .ok_or_else(|| format!("line {}: expected `<name> <bonus>`", n))?;
+
+        let mut shape = [[b'?'; SIZE]; SIZE];
+        for row in shape.iter_mut() {
+            let (n, line) = lines
+                .next()
+                .ok_or_else(|| format!("pattern `{}` has fewer than {} rows", name, SIZE))?;
+            if line.len() != SIZE || !line.bytes().all(|c| SYMBOLS.contains(&c)) {
+                return Err(format!("line {}: expected {} of `XO.xo*#?`", n, SIZE));
+            }
+            row.copy_from_slice(line.as_bytes());
+        }
+        if shape[CENTER][CENTER] != b'X' {
+            return Err(format!("pattern `{}`: the centre must be X", name));
+        }
+
+        patterns.push(Pattern::new(name.to_string(), bonus, shape));
+    }
+    Ok(patterns)
+}
+
+/// Total bonus of every pattern matching around the cells `covered` claims,
+/// sorted as a placement's cells are.
+pub fn score(patterns: &[Pattern], board: &Board, covered: &[(usize, usize)]) -> i64 {
+    debug_assert!(covered.is_sorted());
+    let cell = |y: isize, x: isize| -> Option<Owner> {
+        let (y, x) = (usize::try_from(y).ok()?, usize::try_from(x).ok()?);
+        if covered.binary_search(&(y, x)).is_ok() {
+            Some(Owner::Me)
+        } else {
+            board.get(y, x)
+        }
+    };
+
+    let mut total = 0;
+    for &(y, x) in covered {
+        for pattern in patterns {
+            if pattern.matches(&cell, y, x) {
+                total += pattern.bonus;
+            }
+        }
+    }
+    total
+}
+
+/// The least and most `score` can add per piece cell: every pattern that
+/// takes away matching, or every one that adds.
+pub fn bonus_range(patterns: &[Pattern]) -> (i64, i64) {
+    patterns.iter().fold((0, 0), |(low, high), pattern| {
+        (low + pattern.bonus.min(0), high + pattern.bonus.max(0))
+    })
+}
+
+fn fits(symbol: u8, cell: Option<Owner>) -> bool {
+    match symbol {
+        b'X' => cell == Some(Owner::Me),
+        b'O' => cell == Some(Owner::Opponent),
+        b'.' => cell == Some(Owner::Empty),
+        b'x' => matches!(cell, Some(Owner::Me | Owner::Empty)),
+        b'o' => matches!(cell, Some(Owner::Opponent | Owner::Empty)),
+        b'*' => cell.is_some(),
+        b'#' => cell.is_none(),
+        _ => true,
+    }
+}
+
+fn rotate(shape: &Shape) -> Shape {
+    let mut out = [[b'?'; SIZE]; SIZE];
+    for (y, row) in shape.iter().enumerate() {
+        for (x, &symbol) in row.iter().enumerate() {
+            if let Some(cell) = out.get_mut(x).and_then(|row| row.get_mut(SIZE - 1 - y)) {
+                *cell = symbol;
+            }
+        }
+    }
+    out
+}
+
+fn mirror(shape: &Shape) -> Shape {
+    let mut out = *shape;
+    for row in out.iter_mut() {
+        row.reverse();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One pattern: `symbol` right of the centre, anything elsewhere.
+    fn single(symbol: char) -> Pattern {
+        let text = format!("one 10\n?????\n?????\n??X{}?\n?????\n?????\n", symbol);
+        let mut patterns = parse(&text).expect("pattern parses");
+        assert_eq!(patterns.len(), 1);
+        patterns.remove(0)
+    }
+
+    /// Which of ours, theirs, empty, fogged and off the board `symbol`
+    /// accepts next to a cell of ours.
+    fn accepts(symbol: char) -> [bool; 5] {
+        let pattern = single(symbol);
+        [Some(Owner::Me), Some(Owner::Opponent), Some(Owner::Empty), Some(Owner::Unknown), None].map(|around| {
+            let cell = |y: isize, x: isize| if (y, x) == (2, 2) { Some(Owner::Me) } else { around };
+            pattern.matches(&cell, 2, 2)
+        })
+    }
+
+    #[test]
+    fn each_symbol_matches_its_cells() {
+        assert_eq!(accepts('X'), [true, false, false, false, false]);
+        assert_eq!(accepts('O'), [false, true, false, false, false]);
+        assert_eq!(accepts('.'), [false, false, true, false, false]);
+        assert_eq!(accepts('x'), [true, false, true, false, false]);
+        assert_eq!(accepts('o'), [false, true, true, false, false]);
+        assert_eq!(accepts('*'), [true, true, true, true, false]);
+        assert_eq!(accepts('#'), [false, false, false, false, true]);
+        assert_eq!(accepts('?'), [true, true, true, true, true]);
+    }
+
+    #[test]
+    fn comments_and_blank_lines_between_patterns() {
+        let text = "# a library\n\nfirst 5\n..o..\n.xXx.\n??X??\n*****\n#####\n\n# next\nsecond -3\n?????\n?????\n??X??\n?????\n?????\n";
+        let patterns = parse(text).expect("patterns parse");
+        let named: Vec<_> = patterns.iter().map(|p| (p.name.as_str(), p.bonus)).collect();
+        assert_eq!(named, [("first", 5), ("second", -3)]);
+        assert_eq!(bonus_range(&patterns), (-3, 5));
+    }
+
+    #[test]
+    fn malformed_patterns_name_the_line() {
+        let rows = "?????\n?????\n??X??\n?????\n?????\n";
+        let error = |text: &str| parse(text).err().expect("parse fails");
+        assert_eq!(error(&format!("nameless\n{}", rows)), "line 1: expected `<name> <bonus>`");
+        assert_eq!(error(&format!("\nbonus x\n{}", rows)), "line 2: expected `<name> <bonus>`");
+        assert_eq!(error("short 1\n?????\n??X??\n"), "pattern `short` has fewer than 5 rows");
+        assert_eq!(error("wide 1\n?????\n??????\n??X??\n?????\n?????\n"), "line 3: expected 5 of `XO.xo*#?`");
+        assert_eq!(error("odd 1\n?????\n?????\n??X?Z\n?????\n?????\n"), "line 4: expected 5 of `XO.xo*#?`");
+        assert_eq!(error("centre 1\n?????\n?????\n??x??\n?????\n?????\n"), "pattern `centre`: the centre must be X");
+    }
+}
@@ -1,91 +1,302 @@
-// src/piece.rs
-
-pub struct Piece {
-    pub width: usize,
-    pub height: usize,
-    /// Coordinates of filled cells relative to the top-left of the piece.
-    pub cells: Vec<(usize, usize)>,
-}
-
-impl Piece {
-    /// Build a piece from the "Piece" block lines.
-    ///
-    /// Example:
-    /// Piece 3 5:
-    /// ..*..
-    /// .***.
-    /// ..*..
-    ///
-    /// We treat '*', 'O', 'o' as filled.
-    /// IMPORTANT: format is "Piece <width> <height>:"
-    pub fn from_piece_lines(lines: &[String]) -> Option<Self> {
-        if lines.is_empty() {
-            return None;
-        }
-
-        // Find the header line
-        let header_index = lines
-            .iter()
-            .position(|l| l.trim_start().starts_with("Piece"))?;
-
-        let header = lines[header_index].trim();
-        let parts: Vec<&str> = header.split_whitespace().collect();
-
-        if parts.len() < 3 {
-            return None;
-        }
-
-        // Correct interpretation:
-        // "Piece <width> <height>:"
-        let expected_width: usize = parts[1].parse().ok()?;
-        let expected_height: usize = parts[2]
-            .trim_end_matches(':')
-            .parse()
-            .ok()?;
-
-        // Collect piece pattern lines after the header
-        let mut pattern: Vec<String> = Vec::new();
-        for line in lines.iter().skip(header_index + 1) {
-            let t = line.trim_end().to_string();
-            if t.is_empty() {
-                continue;
-            }
-            pattern.push(t);
-            if pattern.len() == expected_height {
-                break;
-            }
-        }
-
-        if pattern.is_empty() {
-            return None;
-        }
-
-        let height = pattern.len();
-        let mut width = expected_width;
-        let mut filled_cells: Vec<(usize, usize)> = Vec::new();
-
-        for (y, row_str) in pattern.iter().enumerate() {
-            let row_len = row_str.chars().count();
-            if row_len > width {
-                width = row_len;
-            }
-
-            for (x, ch) in row_str.chars().enumerate() {
-                if ch == '*' || ch == 'O' || ch == 'o' {
-                    filled_cells.push((y, x));
-                }
-            }
-        }
-
-        if filled_cells.is_empty() {
-            // No useful piece
-            return None;
-        }
-
-        Some(Piece {
-            width,
-            height,
-            cells: filled_cells,
-        })
-    }
-}
+// src/piece.rs
+
+/// What a piece looks like once its empty margin is trimmed, for the
+/// scorer's shape bonus (see `game::shape_fit`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shape {
+    /// Two or more cells in one row or column: a ready-made wall.
+    Line,
+    /// Two straight arms meeting at a corner.
+    L,
+    /// A bar with a stem from somewhere other than its ends.
+    T,
+    /// Two offset runs side by side, the S and Z tetromino stretched.
+    SZ,
+    /// Anything else in one piece, a single cell included.
+    Blob,
+    /// Cells not all connected, edge to edge.
+    Scatter,
+}
+
+impl Shape {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Shape::Line => "line",
+            Shape::L => "L",
+            Shape::T => "T",
+            Shape::SZ => "S/Z",
+            Shape::Blob => "blob",
+            Shape::Scatter => "scatter",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Piece {
+    pub width: usize,
+    pub height: usize,
+    /// Coordinates of filled cells relative to the top-left of the piece.
+    pub cells: Vec<(usize, usize)>,
+    /// Empty rows above and empty columns left of the filled cells in the
+    /// block as declared, trimmed off (see `declared`).
+    pub margin: (usize, usize),
+    /// Each row's cells as a bit mask, the leftmost in the lowest bit, for
+    /// `Board::can_place`. Empty for pieces wider than 64 cells.
+    masks: Vec<u64>,
+}
+
+impl Piece {
+    /// Build a piece from the "Piece" block lines.
+    ///
+    /// Example:
+    /// Piece 3 5:
+    /// ..*..
+    /// .***.
+    /// ..*..
+    ///
+    /// We treat '*', 'O', 'o' as filled. The piece is trimmed to its
+    /// filled cells, so the one above is 3x3 with a margin of (0, 1).
+    /// IMPORTANT: format is "Piece <width> <height>:"
+    pub fn from_piece_lines(lines: &[String]) -> Option<Self> {
+        if lines.is_empty() {
+            return None;
+        }
+
+        // Find the header line
+        let (header_index, header) = lines
+            .iter()
+            .enumerate()
+            .find(|(_, l)| l.trim_start().starts_with("Piece"))?;
+        let mut parts = header.split_whitespace();
+
+        // Correct interpretation:
+        // "Piece <width> <height>:", the width only checked since the
+        // empty columns are trimmed anyway
+        parts.nth(1)?.parse::<usize>().ok()?;
+        let expected_height: usize = parts
+            .next()?
+            .trim_end_matches(':')
+            .parse()
+            .ok()?;
+
+        // Collect piece pattern lines after the header
+        let mut pattern: Vec<String> = Vec::new();
+        for line in lines.iter().skip(header_index + 1) {
+            let t = line.trim_end().to_string();
+            if t.is_empty() {
+                continue;
+            }
+            pattern.push(t);
+            if pattern.len() == expected_height {
+                break;
+            }
+        }
+
+        if pattern.is_empty() {
+            return None;
+        }
+
+        let mut filled_cells: Vec<(usize, usize)> = Vec::new();
+
+        for (y, row_str) in pattern.iter().enumerate() {
+            for (x, ch) in row_str.chars().enumerate() {
+                if ch == '*' || ch == 'O' || ch == 'o' {
+                    filled_cells.push((y, x));
+                }
+            }
+        }
+
+        if filled_cells.is_empty() {
+            // No useful piece
+            return None;
+        }
+
+        Some(Piece::trimmed(filled_cells))
+    }
+
+    /// The piece made of `cells`, trimmed to their bounding box. A margin
+    /// of empty rows or columns would only keep the piece off the board's
+    /// edges.
+    pub fn trimmed(cells: Vec<(usize, usize)>) -> Self {
+        let top = cells.iter().map(|&(y, _)| y).min().unwrap_or(0);
+        let left = cells.iter().map(|&(_, x)| x).min().unwrap_or(0);
+        let cells: Vec<(usize, usize)> = cells.into_iter().map(|(y, x)| (y - top, x - left)).collect();
+        let width = cells.iter().map(|&(_, x)| x + 1).max().unwrap_or(0);
+        let height = cells.iter().map(|&(y, _)| y + 1).max().unwrap_or(0);
+        let mut masks = if width <= 64 { vec![0u64; height] } else { Vec::new() };
+        for &(y, x) in &cells {
+            if let Some(mask) = masks.get_mut(y) {
+                *mask |= 1 << x;
+            }
+        }
+        Piece {
+            width,
+            height,
+            cells,
+            margin: (top, left),
+            masks,
+        }
+    }
+
+    /// The row masks, when the piece is narrow enough to have them.
+    pub fn row_masks(&self) -> Option<&[u64]> {
+        (!self.masks.is_empty()).then_some(&self.masks[..])
+    }
+
+    /// A placement's top-left as the engine wants it: the corner of the
+    /// block as declared, margin included. That hangs off the board, above
+    /// or to the left, when the trimmed piece sits at its edge.
+    pub fn declared(&self, (y, x): (usize, usize)) -> (i64, i64) {
+        (y as i64 - self.margin.0 as i64, x as i64 - self.margin.1 as i64)
+    }
+
+    /// Whether the cells form one group, edge to edge.
+    pub fn is_connected(&self) -> bool {
+        connected(&self.cells)
+    }
+
+    pub fn shape(&self) -> Shape {
+        let cells = &self.cells;
+        let (h, w) = (self.height, self.width);
+        let has = |y: usize, x: usize| cells.contains(&(y, x));
+
+        if !self.is_connected() {
+            return Shape::Scatter;
+        }
+        if cells.len() >= 2 && (h == 1 || w == 1) {
+            return Shape::Line;
+        }
+
+        // An L or a T is exactly one full row and one full column, and the
+        // row has to be on an edge for the column to hang off it
+        if w >= 2 && h >= 2 && cells.len() == w + h - 1 {
+            let full_row = |y: usize| (0..w).all(|x| has(y, x));
+            let full_col = |x: usize| (0..h).all(|y| has(y, x));
+            for y in [0, h - 1] {
+                if let Some(x) = (0..w).find(|&x| full_row(y) && full_col(x)) {
+                    return if x == 0 || x == w - 1 { Shape::L } else { Shape::T };
+                }
+            }
+            for x in [0, w - 1] {
+                if (0..h).any(|y| full_col(x) && full_row(y)) {
+                    return Shape::T;
+                }
+            }
+        }
+
+        // Two runs, shifted the same way at both ends
+        let runs = |lines: usize, along: &dyn Fn(usize) -> Vec<usize>| -> bool {
+            if lines != 2 {
+                return false;
+            }
+            let (a, b) = (along(0), along(1));
+            let run = |r: &[usize]| r.windows(2).all(|p| matches!(p, [a, b] if *b == a + 1));
+            let (Some(&a0), Some(&a1), Some(&b0), Some(&b1)) = (a.first(), a.last(), b.first(), b.last()) else {
+                return false;
+            };
+            run(&a) && run(&b) && a0 != b0 && (a0 < b0) == (a1 < b1) && a1 != b1
+        };
+        let row = |y: usize| (0..w).filter(|&x| has(y, x)).collect();
+        let col = |x: usize| (0..h).filter(|&y| has(y, x)).collect();
+        if runs(h, &row) || runs(w, &col) {
+            return Shape::SZ;
+        }
+
+        Shape::Blob
+    }
+}
+
+/// Whether `cells` form one group, edge to edge.
+fn connected(cells: &[(usize, usize)]) -> bool {
+    let Some(&start) = cells.first() else {
+        return true;
+    };
+    let mut reached = vec![start];
+    let mut i = 0;
+    while let Some(&(y, x)) = reached.get(i) {
+        i += 1;
+        for &next in cells {
+            if next.0.abs_diff(y) + next.1.abs_diff(x) == 1 && !reached.contains(&next) {
+                reached.push(next);
+            }
+        }
+    }
+    reached.len() == cells.len()
+}
+
+/// Sizes of the pieces dealt so far. The engine draws both players' pieces
+/// alike, so this is also our best guess at what the opponent gets.
+#[derive(Clone, Default)]
+pub struct PieceStats {
+    /// How many pieces claimed each number of new cells (cells - 1, since
+    /// one cell always lands on our own).
+    claims: Vec<usize>,
+    seen: usize,
+}
+
+impl PieceStats {
+    pub fn record(&mut self, piece: &Piece) {
+        let claim = piece.cells.len().saturating_sub(1);
+        if self.claims.len() <= claim {
+            self.claims.resize(claim + 1, 0);
+        }
+        if let Some(count) = self.claims.get_mut(claim) {
+            *count += 1;
+        }
+        self.seen += 1;
+    }
+
+    /// Roughly how far one piece reaches: the side of a square of the
+    /// average size. 1 before any piece is seen.
+    pub fn typical_reach(&self) -> usize {
+        if self.seen == 0 {
+            return 1;
+        }
+        let cells: usize = self.claims.iter().enumerate().map(|(claim, &n)| (claim + 1) * n).sum();
+        ((cells as f64 / self.seen as f64).sqrt().round() as usize).max(1)
+    }
+
+    /// The fewest new cells any piece seen has claimed. 0 before any is
+    /// seen.
+    pub fn smallest_claim(&self) -> usize {
+        self.claims.iter().position(|&n| n > 0).unwrap_or(0)
+    }
+
+    /// Share of the pieces seen that claim more than `cells` new cells, and
+    /// so could never go into a pocket that small. 0 before any is seen.
+    pub fn share_larger_than(&self, cells: usize) -> f64 {
+        if self.seen == 0 {
+            return 0.0;
+        }
+        let larger: usize = self.claims.iter().skip(cells + 1).sum();
+        larger as f64 / self.seen as f64
+    }
+
+    /// Expected cells a player claims over the next `turns` pieces when
+    /// `area` cells are left for them to take. Each piece is drawn from the
+    /// sizes seen so far, and the claim stops at `area`.
+    pub fn expected_claim(&self, area: usize, turns: usize) -> f64 {
+        if self.seen == 0 || area == 0 {
+            return 0.0;
+        }
+
+        // Distribution of the total claim after each piece, capped at `area`
+        let mut total = vec![0.0; area + 1];
+        if let Some(none) = total.first_mut() {
+            *none = 1.0;
+        }
+        for _ in 0..turns {
+            let mut next = vec![0.0; area + 1];
+            for (sum, &p) in total.iter().enumerate().filter(|&(_, &p)| p > 0.0) {
+                for (claim, &count) in self.claims.iter().enumerate() {
+                    if let Some(next) = next.get_mut((sum + claim).min(area)) {
+                        *next += p * count as f64 / self.seen as f64;
+                    }
+                }
+            }
+            total = next;
+        }
+
+        total.iter().enumerate().map(|(sum, p)| sum as f64 * p).sum()
+    }
+}
@@ -0,0 +1,139 @@
+// src/profiles.rs
+// Weight profiles per kind of map. One set of weights doesn't suit every
+// board: on a small one there's no room to expand into and blocking first
+// wins, on a huge one a straight rush gives away the ground on the way. So
+// a profiles file bundles several named weight sets, each with the board
+// sizes and densities it's for, and the first board we see picks one.
+//
+// The format is documented at the top of profiles/maps.profiles, which is
+// compiled in; FILLER_PROFILES can point at another file or turn them off.
+// A weights file (FILLER_WEIGHTS) pins the weights for every map instead.
+
+use std::fs;
+
+use crate::board::{Board, Owner};
+use crate::weights::{Weights, NEUTRAL_AGGRESSION};
+
+pub const BUNDLED: &str = include_str!("../profiles/maps.profiles");
+
+pub struct Profile {
+    pub name: String,
+    /// Cells on the board, inclusive.
+    cells: (usize, usize),
+    /// Percent of them already taken on the first board, inclusive.
+    taken: (f64, f64),
+    aggression: f32,
+    /// The defaults with the profile's changes, its aggression applied.
+    pub weights: Weights,
+}
+
+impl Profile {
+    fn fits(&self, board: &Board) -> bool {
+        let cells = board.rows() * board.cols();
+        let taken = board
+            .iter()
+            .filter(|&(_, owner)| matches!(owner, Owner::Me | Owner::Opponent))
+            .count();
+        let taken = 100.0 * taken as f64 / cells.max(1) as f64;
+        (self.cells.0..=self.cells.1).contains(&cells) && self.taken.0 <= taken && taken <= self.taken.1
+    }
+
+    /// The maps it's for and its aggression, for `filler strategies`.
+    pub fn describe(&self) -> String {
+        let high = |high: usize| if high == usize::MAX { String::new() } else { high.to_string() };
+        format!(
+            "cells {}-{}, taken {}-{}%, aggression {}",
+            self.cells.0,
+            high(self.cells.1),
+            self.taken.0,
+            self.taken.1,
+            self.aggression
+        )
+    }
+}
+
+#[derive(Default)]
+pub struct Profiles {
+    pub profiles: Vec<Profile>,
+}
+
+impl Profiles {
+    /// The profiles shipped with the bot.
+    pub fn bundled() -> Self {
+        Profiles::parse(BUNDLED).unwrap_or_default()
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        Profiles::parse(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let lines: Vec<&str> = text.lines().map(|l| l.split('#').next().unwrap_or("").trim()).collect();
+        let starts: Vec<usize> = (0..lines.len())
+            .filter(|&n| lines.get(n).is_some_and(|l| l.starts_with("profile ")))
+            .collect();
+        let first = starts.first().copied().unwrap_or(lines.len());
+        if let Some(n) = lines.iter().take(first).position(|l| !l.is_empty()) {
+            return Err(format!("line {}: expected `profile <name>`", n + 1));
+        }
+
+        let mut profiles = Vec::new();
+        for (i, &start) in starts.iter().enumerate() {
+            let end = starts.get(i + 1).copied().unwrap_or(lines.len());
+            let mut profile = Profile {
+                name: lines.get(start).and_then(|l| l.strip_prefix("profile ")).unwrap_or("").trim().to_string(),
+                cells: (0, usize::MAX),
+                taken: (0.0, 100.0),
+                aggression: NEUTRAL_AGGRESSION,
+                weights: Weights::default(),
+            };
+
+            // Selectors up to the first section, weights from there on
+            let body = (start + 1..end)
+                .find(|&n| lines.get(n).is_some_and(|l| l.starts_with('[')))
+                .unwrap_or(end);
+            for (n, line) in lines.iter().enumerate().take(body).skip(start + 1) {
+                let bad = || format!("line {}: bad setting `{}`", n + 1, line);
+                match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+                    [] => {}
+                    ["cells", range] => profile.cells = parse_range(range, usize::MAX).ok_or_else(bad)?,
+                    ["taken", range] => profile.taken = parse_range(range, 100.0).ok_or_else(bad)?,
+                    ["aggression", value] => profile.aggression = value.parse().map_err(|_| bad())?,
+                    _ => return Err(bad()),
+                }
+            }
+            // Blanked out around the weights, so their errors keep our line numbers
+            let weights: Vec<&str> = lines
+                .iter()
+                .enumerate()
+                .map(|(n, &l)| if (body..end).contains(&n) { l } else { "" })
+                .collect();
+            profile.weights = Weights::parse(&weights.join("\n"))?.with_aggression(profile.aggression);
+            profiles.push(profile);
+        }
+
+        Ok(Profiles { profiles })
+    }
+
+    /// Every profile's weights turned by the aggression dial as well.
+    pub fn with_aggression(mut self, aggression: f32) -> Self {
+        for profile in &mut self.profiles {
+            profile.weights = profile.weights.with_aggression(aggression);
+        }
+        self
+    }
+
+    /// The first profile for a map that starts out like `board`.
+    pub fn select(&self, board: &Board) -> Option<&Profile> {
+        self.profiles.iter().find(|profile| profile.fits(board))
+    }
+}
+
+/// `<min>-<max>`, where a missing end is 0 or `max`.
+fn parse_range<T: std::str::FromStr + Default>(text: &str, max: T) -> Option<(T, T)> {
+    let (low, high) = text.split_once('-')?;
+    let low = if low.is_empty() { T::default() } else { low.parse().ok()? };
+    let high = if high.is_empty() { max } else { high.parse().ok()? };
+    Some((low, high))
+}
@@ -0,0 +1,158 @@
+// src/promote.rs
+// `filler promote`: make a strategy the default, but only once it has
+// earned it. The candidate plays the current default and every sparring
+// partner (see `config::SPARRING`) on the standard maps, from both seats,
+// and has to win at least a set share of the games against each: a new
+// strategy that beats the old one but falls for the wall-crawler isn't
+// ready. Only when every opponent is cleared is `default_strategy`
+// rewritten; it's compiled in, so the next build plays the new default.
+//
+// The criteria live here rather than in anyone's head: the maps, the games
+// per map and both thresholds are the constants below, and a run prints
+// every opponent's result against its bar whether it passes or not.
+
+use std::fs;
+use std::path::Path;
+
+use crate::config::{self, Config, SPARRING, STRATEGIES};
+use crate::referee::play_game;
+
+const USAGE: &str = "usage: filler promote <strategy> [<map>...] [--games N] [--seed N] \
+                     [--vs-default RATE] [--vs-sparring RATE] [--dry-run]";
+
+/// The maps a promotion is judged on unless others are given, relative to
+/// where the bot is normally run from.
+const STANDARD_MAPS: &[&str] = &["maps/map00", "maps/map01", "maps/map02"];
+
+/// Seeds per map; each is played from both seats.
+const GAMES: usize = 4;
+
+/// Share of games the candidate has to win against the current default,
+/// and against each sparring partner. A tie counts as half a win.
+const VS_DEFAULT: f64 = 0.55;
+const VS_SPARRING: f64 = 0.75;
+
+pub fn run_cli(args: &[String]) -> Result<(), String> {
+    let mut candidate: Option<&str> = None;
+    let mut map_paths: Vec<&str> = Vec::new();
+    let mut games = GAMES;
+    let mut seed: u64 = 1;
+    let mut vs_default = VS_DEFAULT;
+    let mut vs_sparring = VS_SPARRING;
+    let mut dry_run = false;
+
+    let mut i = 0;
+    while let Some(arg) = args.get(i) {
+        let value = args.get(i + 1);
+        let number = |name: &str| -> Result<f64, String> {
+            value
+                .and_then(|v| v.parse().ok())
+                .ok_or(format!("{} expects a number", name))
+        };
+        match arg.as_str() {
+            "--games" => games = number("--games")?.max(1.0) as usize,
+            "--seed" => seed = number("--seed")? as u64,
+            "--vs-default" => vs_default = number("--vs-default")?,
+            "--vs-sparring" => vs_sparring = number("--vs-sparring")?,
+            "--dry-run" => {
+                dry_run = true;
+                i += 1;
+                continue;
+            }
+            other if other.starts_with("--") => return Err(USAGE.to_string()),
+            other => {
+                match candidate {
+                    None => candidate = Some(other),
+                    Some(_) => map_paths.push(other),
+                }
+                i += 1;
+                continue;
+            }
+        }
+        i += 2;
+    }
+
+    let candidate = candidate.ok_or(USAGE)?;
+    let current = config::default_strategy();
+    if !STRATEGIES.iter().any(|s| s.name == candidate) {
+        return Err(format!("unknown strategy `{}`", candidate));
+    }
+    if SPARRING.contains(&candidate) {
+        return Err(format!("`{}` is a sparring partner, not a contender", candidate));
+    }
+    if candidate == current {
+        return Err(format!("`{}` is already the default", candidate));
+    }
+    if map_paths.is_empty() {
+        map_paths = STANDARD_MAPS.to_vec();
+    }
+    let maps = map_paths
+        .iter()
+        .map(|path| {
+            fs::read_to_string(path)
+                .map(|text| text.lines().map(|l| l.to_string()).collect::<Vec<_>>())
+                .map_err(|e| format!("{}: {}", path, e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    println!(
+        "{} against {} on {} map(s), {} games each",
+        candidate,
+        std::iter::once(current).chain(SPARRING.iter().copied()).collect::<Vec<_>>().join(", "),
+        maps.len(),
+        2 * games * maps.len()
+    );
+    let mut cleared = true;
+    let bars = std::iter::once((current, vs_default)).chain(SPARRING.iter().map(|&name| (name, vs_sparring)));
+    for (opponent, bar) in bars {
+        let rate = win_rate(candidate, opponent, &maps, games, seed)?;
+        let passed = rate >= bar;
+        cleared &= passed;
+        println!(
+            "  vs {:<12} won {:>5.1}%  needs {:>5.1}%  {}",
+            opponent,
+            100.0 * rate,
+            100.0 * bar,
+            if passed { "ok" } else { "FAILED" }
+        );
+    }
+
+    if !cleared {
+        println!("not promoted: {} stays the default", current);
+    } else if dry_run {
+        println!("{} would be promoted (dry run, nothing written)", candidate);
+    } else {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(config::DEFAULT_STRATEGY_FILE);
+        fs::write(&path, format!("{}\n", candidate)).map_err(|e| format!("{}: {}", path.display(), e))?;
+        println!("promoted {} over {}: rebuild to ship it", candidate, current);
+    }
+    Ok(())
+}
+
+/// Share of games `candidate` wins against `opponent`, every map and seed
+/// played from both seats.
+fn win_rate(candidate: &str, opponent: &str, maps: &[Vec<String>], games: usize, seed: u64) -> Result<f64, String> {
+    let mut won = 0.0;
+    let mut played = 0;
+    for map in maps {
+        for game in 0..games as u64 {
+            for seat in [0, 1] {
+                let seats = if seat == 0 { [(1u8, candidate), (2, opponent)] } else { [(1, opponent), (2, candidate)] };
+                let mut players = seats.map(|(p, name)| {
+                    let mut config = Config::from_env();
+                    config.strategy = name.to_string();
+                    config.build_game(p)
+                });
+                let [first, second] = play_game(map, seed + game, &mut players)?;
+                let (ours, theirs) = if seat == 0 { (first, second) } else { (second, first) };
+                won += match ours.cmp(&theirs) {
+                    std::cmp::Ordering::Greater => 1.0,
+                    std::cmp::Ordering::Equal => 0.5,
+                    std::cmp::Ordering::Less => 0.0,
+                };
+                played += 1;
+            }
+        }
+    }
+    Ok(won / played.max(1) as f64)
+}
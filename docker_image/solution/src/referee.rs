@@ -0,0 +1,471 @@
+// src/referee.rs
+// A small local engine for reproducible games between built-in strategies.
+// Unlike the bundled binary engine it can deal pieces from a script file,
+// so a specific tactical situation can be replayed exactly.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::time::{Duration, Instant};
+
+use crate::board::{Board, Owner};
+use crate::config::Config;
+use crate::game::{Game, Phase};
+use crate::grid::Grid;
+use crate::latency::LatencyReport;
+use crate::piece::Piece;
+use crate::rng::Rng;
+
+/// Where each turn's piece comes from: the script first, then random pieces.
+pub struct PieceSource {
+    scripted: VecDeque<Piece>,
+    rng: Rng,
+}
+
+impl PieceSource {
+    pub fn scripted(pieces: Vec<Piece>, seed: u64) -> Self {
+        PieceSource {
+            scripted: pieces.into(),
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// The next piece to deal. Pieces are dealt in turn order (p1, p2, p1, ...).
+    pub fn next(&mut self, rows: usize, cols: usize) -> Piece {
+        match self.scripted.pop_front() {
+            Some(piece) => piece,
+            None => random_piece(&mut self.rng, rows, cols),
+        }
+    }
+}
+
+/// Parse a piece script: a sequence of blocks in the engine's own format
+///
+/// Piece 4 1:
+/// OOOO
+///
+/// Lines starting with '#' are comments.
+pub fn parse_piece_script(text: &str) -> Result<Vec<Piece>, String> {
+    let lines: Vec<String> = text
+        .lines()
+        .filter(|l| !l.trim_start().starts_with('#'))
+        .map(|l| l.to_string())
+        .collect();
+
+    let headers: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| l.trim_start().starts_with("Piece"))
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut pieces = Vec::new();
+    for (n, &start) in headers.iter().enumerate() {
+        let end = headers.get(n + 1).copied().unwrap_or(lines.len());
+        let block = lines.get(start..end).unwrap_or_default();
+        let piece = Piece::from_piece_lines(block).ok_or_else(|| {
+            let header = block.first().map_or("", |l| l.trim());
+            format!("bad piece block at line {}: {}", start + 1, header)
+        })?;
+        pieces.push(piece);
+    }
+
+    Ok(pieces)
+}
+
+/// One dealt turn.
+pub struct Turn {
+    /// 1-based count of turns dealt so far, both players included.
+    pub number: usize,
+    pub player: u8,
+    /// Top-left (row, col) of the placement, or `None` if the player is out.
+    pub placed: Option<(usize, usize)>,
+    /// The phase the board was in from the mover's side.
+    pub phase: Phase,
+    /// How long the player took to decide.
+    pub elapsed: Duration,
+    /// What the player was shown, from their side.
+    pub board: Board,
+    pub piece: Piece,
+}
+
+/// Alternative win conditions for evaluating strategies under rule
+/// variants. The default is the standard game: play until neither side can
+/// move, most cells wins.
+#[derive(Clone, Copy, Default)]
+pub struct Rules {
+    /// Stop after this many dealt turns, both players counted.
+    pub max_turns: Option<usize>,
+    /// The first player to hold this many cells wins outright.
+    pub target_cells: Option<usize>,
+    /// The first player who can't move loses, whatever the count.
+    pub sudden_death: bool,
+    /// Fog of war: each player is only shown the cells within this many
+    /// steps of their own, the rest as '?' (`Owner::Unknown`).
+    pub fog: Option<usize>,
+}
+
+/// A game in progress on the referee's board.
+pub struct Match {
+    grid: Grid<char>,
+    active: [bool; 2],
+    turns: usize,
+    pieces: PieceSource,
+    rules: Rules,
+    /// Set when a rule variant ends the game early.
+    over: bool,
+    /// Winner and reason, when a rule variant decided the game.
+    decided: Option<(u8, &'static str)>,
+}
+
+impl Match {
+    /// Start a game on `map` (rows of '.', '@' for p1, '$' for p2).
+    pub fn new(map: &[String], pieces: PieceSource) -> Result<Self, String> {
+        let rows: Vec<Vec<char>> = map
+            .iter()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .map(|l| l.chars().collect())
+            .collect();
+
+        let cols = rows.first().map_or(0, Vec::len);
+        if rows.is_empty() || rows.iter().any(|row| row.len() != cols) {
+            return Err("map must be a non-empty rectangle".to_string());
+        }
+
+        Ok(Match {
+            grid: Grid::from_rows(rows, '.'),
+            active: [true, true],
+            turns: 0,
+            pieces,
+            rules: Rules::default(),
+            over: false,
+            decided: None,
+        })
+    }
+
+    pub fn with_rules(mut self, rules: Rules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Deal the next turn. Returns `None` once neither player can move, or
+    /// once the rules end the game.
+    pub fn step(&mut self, players: &mut [Game; 2]) -> Option<Turn> {
+        if self.over || (!self.active[0] && !self.active[1]) {
+            return None;
+        }
+
+        // Players that are out don't get dealt a piece
+        let seat = self.turns % 2;
+        let index = if self.active.get(seat) == Some(&true) { seat } else { 1 - seat };
+        if index != self.turns % 2 {
+            self.turns += 1;
+        }
+        self.turns += 1;
+
+        let player = index as u8 + 1;
+        let piece = self.pieces.next(self.grid.rows(), self.grid.cols());
+        let truth = Board::from_anfield_lines(&render_anfield(&self.grid), player)?;
+        let board = match self.rules.fog {
+            Some(radius) => Board::from_anfield_lines(&render_anfield(&fogged(&self.grid, player, radius)), player)?,
+            None => truth.clone(),
+        };
+
+        let phase = Phase::of(&truth);
+        let started = Instant::now();
+        let chosen = players.get_mut(index)?.choose_best_move(&board, &piece);
+        let elapsed = started.elapsed();
+
+        let placed = match chosen {
+            Some((top_y, left_x)) if truth.can_place(&piece, top_y, left_x, Owner::Me) => {
+                Some((top_y, left_x))
+            }
+            _ => None,
+        };
+
+        match placed {
+            Some((top_y, left_x)) => self.apply(&piece, top_y, left_x, player),
+            // Like the real engine: a player that can't move is out
+            None => {
+                if let Some(active) = self.active.get_mut(index) {
+                    *active = false;
+                }
+            }
+        }
+        self.apply_rules(index, placed.is_some());
+
+        Some(Turn {
+            number: self.turns,
+            player,
+            placed,
+            phase,
+            elapsed,
+            board,
+            piece,
+        })
+    }
+
+    /// End the game early if a rule variant says so.
+    fn apply_rules(&mut self, index: usize, moved: bool) {
+        let player = index as u8 + 1;
+        if !moved && self.rules.sudden_death {
+            self.decided = Some((3 - player, "opponent couldn't move"));
+        } else if self.rules.target_cells.is_some_and(|t| self.scores().get(index).is_some_and(|&n| n >= t)) {
+            self.decided = Some((player, "reached the target"));
+        }
+        let out_of_turns = self.rules.max_turns.is_some_and(|t| self.turns >= t);
+        self.over = self.decided.is_some() || out_of_turns;
+    }
+
+    /// The winner and why, if a rule variant decided the game rather than
+    /// the final count.
+    pub fn decided(&self) -> Option<(u8, &'static str)> {
+        self.decided
+    }
+
+    pub fn scores(&self) -> [usize; 2] {
+        let count = |marks: [char; 2]| self.grid.iter().filter(|(_, c)| marks.contains(c)).count();
+        [count(['@', 'a']), count(['$', 's'])]
+    }
+
+    pub fn grid_lines(&self) -> Vec<String> {
+        text_rows(&self.grid)
+    }
+
+    /// The Anfield block as the engine would send it, fog aside.
+    #[cfg(test)]
+    pub fn anfield_lines(&self) -> Vec<String> {
+        render_anfield(&self.grid)
+    }
+
+    fn apply(&mut self, piece: &Piece, top_y: usize, left_x: usize, player: u8) {
+        // Only the most recent placement is shown in lowercase
+        let (solid, latest) = if player == 1 { ('@', 'a') } else { ('$', 's') };
+        let shown: Vec<(usize, usize)> = self.grid.iter().filter(|&(_, c)| c == latest).map(|(pos, _)| pos).collect();
+        for (y, x) in shown {
+            self.grid.set(y, x, solid);
+        }
+        for &(dy, dx) in &piece.cells {
+            let (y, x) = (top_y + dy, left_x + dx);
+            if self.grid.get(y, x) == Some('.') {
+                self.grid.set(y, x, latest);
+            }
+        }
+    }
+}
+
+const USAGE: &str = "usage: filler play <map> [--pieces FILE] [--seed N] [--p1 NAME] [--p2 NAME] [--verbose] \
+                     [--p1-aggression X] [--p2-aggression X] [--max-turns N] [--target-cells N] [--sudden-death] [--fog N]";
+
+/// Command line entry, see `USAGE`.
+pub fn run_cli(args: &[String]) -> Result<(), String> {
+    let mut map_path: Option<&str> = None;
+    let mut pieces_path: Option<&str> = None;
+    let mut seed: u64 = 1;
+    let mut names = ["heuristic".to_string(), "heuristic".to_string()];
+    let mut aggression: [Option<f32>; 2] = [None, None];
+    let mut verbose = false;
+    let mut rules = Rules::default();
+
+    let mut i = 0;
+    while let Some(arg) = args.get(i) {
+        let value = args.get(i + 1);
+        let number = |name: &str| -> Result<u64, String> {
+            value
+                .and_then(|v| v.parse().ok())
+                .ok_or(format!("{} expects a number", name))
+        };
+        match arg.as_str() {
+            "--pieces" => pieces_path = value.map(|s| s.as_str()),
+            "--seed" => seed = number("--seed")?,
+            "--p1" => names[0] = value.cloned().unwrap_or_default(),
+            "--p2" => names[1] = value.cloned().unwrap_or_default(),
+            "--p1-aggression" | "--p2-aggression" => {
+                let dial = value
+                    .and_then(|v| v.parse().ok())
+                    .ok_or(format!("{} expects a number", arg))?;
+                let seat = if arg.starts_with("--p1") { &mut aggression[0] } else { &mut aggression[1] };
+                *seat = Some(dial);
+            }
+            "--max-turns" => rules.max_turns = Some(number("--max-turns")? as usize),
+            "--target-cells" => rules.target_cells = Some(number("--target-cells")? as usize),
+            "--fog" => rules.fog = Some(number("--fog")? as usize),
+            "--verbose" => {
+                verbose = true;
+                i += 1;
+                continue;
+            }
+            "--sudden-death" => {
+                rules.sudden_death = true;
+                i += 1;
+                continue;
+            }
+            other => {
+                map_path = Some(other);
+                i += 1;
+                continue;
+            }
+        }
+        i += 2;
+    }
+
+    let map_path = map_path.ok_or(USAGE)?;
+    let map: Vec<String> = fs::read_to_string(map_path)
+        .map_err(|e| format!("{}: {}", map_path, e))?
+        .lines()
+        .map(|l| l.to_string())
+        .collect();
+
+    let script = match pieces_path {
+        Some(path) => {
+            let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+            parse_piece_script(&text)?
+        }
+        None => Vec::new(),
+    };
+
+    let [first, second] = names;
+    let mut players = [(1u8, first, aggression[0]), (2, second, aggression[1])].map(|(p, name, dial)| {
+        let mut config = Config::from_env();
+        config.strategy = name;
+        if let Some(dial) = dial {
+            config.aggression = dial;
+        }
+        config.build_game(p)
+    });
+
+    let mut latency = [LatencyReport::default(), LatencyReport::default()];
+    let mut game = Match::new(&map, PieceSource::scripted(script, seed))?.with_rules(rules);
+    while let Some(turn) = game.step(&mut players) {
+        if let Some(report) = latency.get_mut(turn.player as usize - 1) {
+            report.record(turn.phase, turn.elapsed);
+        }
+        // Analysis mode: pick up pattern edits between turns
+        for player in players.iter_mut() {
+            player.patterns.refresh();
+        }
+        if verbose {
+            match turn.placed {
+                Some((y, x)) => println!("turn {}: p{} places at ({}, {})", turn.number, turn.player, y, x),
+                None => println!("turn {}: p{} can't move", turn.number, turn.player),
+            }
+        }
+    }
+
+    let scores = game.scores();
+    for row in game.grid_lines() {
+        println!("{}", row);
+    }
+    println!("Player1 ({}): {}", players[0].strategy.name(), scores[0]);
+    println!("Player2 ({}): {}", players[1].strategy.name(), scores[1]);
+    if let Some((winner, reason)) = game.decided() {
+        println!("Player{} wins: {}", winner, reason);
+    }
+
+    for (p, (report, player)) in latency.iter().zip(&players).enumerate() {
+        println!("Player{} decision latency by phase:", p + 1);
+        for line in report.lines() {
+            println!("  {}", line);
+        }
+        if let Some(bank) = player.strategy.time_bank() {
+            println!("  time bank: {}", bank.summary());
+        }
+    }
+
+    Ok(())
+}
+
+/// Play a whole game on `map` with random pieces from `seed` and return the
+/// final scores.
+pub fn play_game(map: &[String], seed: u64, players: &mut [Game; 2]) -> Result<[usize; 2], String> {
+    let mut game = Match::new(map, PieceSource::scripted(Vec::new(), seed))?;
+    while game.step(players).is_some() {}
+    Ok(game.scores())
+}
+
+/// `grid` as `player` sees it through the fog: cells more than `radius`
+/// steps from all of their own cells become '?'.
+fn fogged(grid: &Grid<char>, player: u8, radius: usize) -> Grid<char> {
+    let own: &[char] = if player == 1 { &['@', 'a'] } else { &['$', 's'] };
+
+    let mut dist = Grid::new(grid.rows(), grid.cols(), usize::MAX);
+    let mut queue = VecDeque::new();
+    for ((y, x), c) in grid.iter() {
+        if own.contains(&c) {
+            dist.set(y, x, 0);
+            queue.push_back((y, x));
+        }
+    }
+    while let Some((y, x)) = queue.pop_front() {
+        let d = dist.get(y, x).unwrap_or(radius);
+        if d == radius {
+            continue;
+        }
+        for (ny, nx) in grid.neighbors(y, x) {
+            if dist.get(ny, nx) == Some(usize::MAX) {
+                dist.set(ny, nx, d + 1);
+                queue.push_back((ny, nx));
+            }
+        }
+    }
+
+    let mut seen = grid.clone();
+    for ((y, x), d) in dist.iter() {
+        if d == usize::MAX {
+            seen.set(y, x, '?');
+        }
+    }
+    seen
+}
+
+/// The rows of `grid` as text.
+fn text_rows(grid: &Grid<char>) -> Vec<String> {
+    (0..grid.rows())
+        .map(|y| (0..grid.cols()).filter_map(|x| grid.get(y, x)).collect())
+        .collect()
+}
+
+/// Render the grid the way the engine sends it.
+fn render_anfield(grid: &Grid<char>) -> Vec<String> {
+    let cols = grid.cols();
+    let mut lines = Vec::with_capacity(grid.rows() + 2);
+
+    lines.push(format!("Anfield {} {}:", cols, grid.rows()));
+    let ruler: String = (0..cols).map(|x| char::from(b'0' + (x % 10) as u8)).collect();
+    lines.push(format!("    {}", ruler));
+    for (y, row) in text_rows(grid).iter().enumerate() {
+        lines.push(format!("{:03} {}", y, row));
+    }
+
+    lines
+}
+
+/// A random connected blob sized relative to the board, roughly what the
+/// bundled engine deals.
+fn random_piece(rng: &mut Rng, rows: usize, cols: usize) -> Piece {
+    let max_side = (rows.min(cols) / 5).clamp(2, 12);
+    let height = 1 + rng.below(max_side);
+    let width = 1 + rng.below(max_side);
+    let target = 1 + rng.below(width * height);
+
+    let mut cells = vec![(rng.below(height), rng.below(width))];
+    let mut attempts = 0;
+    while cells.len() < target && attempts < target * 20 {
+        attempts += 1;
+        let Some(&(y, x)) = cells.get(rng.below(cells.len())) else {
+            break;
+        };
+        let (ny, nx) = match rng.below(4) {
+            0 if y > 0 => (y - 1, x),
+            1 if y + 1 < height => (y + 1, x),
+            2 if x > 0 => (y, x - 1),
+            3 if x + 1 < width => (y, x + 1),
+            _ => continue,
+        };
+        if !cells.contains(&(ny, nx)) {
+            cells.push((ny, nx));
+        }
+    }
+
+    Piece::trimmed(cells)
+}
@@ -0,0 +1,47 @@
+// src/rng.rs
+// Small xorshift generator so the bot stays dependency-free.
+
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // xorshift gets stuck on zero
+        Rng { state: seed.max(1) }
+    }
+
+    /// Seed from the clock, for when reproducibility doesn't matter.
+    pub fn from_time() -> Self {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0x9E37_79B9_7F4A_7C15);
+        Rng::new(nanos)
+    }
+
+    /// The current state; `Rng::new(state)` continues the same sequence.
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        x
+    }
+
+    /// Uniform float in `[0, 1)`.
+    pub fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform index in `0..n`, or 0 if `n` is zero (which `.get()` then
+    /// finds nothing at).
+    pub fn below(&mut self, n: usize) -> usize {
+        self.next_u64().checked_rem(n as u64).unwrap_or(0) as usize
+    }
+}
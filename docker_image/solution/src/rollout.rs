@@ -0,0 +1,61 @@
+// src/rollout.rs
+// Projecting the final score by playing the rest of the game out. Both
+// sides take turns placing the pieces we've been dealt lately, in the order
+// they came, each where the greedy rollout policy from mcts.rs puts it,
+// until neither can move or the work cap runs out. Far too crude to choose
+// moves with, but a fair way to settle between placements the heuristic
+// can't tell apart: whatever its terms miss about how the board plays out
+// shows up in the counts at the end.
+
+use crate::board::{Board, Owner};
+use crate::mcts;
+use crate::piece::Piece;
+use crate::rng::Rng;
+
+/// Plies played out at most, both sides counted.
+pub const ROLLOUT_PLIES: usize = 60;
+
+/// Piece cells tried at most over a playout. A ply costs about the square
+/// of the piece's cells, so a run of big pieces stops well short of
+/// `ROLLOUT_PLIES` instead of blowing the turn's time.
+pub const ROLLOUT_CELLS: usize = 600;
+
+/// The same cap in piece cells times board cells: every ply scans the whole
+/// board for legal placements, so on a huge map it comes down further.
+pub const ROLLOUT_WORK: usize = 2_000_000;
+
+/// Cells each side holds once the game is played out from `board` with
+/// `to_move` to play, as (ours, theirs). The same board, pieces and seed
+/// always give the same projection.
+pub fn project(board: &Board, pieces: &[Piece], mut to_move: Owner, seed: u64) -> (usize, usize) {
+    let mut board = board.clone();
+    let mut rng = Rng::new(seed);
+    let mut passes = 0;
+    let mut tried = 0;
+    let cap = ROLLOUT_CELLS.min(ROLLOUT_WORK / (board.rows() * board.cols()).max(1));
+
+    for ply in 0..ROLLOUT_PLIES {
+        let Some(piece) = pieces.get(ply % pieces.len().max(1)) else {
+            break;
+        };
+        tried += piece.cells.len();
+        if tried > cap {
+            break;
+        }
+        match mcts::greedy_move(&board, piece, to_move, &mut rng) {
+            Some((top_y, left_x)) => {
+                board.place(piece, top_y, left_x, to_move);
+                passes = 0;
+            }
+            None => {
+                passes += 1;
+                if passes == 2 {
+                    break;
+                }
+            }
+        }
+        to_move = if to_move == Owner::Me { Owner::Opponent } else { Owner::Me };
+    }
+
+    (board.count(Owner::Me), board.count(Owner::Opponent))
+}
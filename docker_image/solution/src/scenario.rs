@@ -0,0 +1,296 @@
+// src/scenario.rs
+// Strategic test cases written in a small text DSL and played out on the
+// local referee. Every `scenarios/*.scn` file is run by `cargo test`.
+//
+//     # comment
+//     seed: 3
+//     p1: heuristic
+//     p2: heuristic
+//     board:
+//     ..........
+//     ..@...$...
+//     end
+//     pieces:
+//     Piece 2 1:
+//     OO
+//     end
+//     expect turn 1: p1 places (1, 2)
+//     expect by turn 6: p1 owns (4,7)-(4,9)
+//     expect by turn 4: p1 reaches (3,0)-(5,9)
+//     expect winner p1
+//     expect p2 score <= 40
+//
+// Coordinates are (row, col). Turns count every dealt piece, both players
+// included, starting from 1. `owns` needs every cell of the rectangle,
+// `reaches` just one.
+
+use std::fs;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::game::Game;
+use crate::referee::{parse_piece_script, Match, PieceSource};
+
+#[derive(Debug)]
+enum Expectation {
+    Places { turn: usize, player: u8, at: (usize, usize) },
+    Owns { turn: usize, player: u8, from: (usize, usize), to: (usize, usize) },
+    Reaches { turn: usize, player: u8, from: (usize, usize), to: (usize, usize) },
+    Winner(u8),
+    Score { player: u8, op: String, value: usize },
+}
+
+struct Scenario {
+    seed: u64,
+    strategies: [String; 2],
+    board: Vec<String>,
+    pieces: String,
+    expectations: Vec<Expectation>,
+}
+
+fn parse(text: &str) -> Result<Scenario, String> {
+    let mut scenario = Scenario {
+        seed: 1,
+        strategies: ["heuristic".to_string(), "heuristic".to_string()],
+        board: Vec::new(),
+        pieces: String::new(),
+        expectations: Vec::new(),
+    };
+
+    let mut lines = text.lines().enumerate();
+    while let Some((n, raw)) = lines.next() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let err = |msg: &str| format!("line {}: {}: {}", n + 1, msg, line);
+
+        if line == "board:" || line == "pieces:" {
+            let mut block = Vec::new();
+            for (_, l) in lines.by_ref() {
+                if l.trim() == "end" {
+                    break;
+                }
+                block.push(l.to_string());
+            }
+            if line == "board:" {
+                scenario.board = block;
+            } else {
+                scenario.pieces = block.join("\n");
+            }
+        } else if let Some(rest) = line.strip_prefix("seed:") {
+            scenario.seed = rest.trim().parse().map_err(|_| err("bad seed"))?;
+        } else if let Some(rest) = line.strip_prefix("p1:") {
+            scenario.strategies[0] = rest.trim().to_string();
+        } else if let Some(rest) = line.strip_prefix("p2:") {
+            scenario.strategies[1] = rest.trim().to_string();
+        } else if let Some(rest) = line.strip_prefix("expect ") {
+            scenario
+                .expectations
+                .push(parse_expectation(rest).ok_or_else(|| err("bad expectation"))?);
+        } else {
+            return Err(err("unknown directive"));
+        }
+    }
+
+    if scenario.board.is_empty() {
+        return Err("missing board".to_string());
+    }
+    Ok(scenario)
+}
+
+fn parse_expectation(text: &str) -> Option<Expectation> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+
+    match words.as_slice() {
+        ["winner", player] => Some(Expectation::Winner(parse_player(player)?)),
+        [player, "score", op, value] => Some(Expectation::Score {
+            player: parse_player(player)?,
+            op: op.to_string(),
+            value: value.parse().ok()?,
+        }),
+        _ => {
+            // "turn N: ..." or "by turn N: ..."
+            let (by, rest) = match text.strip_prefix("by turn ") {
+                Some(rest) => (true, rest),
+                None => (false, text.strip_prefix("turn ")?),
+            };
+            let (turn, rest) = rest.split_once(':')?;
+            let turn: usize = turn.trim().parse().ok()?;
+            let (player, rest) = rest.trim().split_once(' ')?;
+            let player = parse_player(player)?;
+            let (verb, coords) = rest.trim().split_once(' ')?;
+
+            match (by, verb) {
+                (false, "places") => Some(Expectation::Places {
+                    turn,
+                    player,
+                    at: parse_point(coords)?,
+                }),
+                (true, "owns") | (true, "reaches") => {
+                    let (from, to) = match coords.split_once(")-(") {
+                        Some((a, b)) => (parse_point(&format!("{})", a))?, parse_point(&format!("({}", b))?),
+                        None => (parse_point(coords)?, parse_point(coords)?),
+                    };
+                    if verb == "owns" {
+                        Some(Expectation::Owns { turn, player, from, to })
+                    } else {
+                        Some(Expectation::Reaches { turn, player, from, to })
+                    }
+                }
+                _ => None,
+            }
+        }
+    }
+}
+
+fn parse_player(word: &str) -> Option<u8> {
+    match word {
+        "p1" => Some(1),
+        "p2" => Some(2),
+        _ => None,
+    }
+}
+
+/// "(row, col)" -> (row, col)
+fn parse_point(text: &str) -> Option<(usize, usize)> {
+    let inner = text.trim().strip_prefix('(')?.strip_suffix(')')?;
+    let (y, x) = inner.split_once(',')?;
+    Some((y.trim().parse().ok()?, x.trim().parse().ok()?))
+}
+
+/// How many cells of the rectangle `player` holds on the referee grid.
+fn owned_in(grid: &[String], player: u8, from: (usize, usize), to: (usize, usize)) -> (usize, usize) {
+    let marks = if player == 1 { ['@', 'a'] } else { ['$', 's'] };
+    let mut owned = 0;
+    let mut total = 0;
+    for y in from.0.min(to.0)..=from.0.max(to.0) {
+        for x in from.1.min(to.1)..=from.1.max(to.1) {
+            total += 1;
+            let cell = grid.get(y).and_then(|row| row.chars().nth(x));
+            if cell.is_some_and(|c| marks.contains(&c)) {
+                owned += 1;
+            }
+        }
+    }
+    (owned, total)
+}
+
+/// Settle the `owns`/`reaches` expectations that hold now or are past due.
+fn check_regions(
+    grid: &[String],
+    turn: usize,
+    pending: &mut Vec<&Expectation>,
+    failures: &mut Vec<String>,
+    game_over: bool,
+) {
+    pending.retain(|e| {
+        let (deadline, player, from, to, all) = match e {
+            Expectation::Owns { turn, player, from, to } => (*turn, *player, *from, *to, true),
+            Expectation::Reaches { turn, player, from, to } => (*turn, *player, *from, *to, false),
+            _ => return false,
+        };
+        let (owned, total) = owned_in(grid, player, from, to);
+        if (all && owned == total) || (!all && owned > 0) {
+            return false;
+        }
+        if turn >= deadline || game_over {
+            failures.push(format!("{:?}: only {}/{} cells held at turn {}", e, owned, total, turn));
+            return false;
+        }
+        true
+    });
+}
+
+/// Play the scenario out and return every expectation that didn't hold.
+fn run(scenario: &Scenario) -> Result<Vec<String>, String> {
+    let pieces = parse_piece_script(&scenario.pieces)?;
+    let mut game = Match::new(&scenario.board, PieceSource::scripted(pieces, scenario.seed))?;
+    let mut players = [1u8, 2].map(|p| {
+        let config = Config {
+            strategy: scenario.strategies[p as usize - 1].clone(),
+            ..Config::default()
+        };
+        config.build_game(p)
+    });
+
+    let mut pending: Vec<&Expectation> = scenario.expectations.iter().collect();
+    let mut failures = Vec::new();
+
+    let mut last_turn = 0;
+    while let Some(turn) = game.step(&mut players) {
+        last_turn = turn.number;
+        for e in &scenario.expectations {
+            if let Expectation::Places { turn: n, player, at } = e {
+                if *n == turn.number && (*player != turn.player || turn.placed != Some(*at)) {
+                    failures.push(format!("{:?}: p{} played {:?}", e, turn.player, turn.placed));
+                }
+            }
+        }
+        check_regions(&game.grid_lines(), turn.number, &mut pending, &mut failures, false);
+    }
+    check_regions(&game.grid_lines(), last_turn, &mut pending, &mut failures, true);
+
+    let scores = game.scores();
+    for e in &scenario.expectations {
+        let holds = match e {
+            Expectation::Winner(p) => scores[*p as usize - 1] > scores[2 - *p as usize],
+            Expectation::Score { player, op, value } => {
+                let score = scores[*player as usize - 1];
+                match op.as_str() {
+                    ">=" => score >= *value,
+                    "<=" => score <= *value,
+                    ">" => score > *value,
+                    "<" => score < *value,
+                    "==" => score == *value,
+                    _ => false,
+                }
+            }
+            _ => true,
+        };
+        if !holds {
+            failures.push(format!("{:?}: final scores p1={} p2={}", e, scores[0], scores[1]));
+        }
+    }
+
+    Ok(failures)
+}
+
+/// Self-play on one of the maps we ship (`maps/<name>`), both seats
+/// built from the default config and pieces drawn from `seed`. For
+/// tests that want real positions rather than a scenario.
+pub fn shipped_game(map: &str, seed: u64) -> (Match, [Game; 2]) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../maps").join(map);
+    let map: Vec<String> = fs::read_to_string(path)
+        .expect("readable map")
+        .lines()
+        .map(|l| l.to_string())
+        .collect();
+    let game = Match::new(&map, PieceSource::scripted(Vec::new(), seed)).expect("valid map");
+    (game, [1u8, 2].map(|p| Config::default().build_game(p)))
+}
+
+#[test]
+fn scenarios_hold() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios");
+    let mut paths: Vec<_> = fs::read_dir(&dir)
+        .expect("scenarios directory")
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "scn"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no scenarios found in {}", dir.display());
+
+    let mut report = Vec::new();
+    for path in &paths {
+        let name = path.file_name().unwrap().to_string_lossy();
+        let text = fs::read_to_string(path).expect("readable scenario");
+        match parse(&text).and_then(|s| run(&s)) {
+            Ok(failures) => report.extend(failures.into_iter().map(|f| format!("{}: {}", name, f))),
+            Err(e) => report.push(format!("{}: {}", name, e)),
+        }
+    }
+
+    assert!(report.is_empty(), "scenario failures:\n{}", report.join("\n"));
+}
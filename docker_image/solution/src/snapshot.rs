@@ -0,0 +1,93 @@
+// src/snapshot.rs
+// A fingerprint of what we saw and what we did on one turn. Two runs that
+// should play identically can be compared turn by turn; the first differing
+// hash is where they diverged.
+//
+// FNV-1a rather than std's DefaultHasher, whose output isn't guaranteed to be
+// the same across Rust releases or platforms.
+
+use crate::board::{Board, Owner};
+use crate::piece::Piece;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+struct Fnv(u64);
+
+impl Fnv {
+    fn write(&mut self, value: u64) {
+        for byte in value.to_le_bytes() {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+/// Hash of some text, e.g. the bundled weights or book.
+pub fn text_hash(text: &str) -> u64 {
+    let mut h = Fnv(FNV_OFFSET);
+    for byte in text.bytes() {
+        h.write(byte as u64);
+    }
+    h.0
+}
+
+/// Hash of a few numbers, e.g. to rank tied placements (see ties.rs).
+pub fn mix(values: &[u64]) -> u64 {
+    let mut h = Fnv(FNV_OFFSET);
+    for &value in values {
+        h.write(value);
+    }
+    h.0
+}
+
+/// Hash of a piece's shape.
+pub fn piece_hash(piece: &Piece) -> u64 {
+    let mut h = Fnv(FNV_OFFSET);
+    h.write(piece.width as u64);
+    h.write(piece.height as u64);
+    for &(dy, dx) in &piece.cells {
+        h.write(dy as u64);
+        h.write(dx as u64);
+    }
+    h.0
+}
+
+/// Hash of the position we were asked to move in.
+pub fn position_hash(board: &Board, piece: &Piece) -> u64 {
+    turn_hash(board, piece, None)
+}
+
+/// Hash of the parsed board, the piece and the chosen move.
+pub fn turn_hash(board: &Board, piece: &Piece, chosen: Option<(usize, usize)>) -> u64 {
+    let mut h = Fnv(FNV_OFFSET);
+
+    h.write(board.rows() as u64);
+    h.write(board.cols() as u64);
+    for (_, owner) in board.iter() {
+        h.write(match owner {
+            Owner::Empty => 0,
+            Owner::Me => 1,
+            Owner::Opponent => 2,
+            Owner::Unknown => 3,
+        });
+    }
+
+    h.write(piece.width as u64);
+    h.write(piece.height as u64);
+    for &(dy, dx) in &piece.cells {
+        h.write(dy as u64);
+        h.write(dx as u64);
+    }
+
+    match chosen {
+        Some((y, x)) => {
+            h.write(1);
+            h.write(y as u64);
+            h.write(x as u64);
+        }
+        None => h.write(0),
+    }
+
+    h.0
+}
@@ -0,0 +1,97 @@
+// src/solo.rs
+// Filling the board alone, once there's nobody to play against: no
+// opponent in sight and nothing hidden, or they've stopped playing (see
+// `Game::opponent_gone`). Every placement claims the same cells then, so
+// all that's left to win is how many more pieces fit.
+//
+// Each placement is scored on what it leaves behind. A pocket of empty
+// cells smaller than any piece dealt so far claims is lost for good, and
+// counts against the placement that closes it. Among the rest, the ones
+// that pack against edges and our own cells, and that fill from the far
+// side of the open area inward, keep what's left in one compact block for
+// the pieces to come. Charging every pocket by the share of pieces too big
+// for it, not just the ones no piece fits, played worse: it kept steering
+// away from the tightest packing.
+
+use crate::board::{Board, Owner};
+use crate::piece::{Piece, PieceStats};
+
+/// Cost of an empty cell no piece can get into any more.
+const WASTED: f64 = 10.0;
+/// Worth of a covered cell's side against an edge, our cells or theirs.
+const CONTACT: f64 = 4.0;
+/// Worth of covering cells as far out as the open area goes, for the whole
+/// piece.
+const FAR_SIDE: f64 = 30.0;
+
+pub fn choose_move(board: &Board, piece: &Piece, pieces: &PieceStats) -> Option<(usize, usize)> {
+    let open = board.coords(Owner::Empty);
+    let centre = match open.len() {
+        0 => (0.0, 0.0),
+        n => (
+            open.iter().map(|&(y, _)| y as f64).sum::<f64>() / n as f64,
+            open.iter().map(|&(_, x)| x as f64).sum::<f64>() / n as f64,
+        ),
+    };
+    let span = (board.rows() + board.cols()).max(1) as f64;
+    let pocket = pieces.smallest_claim().saturating_sub(1);
+
+    let mut best: Option<(f64, (usize, usize))> = None;
+    for (top_y, left_x) in board.legal_placements(piece, Owner::Me) {
+        let covered: Vec<(usize, usize)> = piece.cells.iter().map(|&(dy, dx)| (top_y + dy, left_x + dx)).collect();
+        let mut after = board.clone();
+        after.place(piece, top_y, left_x, Owner::Me);
+
+        let mut contact = 0;
+        let mut far = 0.0;
+        let mut around = Vec::new();
+        for &(y, x) in &covered {
+            far += (y as f64 - centre.0).abs() + (x as f64 - centre.1).abs();
+            let open_sides = after.neighbors(y, x).filter(|&(ny, nx)| after.is(ny, nx, Owner::Empty)).count();
+            let covered_sides = after.neighbors(y, x).filter(|n| covered.contains(n)).count();
+            contact += 4 - open_sides - covered_sides;
+            around.extend(after.neighbors(y, x).filter(|&(ny, nx)| after.is(ny, nx, Owner::Empty)));
+        }
+
+        // Only the pockets the piece touches can have changed
+        let mut touched: Vec<(usize, usize)> = covered.iter().copied().filter(|&(y, x)| board.is(y, x, Owner::Empty)).collect();
+        touched.extend(&around);
+        let wasted = lost(&after, &around, pocket) as f64 - lost(board, &touched, pocket) as f64;
+
+        let score = -wasted * WASTED + contact as f64 * CONTACT + far / covered.len() as f64 / span * FAR_SIDE;
+        if best.is_none_or(|(top, _)| score > top) {
+            best = Some((score, (top_y, left_x)));
+        }
+    }
+    best.map(|(_, at)| at)
+}
+
+/// Empty cells in the pockets holding `cells` that are no bigger than
+/// `largest`, each pocket counted once.
+fn lost(board: &Board, cells: &[(usize, usize)], largest: usize) -> usize {
+    let mut seen: Vec<(usize, usize)> = Vec::new();
+    let mut lost = 0;
+    for &start in cells {
+        if seen.contains(&start) {
+            continue;
+        }
+        let mut region = vec![start];
+        let mut i = 0;
+        while let Some(&(y, x)) = region.get(i) {
+            i += 1;
+            if region.len() > largest {
+                break;
+            }
+            for next in board.neighbors(y, x) {
+                if board.is(next.0, next.1, Owner::Empty) && !region.contains(&next) {
+                    region.push(next);
+                }
+            }
+        }
+        if region.len() <= largest {
+            lost += region.len();
+        }
+        seen.extend(region);
+    }
+    lost
+}
@@ -0,0 +1,60 @@
+// src/symmetry.rs
+// Mirror and rotational symmetry of the board. Maps are often symmetric
+// between the two starts, and until one side breaks it so is the position:
+//
+// - a symmetry that maps the board onto itself makes pairs of placements
+//   equivalent, so one evaluation serves both;
+// - a symmetry that swaps the two sides means the game is dead even, and
+//   the first move that tilts the race our way is worth extra.
+
+use crate::board::{Board, Owner};
+use crate::grid::Grid;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Symmetry {
+    /// Top to bottom.
+    MirrorRows,
+    /// Left to right.
+    MirrorCols,
+    /// Half a turn about the centre.
+    Rotate180,
+}
+
+const ALL: [Symmetry; 3] = [Symmetry::MirrorRows, Symmetry::MirrorCols, Symmetry::Rotate180];
+
+impl Symmetry {
+    pub fn apply(self, board: &Board, (y, x): (usize, usize)) -> (usize, usize) {
+        let (last_y, last_x) = (board.rows() - 1, board.cols() - 1);
+        match self {
+            Symmetry::MirrorRows => (last_y - y, x),
+            Symmetry::MirrorCols => (y, last_x - x),
+            Symmetry::Rotate180 => (last_y - y, last_x - x),
+        }
+    }
+
+    /// Whether `grid`, laid over `board`, reads the same at every cell as
+    /// at its image.
+    pub fn preserves<T: Copy + PartialEq>(self, board: &Board, grid: &Grid<T>) -> bool {
+        grid.iter().all(|(pos, value)| {
+            let (y, x) = self.apply(board, pos);
+            grid.get(y, x) == Some(value)
+        })
+    }
+}
+
+/// A symmetry that maps the board onto itself, or with `swap` onto itself
+/// with the two sides exchanged. Unknown cells never match.
+pub fn find(board: &Board, swap: bool) -> Option<Symmetry> {
+    let expected = |owner: Owner| match owner {
+        Owner::Me if swap => Owner::Opponent,
+        Owner::Opponent if swap => Owner::Me,
+        other => other,
+    };
+
+    ALL.into_iter().find(|&symmetry| {
+        board.iter().all(|(pos, owner)| {
+            let (y, x) = symmetry.apply(board, pos);
+            owner != Owner::Unknown && board.get(y, x) == Some(expected(owner))
+        })
+    })
+}
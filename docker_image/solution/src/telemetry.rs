@@ -0,0 +1,93 @@
+// src/telemetry.rs
+// Where the `debug!` output goes. FILLER_TELEMETRY picks the sink:
+//
+//   stderr           the default
+//   file:<path>      appended to a file
+//   udp:<host:port>  one datagram per line, for a dashboard on another machine
+//
+// The file and UDP sinks never touch stderr, so they're safe to leave on in
+// a real game. Write errors are dropped: telemetry must never cost a move.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::net::UdpSocket;
+use std::sync::{Mutex, OnceLock};
+
+pub trait Sink: Send {
+    fn write_line(&mut self, line: &str);
+}
+
+struct Stderr;
+
+impl Sink for Stderr {
+    fn write_line(&mut self, line: &str) {
+        eprintln!("{}", line);
+    }
+}
+
+struct FileSink(File);
+
+impl Sink for FileSink {
+    fn write_line(&mut self, line: &str) {
+        let _ = writeln!(self.0, "{}", line);
+    }
+}
+
+struct Udp(UdpSocket);
+
+impl Sink for Udp {
+    fn write_line(&mut self, line: &str) {
+        let _ = self.0.send(line.as_bytes());
+    }
+}
+
+/// Build a sink from a FILLER_TELEMETRY value.
+pub fn from_spec(spec: &str) -> Result<Box<dyn Sink>, String> {
+    let spec = spec.trim();
+    if spec == "stderr" {
+        return Ok(Box::new(Stderr));
+    }
+    if let Some(path) = spec.strip_prefix("file:") {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("{}: {}", path, e))?;
+        return Ok(Box::new(FileSink(file)));
+    }
+    if let Some(addr) = spec.strip_prefix("udp:") {
+        let socket = UdpSocket::bind("0.0.0.0:0").map_err(|e| e.to_string())?;
+        socket.connect(addr).map_err(|e| format!("{}: {}", addr, e))?;
+        // A slow or missing listener must not stall the bot
+        socket.set_nonblocking(true).map_err(|e| e.to_string())?;
+        return Ok(Box::new(Udp(socket)));
+    }
+    Err(format!("unknown telemetry sink `{}`", spec))
+}
+
+/// Whether `debug!` output is wanted at all: FILLER_DEBUG, or a sink named
+/// by FILLER_TELEMETRY.
+pub fn enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        std::env::var_os("FILLER_DEBUG").is_some() || std::env::var_os("FILLER_TELEMETRY").is_some()
+    })
+}
+
+/// Send one line to the configured sink.
+pub fn emit(args: std::fmt::Arguments) {
+    static SINK: OnceLock<Mutex<Box<dyn Sink>>> = OnceLock::new();
+    let sink = SINK.get_or_init(|| {
+        let sink = match std::env::var("FILLER_TELEMETRY") {
+            Ok(spec) => from_spec(&spec).unwrap_or_else(|e| {
+                eprintln!("telemetry on stderr: {}", e);
+                Box::new(Stderr)
+            }),
+            Err(_) => Box::new(Stderr),
+        };
+        Mutex::new(sink)
+    });
+    if let Ok(mut sink) = sink.lock() {
+        sink.write_line(&args.to_string());
+    }
+}
@@ -0,0 +1,68 @@
+// src/tempo.rs
+// Who is gaining cells faster, turn by turn. Each turn's census is compared
+// with the last one: the difference is what we placed last turn and what
+// they placed since. One turn behind is the luck of the draw; several in a
+// row means our plan is losing the race, and the heuristic switches to
+// riskier cutting play (see `Game::turn_weights`).
+
+use std::collections::VecDeque;
+
+use crate::board::Census;
+
+/// Turns of history kept.
+const HISTORY: usize = 8;
+
+/// Consecutive turns the opponent out-gains us before we call it a lost
+/// tempo.
+pub const LOSING_TURNS: usize = 3;
+
+/// Cells gained on one turn, by us and by them.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Gain {
+    pub me: usize,
+    pub opponent: usize,
+}
+
+#[derive(Default)]
+pub struct Tempo {
+    /// Newest last.
+    gains: VecDeque<Gain>,
+}
+
+impl Tempo {
+    pub fn record(&mut self, before: Census, after: Census) {
+        if self.gains.len() == HISTORY {
+            self.gains.pop_front();
+        }
+        self.gains.push_back(Gain {
+            me: after.me.saturating_sub(before.me),
+            opponent: after.opponent.saturating_sub(before.opponent),
+        });
+    }
+
+    /// The latest turn's gains.
+    pub fn last(&self) -> Option<Gain> {
+        self.gains.back().copied()
+    }
+
+    /// Turns in a row, up to now, the opponent gained more than we did.
+    pub fn losing_streak(&self) -> usize {
+        self.gains.iter().rev().take_while(|g| g.opponent > g.me).count()
+    }
+
+    /// Turns in a row, up to now, the opponent took no cells at all.
+    pub fn opponent_idle(&self) -> usize {
+        self.gains.iter().rev().take_while(|g| g.opponent == 0).count()
+    }
+
+    /// Out-gained `LOSING_TURNS` turns running, and behind over the kept
+    /// history: a streak of small pieces after a run of big ones isn't it.
+    pub fn losing(&self) -> bool {
+        self.losing_streak() >= LOSING_TURNS && self.trend() < 0
+    }
+
+    /// Our gains less theirs over the kept history.
+    pub fn trend(&self) -> i64 {
+        self.gains.iter().map(|g| g.me as i64 - g.opponent as i64).sum()
+    }
+}
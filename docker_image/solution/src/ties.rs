@@ -0,0 +1,132 @@
+// src/ties.rs
+// Settling placements that score exactly the same. Taking the first one
+// found favours whatever the scan visits first, the top-left, turn after
+// turn. The default instead ranks tied placements by a hash of where they
+// are and which turn it is: just as reproducible, with no direction to it.
+
+use std::str::FromStr;
+
+use crate::rng::Rng;
+use crate::snapshot;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TieBreak {
+    /// The first in scan order, as before.
+    Scan,
+    /// Lowest hash of placement and turn.
+    #[default]
+    Hash,
+    /// Uniformly at random, from this seed.
+    Random(u64),
+}
+
+impl FromStr for TieBreak {
+    type Err = String;
+
+    /// "scan", "hash", "random" or "random:<seed>".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_lowercase();
+        match s.split_once(':') {
+            Some(("random", seed)) => seed
+                .trim()
+                .parse()
+                .map(TieBreak::Random)
+                .map_err(|_| format!("bad tie-break seed `{}`", seed)),
+            _ => match s.as_str() {
+                "scan" => Ok(TieBreak::Scan),
+                "hash" => Ok(TieBreak::Hash),
+                "random" => Ok(TieBreak::Random(0)),
+                other => Err(format!("unknown tie-break `{}`", other)),
+            },
+        }
+    }
+}
+
+/// Picks among tied placements; see `TieBreak`.
+pub struct Ties {
+    pub rule: TieBreak,
+    rng: Rng,
+}
+
+impl Ties {
+    pub fn new(rule: TieBreak) -> Self {
+        let seed = match rule {
+            TieBreak::Random(seed) => seed,
+            _ => 0,
+        };
+        Ties { rule, rng: Rng::new(seed) }
+    }
+
+    /// The best-scoring placement of `scored`, ties settled by the rule.
+    /// `turn` salts the hash so a tie isn't settled the same way every turn.
+    pub fn best(&mut self, scored: &[(i64, (usize, usize))], turn: usize) -> Option<(i64, (usize, usize))> {
+        let top = scored.iter().map(|&(score, _)| score).max()?;
+        let tied: Vec<(usize, usize)> = scored
+            .iter()
+            .filter(|&&(score, _)| score == top)
+            .map(|&(_, pos)| pos)
+            .collect();
+
+        let &first = tied.first()?;
+        let pos = match self.rule {
+            TieBreak::Scan => first,
+            TieBreak::Hash if tied.len() == 1 => first,
+            TieBreak::Hash => tied
+                .iter()
+                .copied()
+                .min_by_key(|&(y, x)| snapshot::mix(&[turn as u64, y as u64, x as u64]))
+                .unwrap_or(first),
+            TieBreak::Random(_) => tied.get(self.rng.below(tied.len())).copied().unwrap_or(first),
+        };
+        Some((top, pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Four placements tied for best, and one below them.
+    const SCORED: [(i64, (usize, usize)); 5] = [(5, (0, 0)), (5, (0, 3)), (2, (1, 1)), (5, (2, 0)), (5, (3, 3))];
+
+    fn picks(rule: TieBreak, turns: usize) -> Vec<(usize, usize)> {
+        let mut ties = Ties::new(rule);
+        (0..turns).filter_map(|turn| ties.best(&SCORED, turn)).map(|(_, pos)| pos).collect()
+    }
+
+    #[test]
+    fn same_seed_same_picks() {
+        for rule in [TieBreak::Scan, TieBreak::Hash, TieBreak::Random(7)] {
+            assert_eq!(picks(rule, 20), picks(rule, 20), "{:?}", rule);
+        }
+    }
+
+    /// Over turns for the hash, or over seeds at random, every tied
+    /// placement gets picked.
+    #[test]
+    fn picks_spread_across_ties() {
+        let tied = |picks: &[(usize, usize)]| {
+            let mut seen: Vec<_> = picks.to_vec();
+            seen.sort_unstable();
+            seen.dedup();
+            seen
+        };
+        let all = vec![(0, 0), (0, 3), (2, 0), (3, 3)];
+        assert_eq!(tied(&picks(TieBreak::Hash, 40)), all);
+        let seeded: Vec<_> = (0..40).flat_map(|seed| picks(TieBreak::Random(seed), 1)).collect();
+        assert_eq!(tied(&seeded), all);
+        assert_eq!(tied(&picks(TieBreak::Scan, 40)), vec![(0, 0)]);
+    }
+
+    #[test]
+    fn a_better_score_is_never_passed_over() {
+        let mut scored = SCORED;
+        scored[2] = (9, (1, 1));
+        for rule in [TieBreak::Scan, TieBreak::Hash, TieBreak::Random(7)] {
+            let mut ties = Ties::new(rule);
+            for turn in 0..20 {
+                assert_eq!(ties.best(&scored, turn), Some((9, (1, 1))), "{:?} on turn {}", rule, turn);
+            }
+        }
+    }
+}
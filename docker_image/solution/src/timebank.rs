@@ -0,0 +1,113 @@
+// src/timebank.rs
+// Where the anytime search's clock goes over a game. Opening turns are
+// races into open space, and a shallow search plays them as well as a deep
+// one, so they only get a share of the per-turn budget and the rest is
+// banked. The first few turns in contact decide how the board gets divided,
+// and they draw on the bank on top of their own budget, spread evenly over
+// them. Whatever's left after that stays unspent: later turns get the plain
+// budget.
+//
+// The bank is per game, so it is built from that map's own opening: a big
+// map with a long race banks more than a small one where contact comes at
+// once.
+
+use std::time::Duration;
+
+use crate::game::Phase;
+
+#[derive(Clone, Copy, Debug)]
+pub struct BankConfig {
+    /// Share of the budget an opening turn may use; the rest is banked.
+    pub opening_share: f64,
+    /// Contact turns the bank is spread over; 0 turns banking off.
+    pub contact_turns: usize,
+    /// A contact turn gets at most this many times the budget.
+    pub max_factor: f64,
+}
+
+impl Default for BankConfig {
+    fn default() -> Self {
+        BankConfig {
+            opening_share: 0.25,
+            contact_turns: 4,
+            max_factor: 1.8,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct TimeBank {
+    pub config: BankConfig,
+    banked: Duration,
+    /// Drawn from the bank so far.
+    drawn: Duration,
+    /// Contact turns played so far.
+    contact: usize,
+    /// Turns and time used per phase, in the order of `Phase::ALL`.
+    used: [(usize, Duration); 3],
+}
+
+impl TimeBank {
+    pub fn new(config: BankConfig) -> Self {
+        TimeBank {
+            config,
+            ..TimeBank::default()
+        }
+    }
+
+    /// The time to give a turn in `phase` when the plain budget is `base`.
+    pub fn budget(&self, base: Duration, phase: Phase) -> Duration {
+        if self.config.contact_turns == 0 {
+            return base;
+        }
+        match phase {
+            Phase::Opening => base.mul_f64(self.config.opening_share),
+            Phase::Midgame if self.contact < self.config.contact_turns => {
+                let share = self.banked / (self.config.contact_turns - self.contact) as u32;
+                base + share.min(base.mul_f64(self.config.max_factor - 1.0))
+            }
+            _ => base,
+        }
+    }
+
+    /// Record that a turn in `phase` took `used` against the plain budget
+    /// `base`: bank what an opening turn saved, draw what a contact turn
+    /// went over.
+    pub fn settle(&mut self, base: Duration, phase: Phase, used: Duration) {
+        let index = Phase::ALL.iter().position(|&p| p == phase).unwrap_or(0);
+        if let Some((turns, total)) = self.used.get_mut(index) {
+            *turns += 1;
+            *total += used;
+        }
+        if self.config.contact_turns == 0 {
+            return;
+        }
+        match phase {
+            Phase::Opening => self.banked += base.saturating_sub(used),
+            Phase::Midgame if self.contact < self.config.contact_turns => {
+                let over = used.saturating_sub(base).min(self.banked);
+                self.banked -= over;
+                self.drawn += over;
+                self.contact += 1;
+            }
+            _ => {}
+        }
+    }
+
+    /// Time used per phase and what went through the bank, for the
+    /// post-game report.
+    pub fn summary(&self) -> String {
+        let phases: Vec<String> = Phase::ALL
+            .iter()
+            .zip(&self.used)
+            .filter(|(_, (turns, _))| *turns > 0)
+            .map(|(phase, (turns, used))| format!("{} {} turns {:.2?}", phase.name(), turns, used))
+            .collect();
+        format!(
+            "{}; banked {:.2?}, drew {:.2?}",
+            phases.join(", "),
+            self.banked + self.drawn,
+            self.drawn
+        )
+    }
+}
@@ -0,0 +1,140 @@
+// src/transposition.rs
+// Position scores remembered across the branches of a lookahead search.
+// Beam lines often reach the same board by different move orders, and the
+// anytime search re-walks the shallow plies on every pass; with the table
+// each of those boards is scored once. Boards are keyed by their Zobrist
+// hash (see `Board`), which placing a piece keeps up to date.
+//
+// The table is a fixed number of slots, a board going to slot key % size.
+// When two boards want the same slot the replacement policy decides:
+// `always` keeps the newest, `shallower` (the default) keeps the one found
+// fewer plies from the root, since that's the one the next pass will reach
+// again. Entries from an earlier turn always give way.
+
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Replace {
+    /// The newest board takes the slot.
+    Always,
+    /// The board nearer the root keeps it.
+    #[default]
+    Shallower,
+}
+
+impl FromStr for Replace {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "always" => Ok(Replace::Always),
+            "shallower" => Ok(Replace::Shallower),
+            other => Err(format!("unknown replacement policy `{}`", other)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TableConfig {
+    /// Slots in the table; 0 turns it off.
+    pub size: usize,
+    pub replace: Replace,
+}
+
+impl Default for TableConfig {
+    fn default() -> Self {
+        TableConfig {
+            size: 1 << 16,
+            replace: Replace::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Entry {
+    key: u64,
+    /// Plies from the root it was found at.
+    ply: usize,
+    /// The turn it was stored on.
+    generation: usize,
+    score: f64,
+}
+
+pub struct Table {
+    config: TableConfig,
+    slots: Vec<Option<Entry>>,
+    generation: usize,
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl Table {
+    pub fn new(config: TableConfig) -> Self {
+        Table {
+            config,
+            // Allocated on first use, so a strategy that never searches
+            // doesn't pay for it
+            slots: Vec::new(),
+            generation: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Start a new turn: what's stored so far may be replaced freely.
+    pub fn next_turn(&mut self) {
+        self.generation += 1;
+        self.hits = 0;
+        self.misses = 0;
+    }
+
+    pub fn get(&mut self, key: u64) -> Option<f64> {
+        let found = self
+            .slot(key)
+            .and_then(|i| self.slots.get(i).copied().flatten())
+            .filter(|entry| entry.key == key);
+        match found {
+            Some(entry) => {
+                self.hits += 1;
+                Some(entry.score)
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub fn put(&mut self, key: u64, ply: usize, score: f64) {
+        let Some(i) = self.slot(key) else {
+            return;
+        };
+        let entry = Entry {
+            key,
+            ply,
+            generation: self.generation,
+            score,
+        };
+        let replace = self.config.replace;
+        let Some(slot) = self.slots.get_mut(i) else {
+            return;
+        };
+        let keep = match (*slot, replace) {
+            (Some(old), Replace::Shallower) => old.generation == entry.generation && old.ply < ply,
+            _ => false,
+        };
+        if !keep {
+            *slot = Some(entry);
+        }
+    }
+
+    fn slot(&mut self, key: u64) -> Option<usize> {
+        if self.config.size == 0 {
+            return None;
+        }
+        if self.slots.is_empty() {
+            self.slots = vec![None; self.config.size];
+        }
+        Some((key % self.config.size as u64) as usize)
+    }
+}
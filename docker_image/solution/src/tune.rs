@@ -0,0 +1,272 @@
+// src/tune.rs
+// `filler tune`: evolve the heuristic's Weights by self-play on the local
+// referee. A candidate's fitness is its score rate against the starting
+// weights over a fixed set of games, so every candidate faces the same
+// pieces and the comparison is fair.
+//
+// Two searches are available. The default is a plain genetic algorithm:
+// elitism, tournament selection, uniform crossover and multiplicative
+// mutation. `--method cmaes` runs CMA-ES (see cmaes.rs) on the log2 scale of
+// each weight instead, checkpointing after every generation so a long run
+// can be resumed.
+
+use std::fs;
+use std::path::Path;
+
+use crate::cmaes::Cmaes;
+use crate::game::{Game, Strategy};
+use crate::referee::play_game;
+use crate::rng::Rng;
+use crate::weights::Weights;
+
+const USAGE: &str = "usage: filler tune <map>... [--method ga|cmaes] [--population N] [--generations N] \
+                     [--games N] [--seed N] [--out FILE] [--checkpoint FILE]";
+
+/// Initial CMA-ES step size, in doublings of each weight.
+const CMAES_SIGMA: f64 = 0.5;
+
+/// Candidates carried over unchanged into the next generation.
+const ELITE: usize = 2;
+/// Chance of mutating each weight of a child.
+const MUTATION_RATE: f64 = 0.2;
+
+/// The games every candidate is judged on.
+struct Benchmark {
+    maps: Vec<Vec<String>>,
+    /// Piece sequences per map. Each is played from both sides, since who
+    /// wins a given sequence depends heavily on the side.
+    games: usize,
+    seed: u64,
+}
+
+impl Benchmark {
+    /// Share of the available points `candidate` takes off `baseline`:
+    /// a win is 1, a tie 0.5.
+    fn fitness(&self, candidate: &Weights, baseline: &Weights) -> f64 {
+        let mut points = 0.0;
+        let mut played = 0;
+
+        for map in &self.maps {
+            for (g, candidate_side) in (0..self.games).flat_map(|g| [(g, 0), (g, 1)]) {
+                let mut players = [1u8, 2].map(|p| {
+                    let weights = if p as usize - 1 == candidate_side { candidate } else { baseline };
+                    Game::new(p, Strategy::Heuristic).with_weights(*weights)
+                });
+                let Ok(scores) = play_game(map, self.seed + g as u64, &mut players) else {
+                    continue;
+                };
+
+                let [first, second] = scores;
+                let (mine, theirs) = if candidate_side == 0 { (first, second) } else { (second, first) };
+                points += match mine.cmp(&theirs) {
+                    std::cmp::Ordering::Greater => 1.0,
+                    std::cmp::Ordering::Equal => 0.5,
+                    std::cmp::Ordering::Less => 0.0,
+                };
+                played += 1;
+            }
+        }
+
+        if played == 0 {
+            0.0
+        } else {
+            points / played as f64
+        }
+    }
+}
+
+pub fn run_cli(args: &[String]) -> Result<(), String> {
+    let mut map_paths: Vec<&str> = Vec::new();
+    let mut population = 12usize;
+    let mut generations = 10usize;
+    let mut games = 4usize;
+    let mut seed: u64 = 1;
+    let mut out = "tuned_weights.toml".to_string();
+    let mut method = "ga".to_string();
+    let mut checkpoint = "tune.checkpoint".to_string();
+
+    let mut i = 0;
+    while let Some(arg) = args.get(i) {
+        let value = args.get(i + 1);
+        let number = |name: &str| -> Result<u64, String> {
+            value
+                .and_then(|v| v.parse().ok())
+                .ok_or(format!("{} expects a number", name))
+        };
+        match arg.as_str() {
+            "--population" => population = number("--population")?.max(ELITE as u64 + 1) as usize,
+            "--generations" => generations = number("--generations")? as usize,
+            "--games" => games = number("--games")?.max(1) as usize,
+            "--seed" => seed = number("--seed")?,
+            "--out" => out = value.cloned().ok_or("--out expects a path")?,
+            "--method" => method = value.cloned().ok_or("--method expects ga or cmaes")?,
+            "--checkpoint" => checkpoint = value.cloned().ok_or("--checkpoint expects a path")?,
+            other => {
+                map_paths.push(other);
+                i += 1;
+                continue;
+            }
+        }
+        i += 2;
+    }
+
+    if map_paths.is_empty() {
+        return Err(USAGE.to_string());
+    }
+    let maps = map_paths
+        .iter()
+        .map(|path| {
+            fs::read_to_string(path)
+                .map(|text| text.lines().map(|l| l.to_string()).collect())
+                .map_err(|e| format!("{}: {}", path, e))
+        })
+        .collect::<Result<Vec<Vec<String>>, String>>()?;
+
+    let bench = Benchmark { maps, games, seed };
+    let baseline = crate::config::Config::from_env().weights;
+    let best = match method.as_str() {
+        "ga" => evolve(&bench, &baseline, population, generations, seed, &out)?,
+        "cmaes" => cmaes(&bench, &baseline, generations, seed, &out, &checkpoint)?,
+        other => return Err(format!("unknown method `{}`\n{}", other, USAGE)),
+    };
+    println!("best fitness {:.3}, written to {}", bench.fitness(&best, &baseline), out);
+    Ok(())
+}
+
+fn evolve(
+    bench: &Benchmark,
+    baseline: &Weights,
+    population: usize,
+    generations: usize,
+    seed: u64,
+    out: &str,
+) -> Result<Weights, String> {
+    let mut rng = Rng::new(seed);
+    let genes = baseline.values();
+
+    let mut pool: Vec<Vec<i64>> = vec![genes.clone()];
+    while pool.len() < population {
+        pool.push(mutate(&genes, &mut rng));
+    }
+
+    let mut best = genes;
+    for generation in 0..generations {
+        let mut ranked: Vec<(f64, Vec<i64>)> = pool
+            .into_iter()
+            .map(|g| (bench.fitness(&baseline.with_values(&g), baseline), g))
+            .collect();
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let Some((top, genes)) = ranked.first() else {
+            break;
+        };
+        best = genes.clone();
+        baseline
+            .with_values(&best)
+            .save(out)
+            .map_err(|e| format!("{}: {}", out, e))?;
+        println!(
+            "generation {}: best {:.3}, median {:.3}",
+            generation + 1,
+            top,
+            ranked.get(ranked.len() / 2).map_or(*top, |(median, _)| *median)
+        );
+
+        pool = ranked.iter().take(ELITE).map(|(_, g)| g.clone()).collect();
+        while pool.len() < population {
+            let a = tournament(&ranked, &mut rng);
+            let b = tournament(&ranked, &mut rng);
+            let child = crossover(a, b, &mut rng);
+            pool.push(mutate(&child, &mut rng));
+        }
+    }
+
+    Ok(baseline.with_values(&best))
+}
+
+/// CMA-ES over x, where each weight is `base * 2^x` (or `100 * x` for
+/// weights that start at zero). Resumes from `checkpoint` if it exists;
+/// `generations` counts the generations already done there.
+fn cmaes(
+    bench: &Benchmark,
+    baseline: &Weights,
+    generations: usize,
+    seed: u64,
+    out: &str,
+    checkpoint: &str,
+) -> Result<Weights, String> {
+    let base = baseline.values();
+    let decode = |x: &[f64]| -> Weights {
+        let values: Vec<i64> = base
+            .iter()
+            .zip(x)
+            .map(|(&b, &x)| {
+                if b == 0 {
+                    (100.0 * x).max(0.0).round() as i64
+                } else {
+                    (b as f64 * x.clamp(-30.0, 30.0).exp2()).round() as i64
+                }
+            })
+            .collect();
+        baseline.with_values(&values)
+    };
+
+    let mut search = if Path::new(checkpoint).exists() {
+        let search = Cmaes::load(checkpoint)?;
+        println!("resuming from {} at generation {}", checkpoint, search.generation);
+        search
+    } else {
+        Cmaes::new(vec![0.0; base.len()], CMAES_SIGMA, seed)
+    };
+
+    while search.generation < generations {
+        let points = search.ask();
+        let fitness: Vec<f64> = points
+            .iter()
+            .map(|x| bench.fitness(&decode(x), baseline))
+            .collect();
+        search.tell(&points, &fitness);
+        search.save(checkpoint)?;
+
+        if let Some((best_fitness, best)) = &search.best {
+            decode(best).save(out).map_err(|e| format!("{}: {}", out, e))?;
+            println!("generation {}: best {:.3}", search.generation, best_fitness);
+        }
+    }
+
+    Ok(match &search.best {
+        Some((_, best)) => decode(best),
+        None => *baseline,
+    })
+}
+
+/// The fitter of three random candidates.
+fn tournament<'a>(ranked: &'a [(f64, Vec<i64>)], rng: &mut Rng) -> &'a [i64] {
+    // `ranked` is sorted best first, so the lowest index wins
+    let pick = (0..3).map(|_| rng.below(ranked.len())).min().unwrap_or(0);
+    ranked.get(pick).map_or(&[], |(_, genes)| genes)
+}
+
+fn crossover(a: &[i64], b: &[i64], rng: &mut Rng) -> Vec<i64> {
+    a.iter()
+        .zip(b)
+        .map(|(&x, &y)| if rng.below(2) == 0 { x } else { y })
+        .collect()
+}
+
+/// Scale some weights by a random factor between 1/2 and 2. Zeros get a
+/// small value instead, so switched-off features can be switched back on.
+fn mutate(genes: &[i64], rng: &mut Rng) -> Vec<i64> {
+    genes
+        .iter()
+        .map(|&g| {
+            if rng.unit() >= MUTATION_RATE {
+                g
+            } else if g == 0 {
+                rng.below(100) as i64
+            } else {
+                (g as f64 * 2f64.powf(rng.unit() * 2.0 - 1.0)).round() as i64
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,518 @@
+// src/weights.rs
+// Every tunable constant of the heuristic scorer, with the compiled-in
+// defaults. A weights file (FILLER_WEIGHTS) overrides any of them at startup
+// so the bot can be tuned without a rebuild. The format is a small TOML
+// subset, the same one `filler strategies` prints:
+//
+//     [midgame]
+//     territory = 2500
+//     adjacency = 40000
+//
+//     [packing]
+//     hole = 500
+//
+// Sections are `opening`, `contact` (or `midgame`), `endgame` and `packing`.
+// Keys that aren't listed keep their defaults; unknown ones are an error.
+
+use std::fs;
+use std::io;
+
+use crate::game::Phase;
+
+/// How much each scoring feature counts for in a given phase.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PhaseWeights {
+    /// Per point of `closeness()` to the nearest enemy cell.
+    pub closeness: i64,
+    /// Per step the placement closes on the enemy.
+    pub distance_reduction: i64,
+    /// Per step of advance toward the target direction.
+    pub advance: i64,
+    /// Per contested cell claimed.
+    pub territory: i64,
+    /// Per cell the placement adds to our safe area over theirs (see
+    /// `analysis::safe_areas`).
+    pub safe_area: i64,
+    /// Per empty cell the opponent reached first before the placement and
+    /// we reach first after it: what a block takes from them.
+    pub denial: i64,
+    /// Penalty per cell of our territory the placement leaves cut off from
+    /// its largest part (see `analysis::detached_territory`), less any it
+    /// joins back up.
+    pub fragment: i64,
+    /// Penalty per new cell left sticking out into open space the opponent
+    /// reaches first, for their pieces to wrap around (see `game::gifted`).
+    pub gift: i64,
+    /// Per piece-cell edge touching the enemy.
+    pub adjacency: i64,
+    /// Penalty per cell spent inside a dead zone.
+    pub dead_cell: i64,
+    /// Per cell sealed off from the enemy through a chokepoint.
+    pub cut_off: i64,
+    /// Per enemy anchor cell left with no empty neighbour.
+    pub mobility: i64,
+    /// Per enemy live anchor (see `analysis::live_anchors`) knocked out.
+    pub anchor_pressure: i64,
+    /// Per anchor cell of our own gained (or lost, if negative).
+    pub self_mobility: i64,
+    /// Penalty per step behind the front line, summed over piece cells.
+    pub waste: i64,
+    /// Penalty per unit of ragged frontier added (see `Game::raggedness`).
+    pub compactness: i64,
+    /// Penalty per cell we expect to waste in pockets walled off behind our
+    /// own cells (see `Game::pocket_waste`).
+    pub pocket: i64,
+    /// Penalty per new cell in a region the race has already settled (see
+    /// `game::RACE_MARGIN`), instead of counting it as territory.
+    pub settled: i64,
+    /// Per side of a new cell against the board edge or an edge-anchored
+    /// cell of ours.
+    pub wall_hug: i64,
+    /// Per thousandth of influence gained (see `analysis::influence_map`).
+    pub influence: i64,
+    /// Penalty per step away from the targeted enemy cell: the closest one,
+    /// led by the opponent model (see opponent.rs).
+    pub target_drift: i64,
+    /// Penalty per step away from the contested midline, on the first
+    /// `game::MIDLINE_TURNS` turns only.
+    pub midline: i64,
+    /// Multiplier on the tactical pattern bonuses (see patterns.rs).
+    pub tactics: i64,
+    /// Per midline cell we'd reach first, while the position is still symmetric
+    /// between the sides (see symmetry.rs).
+    pub symmetry_break: i64,
+    /// Per fogged cell a placement would bring into view (see fog.rs).
+    pub explore: i64,
+    /// Per cell of wall across a rushing opponent's approach. While they
+    /// rush, the head-on terms (closeness, distance_reduction, advance) are
+    /// off.
+    pub defense: i64,
+    /// Per point of `closeness()` to each of several enemy fronts, scaled by
+    /// that front's share of what they threaten to take (see
+    /// `analysis::fronts`).
+    pub fronts: i64,
+    /// Per piece cell on the planned corridor to them, less per step off it,
+    /// while rushing (see `analysis::corridor`).
+    pub corridor: i64,
+    /// Per cell a placement seals off for us by splitting the empty region
+    /// between us (see `analysis::sealed_by`).
+    pub split: i64,
+    /// Penalty per step away from the opponent's latest placement, as the
+    /// referee marks it (see `Board::latest`).
+    pub latest: i64,
+    /// Penalty per step the overlapped cell of ours lies behind our front
+    /// toward them: growing from deep inside leaves forward anchors unused.
+    pub anchor: i64,
+    /// Per percent a placement serves the turn's objective (see
+    /// objective.rs).
+    pub objective: i64,
+    /// Per new cell placed where the piece's shape does the most good: a
+    /// line on the front, a scatter out in open space, a blob in a
+    /// chokepoint (see `game::shape_fit`).
+    pub shape: i64,
+    /// Per empty cell the placement brings within one more average piece's
+    /// reach, that none of our cells reached before (see
+    /// `analysis::placeable_next`): room to follow it up next turn.
+    pub follow_up: i64,
+}
+
+const OPENING_WEIGHTS: PhaseWeights = PhaseWeights {
+    closeness: 100,           // Getting close is everything
+    distance_reduction: 50000,
+    advance: 1000,
+    territory: 10,            // Territory is almost irrelevant
+    safe_area: 0,
+    denial: 0,                // Nothing to take from them before contact
+    fragment: 0,
+    gift: 0,                  // Nothing of theirs to wrap around us yet
+    adjacency: 100000,        // If we can touch enemy, amazing!
+    dead_cell: 500,
+    cut_off: 20000,           // Plug the corridors they'd run through
+    mobility: 20000,
+    anchor_pressure: 0,
+    self_mobility: 500,
+    waste: 200,
+    compactness: 0,           // Racing lines are ragged by nature
+    pocket: 500,
+    settled: 0,
+    wall_hug: 300,            // Edge-anchored walls are hard to flank
+    influence: 2,
+    target_drift: 0,
+    midline: 5000,            // Reach the split line before they do
+    tactics: 0,
+    symmetry_break: 2000,     // Break a mirrored start in our favour
+    explore: 300,             // Find them under fog before they find us
+    defense: 20000,           // Meet a rush with a wall, not a collision
+    fronts: 0,
+    corridor: 3000,           // Rush along the real way there, round walls
+    split: 20000,
+    latest: 0,                // Their whole blob is the target for now
+    anchor: 500,              // Push off the leading edge
+    objective: 100,
+    shape: 300,               // Scatters stake out the open board
+    follow_up: 200,           // Leave the next piece somewhere to go
+};
+
+const MIDGAME_WEIGHTS: PhaseWeights = PhaseWeights {
+    closeness: 5,
+    distance_reduction: 0,
+    advance: 500,
+    territory: 2000,          // Now territory matters
+    safe_area: 3000,          // What we can count on, ahead of what we touch
+    denial: 2000,             // A block is worth the cells it takes from them
+    fragment: 1500,           // A split blob gets contained piece by piece
+    gift: 1000,               // A lone spur in their space gets cut off
+    adjacency: 50000,         // Stay glued to enemy
+    dead_cell: 1000,          // Safe pockets can wait
+    cut_off: 5000,            // Sealing a corridor beats hugging them
+    mobility: 30000,          // Smother their anchors
+    anchor_pressure: 3000,    // Block mode: drive their live anchors to zero
+    self_mobility: 3000,      // ...without walling ourselves in
+    waste: 300,
+    compactness: 10,          // A tie-breaker: more loses races to the middle
+    pocket: 1000,
+    settled: 1000,            // A region we reach well first is ours anyway
+    wall_hug: 0,
+    influence: 5,
+    target_drift: 100,
+    midline: 0,
+    tactics: 1,               // Contact is where the local tricks live
+    symmetry_break: 0,
+    explore: 100,
+    defense: 5000,
+    fronts: 5,                // Block the front with the most at stake
+    corridor: 0,
+    split: 8000,              // Splitting the board off is the winning move
+    latest: 100,              // Block where they're pushing right now
+    anchor: 300,
+    objective: 100,           // About a contested cell at full service
+    shape: 1500,              // Lines make walls, blobs make plugs
+    follow_up: 300,
+};
+
+const ENDGAME_WEIGHTS: PhaseWeights = PhaseWeights {
+    closeness: 2,
+    distance_reduction: 0,
+    advance: 0,
+    territory: 4000,          // Every contested cell counts now
+    safe_area: 5000,
+    denial: 3000,
+    fragment: 2000,
+    gift: 500,
+    adjacency: 20000,
+    dead_cell: 200,
+    cut_off: 8000,
+    mobility: 10000,
+    anchor_pressure: 0,
+    self_mobility: 1000,
+    waste: 100,
+    compactness: 200,
+    pocket: 1000,
+    settled: 500,
+    wall_hug: 0,
+    influence: 5,
+    target_drift: 50,
+    midline: 0,
+    tactics: 0,
+    symmetry_break: 0,
+    explore: 0,
+    defense: 0,
+    fronts: 2,
+    corridor: 0,
+    split: 8000,
+    latest: 50,
+    anchor: 0,                // Any anchor that still fits will do
+    objective: 50,
+    shape: 500,
+    follow_up: 100,           // Little room left to follow anything up
+};
+
+/// Endgame packing, once the opponent is sealed off.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PackingWeights {
+    /// Per empty cell covered.
+    pub new_cell: i64,
+    /// Per piece-cell edge against a wall, the edge or a taken cell.
+    pub contact: i64,
+    /// Penalty per single-cell hole left behind.
+    pub hole: i64,
+}
+
+const PACKING_WEIGHTS: PackingWeights = PackingWeights {
+    new_cell: 1000,
+    contact: 10,
+    hole: 300,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Weights {
+    pub opening: PhaseWeights,
+    pub midgame: PhaseWeights,
+    pub endgame: PhaseWeights,
+    pub packing: PackingWeights,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Weights {
+            opening: OPENING_WEIGHTS,
+            midgame: MIDGAME_WEIGHTS,
+            endgame: ENDGAME_WEIGHTS,
+            packing: PACKING_WEIGHTS,
+        }
+    }
+}
+
+type Field<T> = (&'static str, fn(&mut T) -> &mut i64);
+
+const PHASE_FIELDS: &[Field<PhaseWeights>] = &[
+    ("closeness", |w| &mut w.closeness),
+    ("distance_reduction", |w| &mut w.distance_reduction),
+    ("advance", |w| &mut w.advance),
+    ("territory", |w| &mut w.territory),
+    ("safe_area", |w| &mut w.safe_area),
+    ("denial", |w| &mut w.denial),
+    ("fragment", |w| &mut w.fragment),
+    ("gift", |w| &mut w.gift),
+    ("adjacency", |w| &mut w.adjacency),
+    ("dead_cell", |w| &mut w.dead_cell),
+    ("cut_off", |w| &mut w.cut_off),
+    ("mobility", |w| &mut w.mobility),
+    ("anchor_pressure", |w| &mut w.anchor_pressure),
+    ("self_mobility", |w| &mut w.self_mobility),
+    ("waste", |w| &mut w.waste),
+    ("compactness", |w| &mut w.compactness),
+    ("pocket", |w| &mut w.pocket),
+    ("settled", |w| &mut w.settled),
+    ("wall_hug", |w| &mut w.wall_hug),
+    ("influence", |w| &mut w.influence),
+    ("target_drift", |w| &mut w.target_drift),
+    ("midline", |w| &mut w.midline),
+    ("tactics", |w| &mut w.tactics),
+    ("symmetry_break", |w| &mut w.symmetry_break),
+    ("explore", |w| &mut w.explore),
+    ("defense", |w| &mut w.defense),
+    ("fronts", |w| &mut w.fronts),
+    ("corridor", |w| &mut w.corridor),
+    ("split", |w| &mut w.split),
+    ("latest", |w| &mut w.latest),
+    ("anchor", |w| &mut w.anchor),
+    ("objective", |w| &mut w.objective),
+    ("shape", |w| &mut w.shape),
+    ("follow_up", |w| &mut w.follow_up),
+];
+
+/// Terms that go after the opponent, and terms that grow our own area.
+/// The aggression dial trades one set against the other; the rest are
+/// housekeeping penalties it leaves alone.
+const BLOCKING: &[&str] = &[
+    "closeness",
+    "distance_reduction",
+    "advance",
+    "adjacency",
+    "denial",
+    "cut_off",
+    "split",
+    "latest",
+    "anchor",
+    "mobility",
+    "anchor_pressure",
+    "target_drift",
+    "midline",
+    "tactics",
+];
+const EXPANSION: &[&str] = &["territory", "safe_area", "self_mobility", "settled", "influence", "wall_hug", "explore", "follow_up"];
+
+/// The dial setting that leaves the weights as they are.
+pub const NEUTRAL_AGGRESSION: f32 = 0.5;
+
+const PACKING_FIELDS: &[Field<PackingWeights>] = &[
+    ("new_cell", |w| &mut w.new_cell),
+    ("contact", |w| &mut w.contact),
+    ("hole", |w| &mut w.hole),
+];
+
+impl PhaseWeights {
+    /// Every weight set to `value`. At 1, `Game::score_terms` reports each
+    /// feature's raw value, which is what the replay trainer fits.
+    pub fn uniform(value: i64) -> PhaseWeights {
+        let mut weights = OPENING_WEIGHTS;
+        for (_, get) in PHASE_FIELDS {
+            *get(&mut weights) = value;
+        }
+        weights
+    }
+
+    /// The weight called `key`, as in the weights file.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut i64> {
+        field(PHASE_FIELDS, self, key)
+    }
+}
+
+impl Weights {
+    pub fn phase(&self, phase: Phase) -> &PhaseWeights {
+        match phase {
+            Phase::Opening => &self.opening,
+            Phase::Midgame => &self.midgame,
+            Phase::Endgame => &self.endgame,
+        }
+    }
+
+    pub fn phase_mut(&mut self, phase: Phase) -> &mut PhaseWeights {
+        match phase {
+            Phase::Opening => &mut self.opening,
+            Phase::Midgame => &mut self.midgame,
+            Phase::Endgame => &mut self.endgame,
+        }
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        fs::write(path, self.toml_lines().join("\n") + "\n")
+    }
+
+    /// The defaults with whatever `path` overrides.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        Weights::parse(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut weights = Weights::default();
+        let mut section: Option<String> = None;
+
+        for (n, raw) in text.lines().enumerate() {
+            let line = raw.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = Some(name.trim().to_string());
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected `key = value`", n + 1))?;
+            let (key, value) = (key.trim(), value.trim().replace('_', ""));
+            let value: i64 = value
+                .parse()
+                .map_err(|_| format!("line {}: `{}` is not an integer", n + 1, value))?;
+
+            let slot = match section.as_deref() {
+                Some("opening") => field(PHASE_FIELDS, &mut weights.opening, key),
+                Some("contact") | Some("midgame") => field(PHASE_FIELDS, &mut weights.midgame, key),
+                Some("endgame") => field(PHASE_FIELDS, &mut weights.endgame, key),
+                Some("packing") => field(PACKING_FIELDS, &mut weights.packing, key),
+                Some(other) => return Err(format!("line {}: unknown section [{}]", n + 1, other)),
+                None => return Err(format!("line {}: `{}` is outside any section", n + 1, key)),
+            };
+            *slot.ok_or_else(|| format!("line {}: unknown key `{}`", n + 1, key))? = value;
+        }
+
+        Ok(weights)
+    }
+
+    /// Every weight as one flat vector, in a fixed order. For the tuner.
+    pub fn values(&self) -> Vec<i64> {
+        let mut copy = *self;
+        let mut values = Vec::new();
+        for phase in [&mut copy.opening, &mut copy.midgame, &mut copy.endgame] {
+            values.extend(PHASE_FIELDS.iter().map(|(_, get)| *get(phase)));
+        }
+        values.extend(PACKING_FIELDS.iter().map(|(_, get)| *get(&mut copy.packing)));
+        values
+    }
+
+    /// These weights with the first `values.len()` of them replaced, in the
+    /// order `values` returns them.
+    pub fn with_values(&self, values: &[i64]) -> Weights {
+        let mut weights = *self;
+        let mut values = values.iter();
+        for phase in [&mut weights.opening, &mut weights.midgame, &mut weights.endgame] {
+            for (_, get) in PHASE_FIELDS {
+                if let Some(&v) = values.next() {
+                    *get(phase) = v;
+                }
+            }
+        }
+        for (_, get) in PACKING_FIELDS {
+            if let Some(&v) = values.next() {
+                *get(&mut weights.packing) = v;
+            }
+        }
+        weights
+    }
+
+    /// These weights turned toward blocking (`aggression` 1: expansion terms
+    /// off, blocking terms doubled) or expansion (0: the other way round).
+    /// `NEUTRAL_AGGRESSION` changes nothing.
+    pub fn with_aggression(&self, aggression: f32) -> Weights {
+        let aggression = aggression.clamp(0.0, 1.0) as f64;
+        let scale = |value: i64, factor: f64| (value as f64 * factor).round() as i64;
+        let mut weights = *self;
+        for phase in [&mut weights.opening, &mut weights.midgame, &mut weights.endgame] {
+            for (name, get) in PHASE_FIELDS {
+                if BLOCKING.contains(name) {
+                    *get(phase) = scale(*get(phase), 2.0 * aggression);
+                } else if EXPANSION.contains(name) {
+                    *get(phase) = scale(*get(phase), 2.0 * (1.0 - aggression));
+                }
+            }
+        }
+        weights
+    }
+
+    /// Every weight that differs from the defaults, as `section.key`.
+    pub fn overrides(&self) -> Vec<(String, i64)> {
+        let defaults = Weights::default();
+        let mut changed = Vec::new();
+        for phase in Phase::ALL {
+            let (mine, base) = (*self.phase(phase), *defaults.phase(phase));
+            for ((key, value), (_, default)) in named(PHASE_FIELDS, mine).into_iter().zip(named(PHASE_FIELDS, base)) {
+                if value != default {
+                    changed.push((format!("{}.{}", phase.name(), key), value));
+                }
+            }
+        }
+        for ((key, value), (_, default)) in named(PACKING_FIELDS, self.packing)
+            .into_iter()
+            .zip(named(PACKING_FIELDS, defaults.packing))
+        {
+            if value != default {
+                changed.push((format!("packing.{}", key), value));
+            }
+        }
+        changed
+    }
+
+    /// The weights in the format `parse` reads.
+    pub fn toml_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for phase in Phase::ALL {
+            lines.push(format!("[{}]", phase.name()));
+            lines.extend(dump(PHASE_FIELDS, *self.phase(phase)));
+            lines.push(String::new());
+        }
+        lines.push("[packing]".to_string());
+        lines.extend(dump(PACKING_FIELDS, self.packing));
+        lines
+    }
+}
+
+fn field<'a, T>(fields: &[Field<T>], weights: &'a mut T, key: &str) -> Option<&'a mut i64> {
+    fields
+        .iter()
+        .find(|(name, _)| *name == key)
+        .map(|(_, get)| get(weights))
+}
+
+fn named<T>(fields: &[Field<T>], mut weights: T) -> Vec<(&'static str, i64)> {
+    fields.iter().map(|(name, get)| (*name, *get(&mut weights))).collect()
+}
+
+fn dump<T>(fields: &[Field<T>], weights: T) -> Vec<String> {
+    named(fields, weights)
+        .into_iter()
+        .map(|(name, value)| format!("{} = {}", name, value))
+        .collect()
+}
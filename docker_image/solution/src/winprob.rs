@@ -0,0 +1,64 @@
+// src/winprob.rs
+// A small logistic model estimating our chance of winning from the current
+// board, printed every turn so it's easy to spot where a game swings.
+//
+// The weights are fitted from engine transcripts with `filler fit --winprob
+// --epochs 20000` (see fit.rs): 24 games, the bot against each bundled
+// robot on map00, map01 and map02 from both seats, 16278 turns. Log loss
+// went from 0.0536 under the earlier hand-set weights to 0.0450. We won 23
+// of those games, so refit once there are closer games to learn from.
+
+use crate::analysis;
+use crate::board::{Board, Owner};
+
+pub struct Features {
+    /// (my cells - their cells) / board size
+    pub cell_diff: f64,
+    /// (cells I reach first - cells they reach first) / board size
+    pub reach_diff: f64,
+    /// Fraction of the board that is already taken, 0.0 to 1.0.
+    pub phase: f64,
+}
+
+/// Weights of `Features::terms`: the bias, the cell lead, the reach lead,
+/// and extra weight on the cell lead as the board fills up.
+pub const WEIGHTS: [f64; 4] = [0.33, 1.05, 10.50, 2.94];
+
+impl Features {
+    pub fn from_board(board: &Board) -> Self {
+        let size = (board.rows() * board.cols()).max(1) as f64;
+
+        let mut mine = 0usize;
+        let mut theirs = 0usize;
+        for (_, owner) in board.iter() {
+            match owner {
+                Owner::Me => mine += 1,
+                Owner::Opponent => theirs += 1,
+                Owner::Empty | Owner::Unknown => {}
+            }
+        }
+
+        let (my_area, their_area) = analysis::voronoi_areas(board);
+
+        Features {
+            cell_diff: (mine as f64 - theirs as f64) / size,
+            reach_diff: (my_area as f64 - their_area as f64) / size,
+            phase: (mine + theirs) as f64 / size,
+        }
+    }
+
+    /// What `WEIGHTS` apply to, in the same order.
+    pub fn terms(&self) -> [f64; 4] {
+        [1.0, self.cell_diff, self.reach_diff, self.phase * self.cell_diff]
+    }
+
+    pub fn win_probability(&self) -> f64 {
+        logistic(&WEIGHTS, &self.terms())
+    }
+}
+
+/// The chance of a win under `weights` for these terms.
+pub fn logistic(weights: &[f64; 4], terms: &[f64; 4]) -> f64 {
+    let z: f64 = weights.iter().zip(terms).map(|(w, t)| w * t).sum();
+    1.0 / (1.0 + (-z).exp())
+}